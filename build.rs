@@ -0,0 +1,31 @@
+// With the `spirv` feature, compiles every shader in `shaders/` to SPIR-V for `include_spirv!`.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+  println!("cargo:rerun-if-changed=shaders");
+  if env::var_os("CARGO_FEATURE_SPIRV").is_none() {
+    return;
+  }
+
+  let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("shaders");
+  fs::create_dir_all(&out_dir).unwrap();
+
+  for entry in fs::read_dir("shaders").unwrap() {
+    let path = entry.unwrap().path();
+    let name = path.file_name().unwrap().to_str().unwrap();
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    // -G targets OpenGL SPIR-V; plain uniforms get their locations assigned automatically.
+    let status = Command::new("glslangValidator")
+      .args(&["-G", "--auto-map-locations", "--auto-map-bindings", "-o"])
+      .arg(out_dir.join(format!("{}.spv", name)))
+      .arg(&path)
+      .status()
+      .expect("failed to run glslangValidator, is it installed?");
+    assert!(status.success(), "failed to compile {} to SPIR-V", name);
+  }
+}
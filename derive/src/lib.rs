@@ -0,0 +1,62 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta};
+
+mod uniforms;
+mod vertex;
+
+/// Implements `learn_opengl::uniform::Uniforms`, setting one uniform per field.
+///
+/// The uniform name defaults to the field name and can be changed with `#[uniform(name = "...")]`. Fields
+/// marked `#[uniform(nested)]` are GLSL structs (or arrays of them) that implement `Uniforms` themselves.
+#[proc_macro_derive(Uniforms, attributes(uniform))]
+pub fn derive_uniforms(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  uniforms::expand(&input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+/// Implements `learn_opengl::vertex::Vertex` for a `#[repr(C)]` struct, one attribute per field.
+///
+/// Attribute locations follow the field order unless set with `#[vertex(location = N)]`, with matrix fields
+/// taking one location per column. Integer fields can be marked `#[vertex(normalized)]`, or `#[vertex(integer)]` to
+/// be read as `int`/`uint` in the shader.
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  vertex::expand(&input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<&syn::FieldsNamed> {
+  match &input.data {
+    Data::Struct(data) => match &data.fields {
+      Fields::Named(fields) => Ok(fields),
+      _ => Err(Error::new_spanned(
+        input,
+        "only structs with named fields are supported",
+      )),
+    },
+    _ => Err(Error::new_spanned(input, "only structs are supported")),
+  }
+}
+
+/// Parses `#[attr_name(key = value, flag)]` attributes into `(key, value)` pairs, `value` being `None` for flags.
+fn attr_args(attrs: &[syn::Attribute], attr_name: &str) -> syn::Result<Vec<(syn::Ident, Option<Lit>)>> {
+  let mut args = vec![];
+  for attr in attrs.iter().filter(|a| a.path.is_ident(attr_name)) {
+    let list = match attr.parse_meta()? {
+      Meta::List(list) => list,
+      meta => return Err(Error::new_spanned(meta, format!("expected #[{}(...)]", attr_name))),
+    };
+    for nested in list.nested {
+      match nested {
+        NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.get_ident().is_some() => {
+          args.push((nv.path.get_ident().unwrap().clone(), Some(nv.lit)))
+        }
+        NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+          args.push((path.get_ident().unwrap().clone(), None))
+        }
+        nested => return Err(Error::new_spanned(nested, "unsupported attribute argument")),
+      }
+    }
+  }
+  Ok(args)
+}
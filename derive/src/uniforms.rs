@@ -0,0 +1,68 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Lit};
+
+use crate::{attr_args, named_fields};
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+  let name = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+  let mut fields = vec![];
+  let mut applies = vec![];
+  for field in &named_fields(input)?.named {
+    let ident = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+
+    let mut uniform_name = ident.to_string();
+    let mut nested = false;
+    for (key, value) in attr_args(&field.attrs, "uniform")? {
+      match (key.to_string().as_str(), value) {
+        ("name", Some(Lit::Str(s))) => uniform_name = s.value(),
+        ("nested", None) => nested = true,
+        _ => return Err(Error::new_spanned(key, "expected `name = \"...\"` or `nested`")),
+      }
+    }
+
+    if nested {
+      fields.push(quote! {
+        fields.extend(<#ty as ::learn_opengl::uniform::Uniforms>::fields_prefixed(
+          &::learn_opengl::uniform::member(prefix, #uniform_name),
+        ));
+      });
+      applies.push(quote! {
+        ::learn_opengl::uniform::Uniforms::apply_prefixed(
+          &self.#ident,
+          program,
+          &::learn_opengl::uniform::member(prefix, #uniform_name),
+        );
+      });
+    } else {
+      fields.push(quote! {
+        fields.push(::learn_opengl::uniform::UniformField::new::<#ty>(
+          &::learn_opengl::uniform::member(prefix, #uniform_name),
+        ));
+      });
+      applies.push(quote! {
+        ::learn_opengl::uniform::UniformValue::set_uniform(
+          &self.#ident,
+          program.uniform_location(&::learn_opengl::uniform::member(prefix, #uniform_name)),
+        );
+      });
+    }
+  }
+
+  Ok(quote! {
+    impl #impl_generics ::learn_opengl::uniform::Uniforms for #name #ty_generics #where_clause {
+      fn fields_prefixed(prefix: &str) -> ::std::vec::Vec<::learn_opengl::uniform::UniformField> {
+        let mut fields = ::std::vec::Vec::new();
+        #(#fields)*
+        fields
+      }
+
+      unsafe fn apply_prefixed(&self, program: &::learn_opengl::ShaderProgram, prefix: &str) {
+        #(#applies)*
+      }
+    }
+  })
+}
@@ -0,0 +1,104 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DeriveInput, Error, Lit, Meta, NestedMeta};
+
+use crate::{attr_args, named_fields};
+
+fn is_repr_c(input: &DeriveInput) -> bool {
+  input
+    .attrs
+    .iter()
+    .filter(|a| a.path.is_ident("repr"))
+    .filter_map(|a| a.parse_meta().ok())
+    .any(|meta| match meta {
+      Meta::List(list) => list
+        .nested
+        .iter()
+        .any(|n| matches!(n, NestedMeta::Meta(Meta::Path(p)) if p.is_ident("C"))),
+      _ => false,
+    })
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+  let name = &input.ident;
+  if !is_repr_c(input) {
+    return Err(Error::new_spanned(
+      name,
+      "vertex structs need #[repr(C)] for a stable layout",
+    ));
+  }
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+  let mut attributes = vec![];
+  // Fields without an explicit location come after all the locations taken by the fields before them, which is
+  // more than one for matrices.
+  let mut default_location = quote!(0);
+  for field in named_fields(input)?.named.iter() {
+    let ident = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+
+    let mut location = None;
+    let mut normalized = false;
+    let mut integer = false;
+    for (key, value) in attr_args(&field.attrs, "vertex")? {
+      match (key.to_string().as_str(), value) {
+        ("location", Some(Lit::Int(i))) => location = Some(i.base10_parse::<u32>()?),
+        ("normalized", None) => normalized = true,
+        ("integer", None) => integer = true,
+        _ => {
+          return Err(Error::new_spanned(
+            key,
+            "expected `location = N`, `normalized` or `integer`",
+          ))
+        }
+      }
+    }
+    if normalized && integer {
+      return Err(Error::new_spanned(
+        ident,
+        "an attribute can't be both `normalized` and `integer`",
+      ));
+    }
+
+    let location = match location {
+      Some(location) => quote!(#location),
+      None => default_location.clone(),
+    };
+    // Fails to compile, pointing at the field's type, when it has float components.
+    if integer {
+      attributes.push(quote_spanned! {ty.span()=>
+        ::learn_opengl::vertex::assert_integer::<#ty>();
+      });
+    }
+    attributes.push(quote! {
+      let locations = <#ty as ::learn_opengl::vertex::AttributeType>::LOCATIONS;
+      let offset = {
+        let vertex = ::std::mem::MaybeUninit::<Self>::uninit();
+        let base = vertex.as_ptr();
+        unsafe { (::std::ptr::addr_of!((*base).#ident) as *const u8).offset_from(base as *const u8) as usize }
+      };
+      for column in 0..locations {
+        attributes.push(::learn_opengl::vertex::VertexAttribute {
+          location: #location + column,
+          components: <#ty as ::learn_opengl::vertex::AttributeType>::COMPONENTS,
+          gl_type: <#ty as ::learn_opengl::vertex::AttributeType>::GL_TYPE,
+          normalized: #normalized,
+          integer: #integer,
+          offset: offset + column as usize * (::std::mem::size_of::<#ty>() / locations as usize),
+        });
+      }
+    });
+    default_location = quote!(#default_location + <#ty as ::learn_opengl::vertex::AttributeType>::LOCATIONS);
+  }
+
+  Ok(quote! {
+    impl #impl_generics ::learn_opengl::vertex::Vertex for #name #ty_generics #where_clause {
+      fn attributes() -> ::std::vec::Vec<::learn_opengl::vertex::VertexAttribute> {
+        let mut attributes = ::std::vec::Vec::new();
+        #({ #attributes })*
+        attributes
+      }
+    }
+  })
+}
@@ -1,10 +1,9 @@
 use glfw::Context;
 use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
 use nalgebra_glm as glm;
-use std::ffi::c_void;
-use std::mem;
 
-use strugl::{deg_to_rad, Matrix4, ShaderProgram};
+use learn_opengl::{Mesh, ShaderProgram, Texture2D, TextureUnit};
+use strugl::{deg_to_rad, Matrix4};
 
 fn main() {
   let mut wireframe_mode = false;
@@ -39,185 +38,36 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec2 aTexCoord;
-
-out vec2 TexCoord;
-
-uniform mat4 model;
-uniform mat4 view;
-uniform mat4 projection;
-
-void main()
-{
-    gl_Position = projection * view * model * vec4(aPos, 1.0);
-    TexCoord = aTexCoord;
-}";
+    let vs_code = include_str!("../shaders/3d.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-in vec2 TexCoord;
-
-uniform sampler2D texture1;
-uniform sampler2D texture2;
-
-uniform float mixValue;
-
-void main()
-{
-    FragColor = mix(texture(texture1, TexCoord), texture(texture2, TexCoord), mixValue);
-}";
+    let fs_code = include_str!("../shaders/3d.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
 
     // Vertex Data
-    let vao = unsafe {
-      let mut vertices: Vec<f32> = vec![];
-      // position (xyz), texture coord (xy)
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-
-      let mut vbo = 0;
-      gl::GenBuffers(1, &mut vbo);
-
-      let mut vao = 0;
-      gl::GenVertexArrays(1, &mut vao);
-
-      gl::BindVertexArray(vao);
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-      gl::BufferData(
-        gl::ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        vertices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      gl::VertexAttribPointer(
-        0,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        5 * mem::size_of::<f32>() as i32,
-        (0 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(0);
-
-      gl::VertexAttribPointer(
-        1,
-        2,
-        gl::FLOAT,
-        gl::FALSE,
-        5 * mem::size_of::<f32>() as i32,
-        (3 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(1);
-
-      vao
-    };
+    let cube = unsafe { Mesh::cube(1.0).upload() };
 
     // Texture
     let tex = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/container.jpg")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgb();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGB,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
 
     let tex2 = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/awesomeface.png")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgba();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGBA,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
@@ -276,13 +126,10 @@ void main()
         sp.set_uniform_value("view", view);
         sp.set_uniform_value("projection", projection);
 
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-        gl::ActiveTexture(gl::TEXTURE1);
-        gl::BindTexture(gl::TEXTURE_2D, tex2);
+        tex.bind_to_unit(TextureUnit(0));
+        tex2.bind_to_unit(TextureUnit(1));
 
-        gl::BindVertexArray(vao);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
+        cube.draw();
       }
 
       window.swap_buffers();
@@ -0,0 +1,185 @@
+use glfw::Context;
+use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
+use nalgebra_glm as glm;
+use std::f32::consts::PI;
+
+use learn_opengl::{BoundingSphere, Buffer, Frustum, Mesh, MeshInstance, ShaderProgram, StreamBuffer};
+use strugl::{deg_to_rad, Matrix4, Vec3};
+
+const ASTEROIDS: usize = 10_000;
+
+/// A small xorshift generator, enough to scatter the asteroids the same way on every run.
+struct Rng(u32);
+
+impl Rng {
+  fn next(&mut self) -> f32 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 17;
+    self.0 ^= self.0 << 5;
+    self.0 as f32 / u32::MAX as f32
+  }
+
+  fn range(&mut self, min: f32, max: f32) -> f32 {
+    min + (max - min) * self.next()
+  }
+}
+
+/// Places the asteroids in a ring around the planet, each with its own size, rotation and shade of gray.
+fn asteroid_field(count: usize) -> Vec<MeshInstance> {
+  let mut rng = Rng(0x9e37_79b9);
+  let (radius, spread) = (50.0, 5.0);
+
+  (0..count)
+    .map(|i| {
+      let angle = i as f32 / count as f32 * 360.0;
+      let x = deg_to_rad(angle).sin() * radius + rng.range(-spread, spread);
+      let y = rng.range(-spread, spread) * 0.4;
+      let z = deg_to_rad(angle).cos() * radius + rng.range(-spread, spread);
+
+      let model = glm::translate(&glm::Mat4::identity(), &glm::vec3(x, y, z));
+      let model = glm::scale(&model, &glm::vec3(1.0, 1.0, 1.0) * rng.range(0.05, 0.25));
+      let model = glm::rotate(&model, rng.range(0.0, 2.0 * PI), &glm::vec3(0.4, 0.6, 0.8));
+
+      let gray = rng.range(0.4, 0.7);
+      MeshInstance {
+        model: model.into(),
+        color: [gray, gray * 0.95, gray * 0.9, 1.0],
+      }
+    })
+    .collect()
+}
+
+fn main() {
+  let mut wireframe_mode = false;
+  for arg in std::env::args() {
+    if &arg == "--wireframe" {
+      wireframe_mode = true;
+    }
+  }
+
+  let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+  glfw.window_hint(WindowHint::ContextVersion(4, 6));
+  glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+
+  if let Some((mut window, events)) = glfw.create_window(800, 600, "Rust-LearnOpenGL", WindowMode::Windowed) {
+    window.make_current();
+
+    gl_loader::init_gl();
+    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+
+    unsafe {
+      gl::Viewport(0, 0, 800, 600);
+    }
+
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+
+    unsafe {
+      gl::Enable(gl::DEPTH_TEST);
+      gl::Enable(gl::CULL_FACE);
+    }
+
+    if wireframe_mode {
+      unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+      }
+    }
+
+    // Shader Program
+    let vs_code = include_str!("../shaders/instanced.vert");
+    let fs_code = include_str!("../shaders/instanced.frag");
+    let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
+
+    // Meshes, each drawn from its own buffer of instances
+    let planet = unsafe { Mesh::uv_sphere(4.0, 48, 24).upload() };
+    let planet_instance = [MeshInstance {
+      model: glm::Mat4::identity().into(),
+      color: [0.8, 0.55, 0.35, 1.0],
+    }];
+
+    let rock = unsafe { Mesh::icosphere(1.0, 1).upload() };
+    let rock_instances = asteroid_field(ASTEROIDS);
+
+    // The asteroids don't move, so their world space spheres are computed once. Every frame the ones outside the
+    // view are culled and the rest are streamed into the instance buffer.
+    let rock_sphere = rock.bounds().expect("the rock has vertices").sphere;
+    let rock_spheres: Vec<BoundingSphere> = rock_instances
+      .iter()
+      .map(|instance| rock_sphere.transformed(instance.model))
+      .collect();
+    let mut visible_rocks = Vec::with_capacity(ASTEROIDS);
+
+    let (_planet_buffer, mut rock_stream) = unsafe {
+      let planet_buffer = Buffer::new(gl::ARRAY_BUFFER);
+      planet_buffer.upload(&planet_instance, gl::STATIC_DRAW);
+      planet
+        .vertex_array()
+        .set_instance_buffer::<MeshInstance>(&planet_buffer);
+
+      let rock_stream = StreamBuffer::<MeshInstance>::new(gl::ARRAY_BUFFER, ASTEROIDS, 3);
+      rock
+        .vertex_array()
+        .set_instance_buffer::<MeshInstance>(rock_stream.buffer());
+
+      (planet_buffer, rock_stream)
+    };
+
+    let light_dir: Vec3 = glm::normalize(&glm::vec3(-1.0f32, -0.3, -0.5)).into();
+    let projection = glm::perspective_fov(deg_to_rad(45.0), 800.0, 600.0, 0.1, 500.0);
+
+    // Loop
+    while !window.should_close() {
+      glfw.poll_events();
+      for (_, event) in glfw::flush_messages(&events) {
+        match event {
+          glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
+            gl::Viewport(0, 0, width, height);
+          },
+          glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+          _ => {}
+        }
+      }
+
+      // Orbit slowly around the planet, a little above the ring.
+      let angle = glfw.get_time() as f32 * deg_to_rad(5.0);
+      let eye = glm::vec3(angle.sin() * 90.0, 20.0, angle.cos() * 90.0);
+      let view = glm::look_at(&eye, &glm::vec3(0.0, 0.0, 0.0), &glm::vec3(0.0, 1.0, 0.0));
+
+      let frustum = Frustum::from_matrix(projection * view);
+      visible_rocks.clear();
+      visible_rocks.extend(
+        rock_instances
+          .iter()
+          .zip(&rock_spheres)
+          .filter(|(_, sphere)| frustum.intersects_sphere(sphere))
+          .map(|(instance, _)| *instance),
+      );
+      let rock_range = rock_stream.write(&visible_rocks);
+      window.set_title(&format!(
+        "Rust-LearnOpenGL - {} of {} asteroids drawn",
+        visible_rocks.len(),
+        ASTEROIDS
+      ));
+
+      unsafe {
+        gl::ClearColor(0.02, 0.02, 0.05, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        let view: Matrix4 = view.into();
+        let projection: Matrix4 = projection.into();
+
+        sp.use_program();
+        sp.set_uniform_value("view", view);
+        sp.set_uniform_value("projection", projection);
+        sp.set_uniform_value("lightDir", light_dir);
+
+        planet.draw_instanced(planet_instance.len());
+        rock.draw_instance_range(rock_range);
+      }
+
+      window.swap_buffers();
+    }
+  } else {
+    panic!("failed to create GLFW window");
+  }
+}
@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
+
+use learn_opengl::MeshCache;
+
+/// Converts an OBJ, glTF, PLY or STL file into a mesh cache next to it, or at the given output path.
+fn main() {
+  let mut args = std::env::args().skip(1);
+  let source = match args.next() {
+    Some(source) => PathBuf::from(source),
+    None => {
+      eprintln!("usage: bake_mesh <model.obj|.gltf|.glb|.ply|.stl> [output.meshcache]");
+      process::exit(2);
+    }
+  };
+  let output = args
+    .next()
+    .map_or_else(|| source.with_extension("meshcache"), PathBuf::from);
+
+  let start = Instant::now();
+  if let Err(e) = MeshCache::convert(&source, &output) {
+    eprintln!("{}", e);
+    process::exit(1);
+  }
+  let converted = start.elapsed();
+
+  let start = Instant::now();
+  let cache = MeshCache::open(&output).expect("failed to open the new cache");
+  let vertices: usize = cache.meshes().map(|m| m.vertices.len()).sum();
+  let triangles: usize = cache.meshes().map(|m| m.indices.len() / 3).sum();
+  println!(
+    "{}: {} meshes, {} vertices, {} triangles",
+    output.display(),
+    cache.len(),
+    vertices,
+    triangles
+  );
+  println!("converted in {:?}, opened in {:?}", converted, start.elapsed());
+}
@@ -1,10 +1,9 @@
 use glfw::Context;
 use glfw::{OpenGlProfileHint, WindowHint, WindowMode};
 use nalgebra_glm as glm;
-use std::ffi::c_void;
-use std::mem;
 
-use strugl::{deg_to_rad, Matrix4, ShaderProgram};
+use learn_opengl::{Mesh, Ray, ShaderProgram, Texture2D, TextureUnit};
+use strugl::{deg_to_rad, Matrix4};
 
 const INIT_WIDTH: u32 = 800;
 const INIT_HEIGHT: u32 = 600;
@@ -28,6 +27,7 @@ fn main() {
     window.set_framebuffer_size_polling(true);
     window.set_key_polling(true);
     window.set_cursor_pos_polling(true);
+    window.set_mouse_button_polling(true);
     window.set_cursor_mode(glfw::CursorMode::Disabled);
     window.set_scroll_polling(true);
 
@@ -46,185 +46,37 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec2 aTexCoord;
-
-out vec2 TexCoord;
-
-uniform mat4 model;
-uniform mat4 view;
-uniform mat4 projection;
-
-void main()
-{
-    gl_Position = projection * view * model * vec4(aPos, 1.0);
-    TexCoord = aTexCoord;
-}";
+    let vs_code = include_str!("../shaders/3d.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-in vec2 TexCoord;
-
-uniform sampler2D texture1;
-uniform sampler2D texture2;
-
-uniform float mixValue;
-
-void main()
-{
-    FragColor = mix(texture(texture1, TexCoord), texture(texture2, TexCoord), mixValue);
-}";
+    let fs_code = include_str!("../shaders/3d.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
 
     // Vertex Data
-    let vao = unsafe {
-      let mut vertices: Vec<f32> = vec![];
-      // position (xyz), texture coord (xy)
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, -0.5, 1.0, 1.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![0.5, 0.5, 0.5, 1.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.5, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, -0.5, 0.0, 1.0]);
-
-      let mut vbo = 0;
-      gl::GenBuffers(1, &mut vbo);
-
-      let mut vao = 0;
-      gl::GenVertexArrays(1, &mut vao);
-
-      gl::BindVertexArray(vao);
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-      gl::BufferData(
-        gl::ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        vertices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      gl::VertexAttribPointer(
-        0,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        5 * mem::size_of::<f32>() as i32,
-        (0 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(0);
-
-      gl::VertexAttribPointer(
-        1,
-        2,
-        gl::FLOAT,
-        gl::FALSE,
-        5 * mem::size_of::<f32>() as i32,
-        (3 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(1);
-
-      vao
-    };
+    let cube = unsafe { Mesh::cube(1.0).upload() };
+    let cube_bounds = *cube.bounds().expect("the cube has vertices");
 
     // Texture
     let tex = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/container.jpg")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgb();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGB,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
 
     let tex2 = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/awesomeface.png")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgba();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGBA,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
@@ -271,6 +123,12 @@ void main()
 
       let camera_movement = camera_speed * delta_time;
 
+      let model = glm::rotate(
+        &base_model,
+        glfw.get_time() as f32 * deg_to_rad(50.0),
+        &glm::vec3(0.5, 1.0, 0.0),
+      );
+
       glfw.poll_events();
       for (_, event) in glfw::flush_messages(&events) {
         use glfw::{Action::*, Key::*, WindowEvent::*};
@@ -308,6 +166,20 @@ void main()
           Scroll(_x, y) => {
             fov = (fov - y as f32).min(45.0).max(1.0);
           }
+          // Frame the cube: back away along the view direction until it fits the window.
+          Key(F, _, Press, _) => {
+            let sphere = cube_bounds.sphere.transformed(model);
+            let distance = sphere.framing_distance(deg_to_rad(fov), 800.0 / 600.0);
+            camera_pos = glm::Vec3::from(sphere.center) - camera_front * distance;
+          }
+          // Pick the cube under the crosshair in the middle of the window.
+          MouseButton(glfw::MouseButtonLeft, Press, _) => {
+            let ray = Ray::new(camera_pos.into(), camera_front.into());
+            match ray.intersect_aabb(&cube_bounds.aabb.transformed(model)) {
+              Some(distance) => println!("cube hit {:.2} units away", distance),
+              None => println!("missed the cube"),
+            }
+          }
           _ => {}
         }
       }
@@ -319,13 +191,7 @@ void main()
         sp.use_program();
         sp.set_uniform_value("mixValue", mix_value);
 
-        let model: Matrix4 = glm::rotate(
-          &base_model,
-          glfw.get_time() as f32 * deg_to_rad(50.0),
-          &glm::vec3(0.5, 1.0, 0.0),
-        )
-        .into();
-
+        let model: Matrix4 = model.into();
         let view: Matrix4 = glm::look_at(&camera_pos, &(camera_pos + camera_front), &camera_up).into();
         let projection: Matrix4 = glm::perspective_fov(deg_to_rad(fov), 800.0, 600.0, 0.1, 100.0).into();
 
@@ -333,13 +199,10 @@ void main()
         sp.set_uniform_value("view", view);
         sp.set_uniform_value("projection", projection);
 
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-        gl::ActiveTexture(gl::TEXTURE1);
-        gl::BindTexture(gl::TEXTURE_2D, tex2);
+        tex.bind_to_unit(TextureUnit(0));
+        tex2.bind_to_unit(TextureUnit(1));
 
-        gl::BindVertexArray(vao);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
+        cube.draw();
       }
 
       window.swap_buffers();
@@ -1,14 +1,28 @@
 use glfw::Context;
 use glfw::{OpenGlProfileHint, WindowHint, WindowMode};
 use nalgebra_glm as glm;
-use std::ffi::c_void;
-use std::mem;
 
-use strugl::{deg_to_rad, Matrix4, ShaderProgram, Vec3};
+use learn_opengl::{Defines, Mesh, ProgramVariants, ShaderStage, Uniforms};
+use strugl::{deg_to_rad, Matrix4, Vec3};
 
 const INIT_WIDTH: u32 = 800;
 const INIT_HEIGHT: u32 = 600;
 
+#[derive(Uniforms)]
+struct Transforms {
+  model: Matrix4,
+  view: Matrix4,
+  projection: Matrix4,
+}
+
+#[derive(Uniforms)]
+struct Colors {
+  #[uniform(name = "objectColor")]
+  object_color: Vec3,
+  #[uniform(name = "lightColor")]
+  light_color: Vec3,
+}
+
 fn main() {
   let mut wireframe_mode = false;
   for arg in std::env::args() {
@@ -56,100 +70,23 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location = 0) in vec3 aPos;
+    let vs_code = include_str!("../shaders/colors.vert");
 
-uniform mat4 model;
-uniform mat4 view;
-uniform mat4 projection;
+    // Fragment Shader
+    let fs_code = include_str!("../shaders/colors.frag");
 
-void main()
-{
-  gl_Position = projection * view * model * vec4(aPos, 1.0);
-}";
+    // Shader Programs
+    let mut programs = ProgramVariants::new(&[(ShaderStage::Vertex, vs_code), (ShaderStage::Fragment, fs_code)]);
+    let sp = unsafe { programs.get(&Defines::new()).unwrap() };
+    let light_sp = unsafe { programs.get(&Defines::new().flag("LIGHT_SOURCE")).unwrap() };
+    unsafe {
+      sp.validate_uniforms::<Transforms>().unwrap();
+      sp.validate_uniforms::<Colors>().unwrap();
+      light_sp.validate_uniforms::<Transforms>().unwrap();
+    }
 
-    // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-uniform vec3 objectColor;
-uniform vec3 lightColor;
-
-void main()
-{
-  FragColor = vec4(lightColor * objectColor, 1.0);
-}";
-
-    let light_fs_code: &str = "
-#version 460 core
-out vec4 FragColor;
-
-void main()
-{
-  FragColor = vec4(1.0);
-}";
-
-    // Shader Program
-    let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
-    let light_sp = unsafe { ShaderProgram::from_str(vs_code, light_fs_code) };
-
-    // Vertex Data
-    let (cube_vao, light_vao) = unsafe {
-      let mut vertices: Vec<f32> = vec![];
-      // position (xyz)
-      vertices.append(&mut vec![
-        -0.5, -0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, -0.5, 0.5, 0.5, -0.5, -0.5, 0.5, -0.5, -0.5, -0.5, -0.5, -0.5,
-        -0.5, 0.5, 0.5, -0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, -0.5, 0.5, 0.5, -0.5, -0.5, 0.5, -0.5, 0.5, 0.5, -0.5,
-        0.5, -0.5, -0.5, -0.5, -0.5, -0.5, -0.5, -0.5, -0.5, -0.5, 0.5, -0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, -0.5,
-        0.5, -0.5, -0.5, 0.5, -0.5, -0.5, 0.5, -0.5, 0.5, 0.5, 0.5, 0.5, -0.5, -0.5, -0.5, 0.5, -0.5, -0.5, 0.5, -0.5,
-        0.5, 0.5, -0.5, 0.5, -0.5, -0.5, 0.5, -0.5, -0.5, -0.5, -0.5, 0.5, -0.5, 0.5, 0.5, -0.5, 0.5, 0.5, 0.5, 0.5,
-        0.5, 0.5, -0.5, 0.5, 0.5, -0.5, 0.5, -0.5,
-      ]);
-
-      let mut vbo = 0;
-      gl::GenBuffers(1, &mut vbo);
-
-      let mut vaos = vec![0, 0];
-      gl::GenVertexArrays(2, vaos.as_mut_ptr());
-
-      // Cube
-      gl::BindVertexArray(vaos[0]);
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-      gl::BufferData(
-        gl::ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        vertices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      gl::VertexAttribPointer(
-        0,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        3 * mem::size_of::<f32>() as i32,
-        (0 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(0);
-
-      // Light
-      gl::BindVertexArray(vaos[1]);
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-
-      gl::VertexAttribPointer(
-        0,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        3 * mem::size_of::<f32>() as i32,
-        (0 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(0);
-
-      (vaos[0], vaos[1])
-    };
+    // Vertex Data, shared by the cube and the light
+    let cube = unsafe { Mesh::cube(1.0).upload() };
 
     // Transformations
     let base_model = glm::rotate_x(&glm::Mat4::identity(), deg_to_rad(-55.0));
@@ -236,8 +173,11 @@ void main()
         gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
 
         sp.use_program();
-        sp.set_uniform_value("objectColor", obj_color);
-        sp.set_uniform_value("lightColor", light_color);
+        Colors {
+          object_color: obj_color,
+          light_color,
+        }
+        .apply(&sp);
 
         let model: Matrix4 = glm::rotate(
           &base_model,
@@ -249,20 +189,24 @@ void main()
         let view: Matrix4 = glm::look_at(&camera_pos, &(camera_pos + camera_front), &camera_up).into();
         let projection: Matrix4 = glm::perspective_fov(deg_to_rad(fov), 800.0, 600.0, 0.1, 100.0).into();
 
-        sp.set_uniform_value("model", model);
-        sp.set_uniform_value("view", view);
-        sp.set_uniform_value("projection", projection);
+        Transforms {
+          model,
+          view,
+          projection,
+        }
+        .apply(&sp);
 
-        gl::BindVertexArray(cube_vao);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
+        cube.draw();
 
         light_sp.use_program();
-        light_sp.set_uniform_value("model", light_model);
-        light_sp.set_uniform_value("view", view);
-        light_sp.set_uniform_value("projection", projection);
+        Transforms {
+          model: light_model,
+          view,
+          projection,
+        }
+        .apply(&light_sp);
 
-        gl::BindVertexArray(light_vao);
-        gl::DrawArrays(gl::TRIANGLES, 0, 36);
+        cube.draw();
       }
 
       window.swap_buffers();
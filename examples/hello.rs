@@ -3,7 +3,7 @@ use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
 use std::ffi::c_void;
 use std::mem;
 
-use strugl::ShaderProgram;
+use learn_opengl::{IndexBuffer, ShaderProgram};
 
 fn main() {
   let mut wireframe_mode = false;
@@ -31,30 +31,18 @@ fn main() {
     window.set_key_polling(true);
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location=0) in vec3 aPos;
-
-void main() {
-  gl_Position = vec4(aPos.x, aPos.y, aPos.z, 1.0);
-}";
+    let vs_code = include_str!("../shaders/hello.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-void main() {
-  FragColor = vec4(1.0f, 0.5f, 0.2f, 1.0f);
-}";
+    let fs_code = include_str!("../shaders/hello.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
 
     // Vertex Data
-    let va_triangle = unsafe {
+    let (va_triangle, ebo) = unsafe {
       let vertices: Vec<f32> = vec![0.5, 0.5, 0.0, 0.5, -0.5, 0.0, -0.5, -0.5, 0.0, -0.5, 0.5, 0.0];
-      let indices = vec![0, 1, 3, 1, 2, 3];
+      let indices: [u32; 6] = [0, 1, 3, 1, 2, 3];
 
       let mut vbo = 0;
       gl::GenBuffers(1, &mut vbo);
@@ -81,18 +69,10 @@ void main() {
       );
       gl::EnableVertexAttribArray(0);
 
-      let mut ebo = 0;
-      gl::GenBuffers(1, &mut ebo);
-
-      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-      gl::BufferData(
-        gl::ELEMENT_ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        indices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
+      let ebo = IndexBuffer::new(&indices, gl::STATIC_DRAW);
+      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo.id());
 
-      vao
+      (vao, ebo)
     };
 
     if wireframe_mode {
@@ -123,7 +103,7 @@ void main() {
         sp.use_program();
 
         gl::BindVertexArray(va_triangle);
-        gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const c_void);
+        ebo.draw(gl::TRIANGLES);
       }
 
       window.swap_buffers();
@@ -5,7 +5,43 @@ use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
 use std::ffi::c_void;
 use std::mem;
 
-use strugl::ShaderProgram;
+use learn_opengl::ShaderProgram;
+
+/// Builds the two triangles' programs, which differ only in the color they draw.
+#[cfg(not(feature = "spirv"))]
+unsafe fn shader_programs() -> (ShaderProgram, ShaderProgram) {
+  // Vertex Shader
+  let vs_code = include_str!("../shaders/hello.vert");
+
+  // Fragment Shader
+  let fs_code1 = include_str!("../shaders/hello.frag");
+
+  let fs_code2 = include_str!("../shaders/hello_triangle_ex.frag");
+
+  (
+    ShaderProgram::from_str(vs_code, fs_code1),
+    ShaderProgram::from_str(vs_code, fs_code2),
+  )
+}
+
+/// With the `spirv` feature, both programs share one precompiled fragment shader, specialized to each color.
+#[cfg(feature = "spirv")]
+unsafe fn shader_programs() -> (ShaderProgram, ShaderProgram) {
+  use learn_opengl::{include_spirv, ProgramBuilder, ShaderStage, SpirvModule};
+
+  let build = |[red, green, blue]: [f32; 3]| {
+    let fragment = SpirvModule::new(include_spirv!("spec_color.frag"))
+      .constant_f32(0, red)
+      .constant_f32(1, green)
+      .constant_f32(2, blue);
+    ProgramBuilder::new()
+      .spirv(ShaderStage::Vertex, SpirvModule::new(include_spirv!("hello.vert")))
+      .spirv(ShaderStage::Fragment, fragment)
+      .build()
+      .unwrap_or_else(|e| panic!("{}", e))
+  };
+  (build([1.0, 0.5, 0.2]), build([0.99607, 0.87450, 0.88235]))
+}
 
 fn main() {
   let mut wireframe_mode = false;
@@ -23,7 +59,8 @@ fn main() {
     window.make_current();
 
     gl_loader::init_gl();
-    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+    // Also loads `glSpecializeShader`, which the `gl` bindings lack.
+    learn_opengl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
 
     unsafe {
       gl::Viewport(0, 0, 800, 600);
@@ -32,37 +69,8 @@ fn main() {
     window.set_framebuffer_size_polling(true);
     window.set_key_polling(true);
 
-    // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location=0) in vec3 aPos;
-
-void main() {
-  gl_Position = vec4(aPos.x, aPos.y, aPos.z, 1.0);
-}";
-
-    // Fragment Shader
-    let fs_code1 = "
-#version 460 core
-out vec4 FragColor;
-
-void main() {
-  FragColor = vec4(1.0f, 0.5f, 0.2f, 1.0f);
-}
-";
-
-    let fs_code2 = "
-#version 460 core
-out vec4 FragColor;
-
-void main() {
-  FragColor = vec4(0.99607f, 0.87450f, 88235f, 1.0f);
-}
-";
-
     // Shader Programs
-    let sp1 = unsafe { ShaderProgram::from_str(vs_code, fs_code1) };
-    let sp2 = unsafe { ShaderProgram::from_str(vs_code, fs_code2) };
+    let (sp1, sp2) = unsafe { shader_programs() };
 
     // Triangle 1
     let t1 = unsafe {
@@ -0,0 +1,142 @@
+use glfw::Context;
+use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
+use nalgebra_glm as glm;
+
+use learn_opengl::import::ObjModel;
+use learn_opengl::lod::pixels_per_unit;
+use learn_opengl::{Defines, Lod, Mesh, ProgramVariants, ShaderStage};
+use strugl::{deg_to_rad, Matrix4, Vec3};
+
+/// The tint of each level, from the original to the coarsest.
+const LEVEL_COLORS: [[f32; 4]; 5] = [
+  [0.8, 0.8, 0.8, 1.0],
+  [0.3, 0.8, 0.3, 1.0],
+  [0.3, 0.6, 0.9, 1.0],
+  [0.9, 0.8, 0.3, 1.0],
+  [0.9, 0.4, 0.3, 1.0],
+];
+
+/// How far a level's surface may be off the original on screen before a finer one is drawn.
+const MAX_PIXEL_ERROR: f32 = 1.0;
+
+fn main() {
+  let mut wireframe_mode = false;
+  let mut path = None;
+  for arg in std::env::args().skip(1) {
+    if &arg == "--wireframe" {
+      wireframe_mode = true;
+    } else {
+      path = Some(arg);
+    }
+  }
+
+  // The first mesh of an OBJ file, or a finely tessellated sphere.
+  let mesh = match &path {
+    Some(path) => {
+      let model = ObjModel::load(path).unwrap_or_else(|e| panic!("failed to load {}: {}", path, e));
+      let first = model.meshes.into_iter().next();
+      first.unwrap_or_else(|| panic!("{} has no faces", path)).mesh
+    }
+    None => Mesh::uv_sphere(1.0, 128, 64),
+  };
+
+  let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+  glfw.window_hint(WindowHint::ContextVersion(4, 6));
+  glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+
+  if let Some((mut window, events)) = glfw.create_window(800, 600, "Rust-LearnOpenGL", WindowMode::Windowed) {
+    window.make_current();
+
+    gl_loader::init_gl();
+    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+
+    unsafe {
+      gl::Viewport(0, 0, 800, 600);
+    }
+
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+
+    unsafe {
+      gl::Enable(gl::DEPTH_TEST);
+    }
+
+    if wireframe_mode {
+      unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+      }
+    }
+
+    // Shader Program
+    let vs_code = include_str!("../shaders/lit.vert");
+    let fs_code = include_str!("../shaders/lit.frag");
+    let mut programs = ProgramVariants::new(&[(ShaderStage::Vertex, vs_code), (ShaderStage::Fragment, fs_code)]);
+    let sp = unsafe { programs.get(&Defines::new().flag("VERTEX_COLOR")).unwrap() };
+
+    // Levels halving the triangles each time, tinted so the switches show.
+    let sphere = mesh.bounds().expect("the mesh has no vertices").sphere;
+    let mut lod = Lod::build(mesh, LEVEL_COLORS.len(), 0.5);
+    for (level, color) in lod.levels.iter_mut().zip(&LEVEL_COLORS) {
+      level.mesh.colors = vec![*color; level.mesh.vertex_count()];
+      println!("{} triangles, error {:.5}", level.mesh.triangle_count(), level.error);
+    }
+    let lod = unsafe { lod.upload() };
+
+    // A row of copies, each twice as far from the camera as the one before, scaled to a unit radius.
+    let fov = deg_to_rad(45.0);
+    let scale = 1.0 / sphere.radius.max(f32::EPSILON);
+    let center = glm::Vec3::from(sphere.center);
+    let distances = [3.0f32, 6.0, 12.0, 24.0, 48.0, 96.0];
+
+    let light_dir: Vec3 = glm::normalize(&glm::vec3(-0.4f32, -1.0, -0.6)).into();
+
+    // Loop
+    while !window.should_close() {
+      glfw.poll_events();
+      for (_, event) in glfw::flush_messages(&events) {
+        match event {
+          glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
+            gl::Viewport(0, 0, width, height);
+          },
+          glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+          _ => {}
+        }
+      }
+
+      unsafe {
+        gl::ClearColor(0.1, 0.1, 0.12, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        // Dollies slowly back and forth so every copy switches levels.
+        let eye_z = 2.0 * (glfw.get_time() as f32 * 0.3).sin();
+        let eye = glm::vec3(1.5, 1.0, eye_z);
+        let target = glm::vec3(0.0, 0.0, eye_z - 10.0);
+        let (width, height) = window.get_framebuffer_size();
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let view: Matrix4 = glm::look_at(&eye, &target, &glm::vec3(0.0, 1.0, 0.0)).into();
+        let projection: Matrix4 = glm::perspective_fov(fov, width, height, 0.1, 200.0).into();
+
+        sp.use_program();
+        sp.set_uniform_value("view", view);
+        sp.set_uniform_value("projection", projection);
+        sp.set_uniform_value("lightDir", light_dir);
+
+        for &distance in &distances {
+          let position = glm::vec3(0.0, 0.0, -distance);
+          let model = glm::scale(&glm::translation(&position), &glm::vec3(scale, scale, scale));
+          let model: Matrix4 = glm::translate(&model, &-center).into();
+
+          // Errors are in model units, which the scale stretches.
+          let pixels = pixels_per_unit(glm::distance(&eye, &position), fov, height) * scale;
+          let level = lod.select(pixels, MAX_PIXEL_ERROR);
+          sp.set_uniform_value("model", model);
+          lod.levels[level].mesh.draw();
+        }
+      }
+
+      window.swap_buffers();
+    }
+  } else {
+    panic!("failed to create GLFW window");
+  }
+}
@@ -4,7 +4,8 @@ use nalgebra_glm::{rotate_z, scale, translate, vec3, Mat4};
 use std::ffi::c_void;
 use std::mem;
 
-use strugl::{Matrix4, ShaderProgram};
+use learn_opengl::{IndexBuffer, ShaderProgram, Texture2D, TextureUnit};
+use strugl::Matrix4;
 
 fn main() {
   let mut wireframe_mode = false;
@@ -37,43 +38,16 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec2 aTexCoord;
-
-out vec2 TexCoord;
-
-uniform mat4 transform;
-
-void main()
-{
-    gl_Position = transform * vec4(aPos, 1.0);
-    TexCoord = aTexCoord;
-}";
+    let vs_code = include_str!("../shaders/matrix.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-in vec2 TexCoord;
-
-uniform sampler2D texture1;
-uniform sampler2D texture2;
-
-uniform float mixValue;
-
-void main()
-{
-    FragColor = mix(texture(texture1, TexCoord), texture(texture2, TexCoord), mixValue);
-}";
+    let fs_code = include_str!("../shaders/matrix.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
 
     // Vertex Data
-    let vao = unsafe {
+    let (vao, ebo) = unsafe {
       let mut vertices: Vec<f32> = vec![];
       // position (xyz), texture coord (xy)
       vertices.append(&mut vec![0.5, 0.5, 0.0, 2.0, 2.0]);
@@ -81,7 +55,7 @@ void main()
       vertices.append(&mut vec![-0.5, -0.5, 0.0, 0.0, 0.0]);
       vertices.append(&mut vec![-0.5, 0.5, 0.0, 0.0, 2.0]);
 
-      let indices = vec![0, 1, 3, 1, 2, 3];
+      let indices: [u32; 6] = [0, 1, 3, 1, 2, 3];
 
       let mut vbo = 0;
       gl::GenBuffers(1, &mut vbo);
@@ -118,83 +92,31 @@ void main()
       );
       gl::EnableVertexAttribArray(1);
 
-      let mut ebo = 0;
-      gl::GenBuffers(1, &mut ebo);
+      let ebo = IndexBuffer::new(&indices, gl::STATIC_DRAW);
+      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo.id());
 
-      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-      gl::BufferData(
-        gl::ELEMENT_ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        indices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      vao
+      (vao, ebo)
     };
 
     // Texture
     let tex = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/container.jpg")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgb();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGB,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
 
     let tex2 = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/awesomeface.png")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgba();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGBA,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
@@ -246,18 +168,16 @@ void main()
         let trans: Matrix4 = rotate_z(&base_trans, glfw.get_time() as f32).into();
         sp.set_uniform_value("transform", trans);
 
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-        gl::ActiveTexture(gl::TEXTURE1);
-        gl::BindTexture(gl::TEXTURE_2D, tex2);
+        tex.bind_to_unit(TextureUnit(0));
+        tex2.bind_to_unit(TextureUnit(1));
 
         gl::BindVertexArray(vao);
-        gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const c_void);
+        ebo.draw(gl::TRIANGLES);
 
         let sc = glfw.get_time().sin() as f32;
         let trans2: Matrix4 = scale(&base_trans2, &vec3(sc, sc, 1.0)).into();
         sp.set_uniform_value("transform", trans2);
-        gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const c_void);
+        ebo.draw(gl::TRIANGLES);
       }
 
       window.swap_buffers();
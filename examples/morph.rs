@@ -0,0 +1,157 @@
+use glfw::Context;
+use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
+use nalgebra_glm as glm;
+
+use learn_opengl::import::GltfScene;
+use learn_opengl::{Aabb, BoundingSphere, Buffer, Defines, GpuMesh, ProgramVariants, ShaderStage};
+use strugl::{deg_to_rad, Matrix4, Vec3};
+
+/// A primitive with morph targets and the node drawing it, whose weights it takes.
+struct MorphedMesh {
+  mesh: GpuMesh,
+  node: usize,
+  vertex_count: usize,
+  target_count: usize,
+}
+
+fn main() {
+  let mut wireframe_mode = false;
+  let mut path = "tests/fixtures/morph.gltf".to_string();
+  for arg in std::env::args().skip(1) {
+    if &arg == "--wireframe" {
+      wireframe_mode = true;
+    } else {
+      path = arg;
+    }
+  }
+  let scene = GltfScene::load(&path).unwrap_or_else(|e| panic!("failed to load {}: {}", path, e));
+
+  let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+  glfw.window_hint(WindowHint::ContextVersion(4, 6));
+  glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+
+  if let Some((mut window, events)) = glfw.create_window(800, 600, "Rust-LearnOpenGL", WindowMode::Windowed) {
+    window.make_current();
+
+    gl_loader::init_gl();
+    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+
+    unsafe {
+      gl::Viewport(0, 0, 800, 600);
+    }
+
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+
+    unsafe {
+      gl::Enable(gl::DEPTH_TEST);
+    }
+
+    if wireframe_mode {
+      unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+      }
+    }
+
+    // Shader Program
+    let vs_code = include_str!("../shaders/lit.vert");
+    let fs_code = include_str!("../shaders/lit.frag");
+    let mut programs = ProgramVariants::new(&[(ShaderStage::Vertex, vs_code), (ShaderStage::Fragment, fs_code)]);
+    let sp = unsafe {
+      programs
+        .get(&Defines::new().flag("VERTEX_COLOR").flag("MORPHED"))
+        .unwrap()
+    };
+
+    // Meshes with morph targets only, light gray where they have no colors.
+    let mut meshes = Vec::new();
+    let mut bounds: Option<Aabb> = None;
+    for (node_index, node) in scene.nodes.iter().enumerate() {
+      let primitives = node.mesh.map_or(&[][..], |mesh| &scene.meshes[mesh].primitives[..]);
+      for primitive in primitives.iter().filter(|p| !p.mesh.morph_targets.is_empty()) {
+        let mut mesh = primitive.mesh.clone();
+        if mesh.colors.is_empty() {
+          mesh.colors = vec![[0.8, 0.8, 0.8, 1.0]; mesh.vertex_count()];
+        }
+        if let Some(mesh_bounds) = mesh.bounds() {
+          let aabb = mesh_bounds.aabb.transformed(scene.world_transform(node_index));
+          bounds = Some(bounds.map_or(aabb, |b| b.union(&aabb)));
+        }
+        meshes.push(MorphedMesh {
+          mesh: unsafe { mesh.upload() },
+          node: node_index,
+          vertex_count: mesh.vertex_count(),
+          target_count: mesh.morph_targets.len(),
+        });
+      }
+    }
+    let bounds = bounds.expect("the file has no meshes with morph targets");
+
+    // Looks at the rest pose from a distance that fits it in the window, with some room for the targets.
+    let fov = deg_to_rad(45.0);
+    let sphere = BoundingSphere {
+      center: bounds.center(),
+      radius: glm::length(&glm::Vec3::from(bounds.extents())),
+    };
+    let center = glm::Vec3::from(sphere.center);
+    let distance = sphere.framing_distance(fov, 800.0 / 600.0) * 1.5;
+    let eye = center + glm::vec3(0.3, 0.3, 1.0).normalize() * distance;
+
+    let light_dir: Vec3 = glm::normalize(&glm::vec3(-0.4f32, -1.0, -0.6)).into();
+    let view: Matrix4 = glm::look_at(&eye, &center, &glm::vec3(0.0, 1.0, 0.0)).into();
+    let projection: Matrix4 = glm::perspective_fov(fov, 800.0, 600.0, distance * 0.01, distance * 3.0).into();
+
+    // The weights of the mesh being drawn, read by the vertex shader at binding 2.
+    let weight_buffer = unsafe { Buffer::new(gl::SHADER_STORAGE_BUFFER) };
+    let clip = scene.animations.first();
+
+    // Loop
+    while !window.should_close() {
+      glfw.poll_events();
+      for (_, event) in glfw::flush_messages(&events) {
+        match event {
+          glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
+            gl::Viewport(0, 0, width, height);
+          },
+          glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+          _ => {}
+        }
+      }
+
+      // Loops the first animation, or holds the rest weights if there is none.
+      let mut weights = scene.rest_weights();
+      if let Some(clip) = clip {
+        let duration = clip.duration().max(f32::EPSILON);
+        clip.apply_weights(glfw.get_time() as f32 % duration, &mut weights);
+      }
+
+      unsafe {
+        gl::ClearColor(0.1, 0.1, 0.12, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        sp.use_program();
+        sp.set_uniform_value("view", view);
+        sp.set_uniform_value("projection", projection);
+        sp.set_uniform_value("lightDir", light_dir);
+
+        for morphed in &meshes {
+          let model: Matrix4 = scene.world_transform(morphed.node).into();
+          sp.set_uniform_value("model", model);
+          sp.set_uniform_value("vertexCount", morphed.vertex_count as i32);
+          sp.set_uniform_value("targetCount", morphed.target_count as i32);
+          morphed.mesh.morph_targets().unwrap().bind_base(1);
+          // A node's weights can disagree with its mesh, e.g. when they were written for an older version of it.
+          let mut mesh_weights = weights[morphed.node].clone();
+          mesh_weights.resize(morphed.target_count, 0.0);
+          weight_buffer.upload(&mesh_weights, gl::STREAM_DRAW);
+          weight_buffer.bind_base(2);
+          morphed.mesh.draw();
+        }
+      }
+
+      window.swap_buffers();
+    }
+  } else {
+    panic!("failed to create GLFW window");
+  }
+}
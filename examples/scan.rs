@@ -0,0 +1,117 @@
+use glfw::Context;
+use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
+use nalgebra_glm as glm;
+use std::path::Path;
+
+use learn_opengl::import::{load_ply, load_stl};
+use learn_opengl::{Defines, Mesh, ProgramVariants, ShaderStage};
+use strugl::{deg_to_rad, Matrix4, Vec3};
+
+/// Loads the PLY or STL file at `path`, picking the loader from the extension.
+fn load_scan(path: &Path) -> Mesh {
+  let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+  let mesh = match extension.as_deref() {
+    Some("ply") => load_ply(path).map_err(|e| e.to_string()),
+    Some("stl") => load_stl(path).map_err(|e| e.to_string()),
+    _ => Err("expected a .ply or .stl file".to_string()),
+  };
+  mesh.unwrap_or_else(|e| panic!("failed to load {}: {}", path.display(), e))
+}
+
+fn main() {
+  let mut wireframe_mode = false;
+  let mut path = "tests/fixtures/quad_ascii.ply".to_string();
+  for arg in std::env::args().skip(1) {
+    if &arg == "--wireframe" {
+      wireframe_mode = true;
+    } else {
+      path = arg;
+    }
+  }
+
+  let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+  glfw.window_hint(WindowHint::ContextVersion(4, 6));
+  glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+
+  if let Some((mut window, events)) = glfw.create_window(800, 600, "Rust-LearnOpenGL", WindowMode::Windowed) {
+    window.make_current();
+
+    gl_loader::init_gl();
+    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+
+    unsafe {
+      gl::Viewport(0, 0, 800, 600);
+    }
+
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+
+    unsafe {
+      gl::Enable(gl::DEPTH_TEST);
+    }
+
+    if wireframe_mode {
+      unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+      }
+    }
+
+    // Shader Program
+    let vs_code = include_str!("../shaders/lit.vert");
+    let fs_code = include_str!("../shaders/lit.frag");
+    let mut programs = ProgramVariants::new(&[(ShaderStage::Vertex, vs_code), (ShaderStage::Fragment, fs_code)]);
+    let sp = unsafe { programs.get(&Defines::new().flag("VERTEX_COLOR")).unwrap() };
+
+    // Mesh, light gray where the file has no colors
+    let mut mesh = load_scan(Path::new(&path));
+    if mesh.colors.is_empty() {
+      mesh.colors = vec![[0.8, 0.8, 0.8, 1.0]; mesh.vertex_count()];
+    }
+    let scan = unsafe { mesh.upload() };
+
+    // Orbit at a distance that fits the whole scan in the window.
+    let fov = deg_to_rad(45.0);
+    let sphere = scan.bounds().expect("the file has no vertices").sphere;
+    let center = glm::Vec3::from(sphere.center);
+    let distance = sphere.framing_distance(fov, 800.0 / 600.0) * 1.1;
+
+    let light_dir: Vec3 = glm::normalize(&glm::vec3(-0.4f32, -1.0, -0.6)).into();
+    let model: Matrix4 = glm::Mat4::identity().into();
+    let projection: Matrix4 = glm::perspective_fov(fov, 800.0, 600.0, distance * 0.01, distance * 3.0).into();
+
+    // Loop
+    while !window.should_close() {
+      glfw.poll_events();
+      for (_, event) in glfw::flush_messages(&events) {
+        match event {
+          glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
+            gl::Viewport(0, 0, width, height);
+          },
+          glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+          _ => {}
+        }
+      }
+
+      unsafe {
+        gl::ClearColor(0.1, 0.1, 0.12, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        let angle = glfw.get_time() as f32 * deg_to_rad(20.0);
+        let eye = center + glm::vec3(angle.sin(), 0.3, angle.cos()).normalize() * distance;
+        let view: Matrix4 = glm::look_at(&eye, &center, &glm::vec3(0.0, 1.0, 0.0)).into();
+
+        sp.use_program();
+        sp.set_uniform_value("model", model);
+        sp.set_uniform_value("view", view);
+        sp.set_uniform_value("projection", projection);
+        sp.set_uniform_value("lightDir", light_dir);
+
+        scan.draw();
+      }
+
+      window.swap_buffers();
+    }
+  } else {
+    panic!("failed to create GLFW window");
+  }
+}
@@ -3,7 +3,7 @@ use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
 use std::ffi::c_void;
 use std::mem;
 
-use strugl::ShaderProgram;
+use learn_opengl::ShaderProgram;
 
 fn main() {
   let mut wireframe_mode = false;
@@ -42,31 +42,10 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location=0) in vec3 aPos;
-layout (location=1) in vec3 aColor;
-
-uniform float xOffset;
-
-out vec3 vtxColor;
-
-void main() {
-  gl_Position = vec4(aPos.x + xOffset, -aPos.y, aPos.z, 1.0);
-  vtxColor = aColor;
-}";
+    let vs_code = include_str!("../shaders/shader.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-in vec3 vtxColor;
-uniform float uAlpha;
-
-out vec4 FragColor;
-
-void main() {
-  FragColor = vec4(vtxColor, uAlpha);
-}";
+    let fs_code = include_str!("../shaders/shader.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
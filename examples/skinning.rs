@@ -0,0 +1,145 @@
+use glfw::Context;
+use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
+use nalgebra_glm as glm;
+
+use learn_opengl::import::GltfScene;
+use learn_opengl::{Bounds, Buffer, Defines, GpuMesh, ProgramVariants, ShaderStage};
+use strugl::{deg_to_rad, Matrix4, Vec3};
+
+/// A skinned primitive and the skin deforming it.
+struct SkinnedMesh {
+  mesh: GpuMesh,
+  skin: usize,
+}
+
+fn main() {
+  let mut wireframe_mode = false;
+  let mut path = "tests/fixtures/skinned.gltf".to_string();
+  for arg in std::env::args().skip(1) {
+    if &arg == "--wireframe" {
+      wireframe_mode = true;
+    } else {
+      path = arg;
+    }
+  }
+  let scene = GltfScene::load(&path).unwrap_or_else(|e| panic!("failed to load {}: {}", path, e));
+
+  let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
+  glfw.window_hint(WindowHint::ContextVersion(4, 6));
+  glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
+
+  if let Some((mut window, events)) = glfw.create_window(800, 600, "Rust-LearnOpenGL", WindowMode::Windowed) {
+    window.make_current();
+
+    gl_loader::init_gl();
+    gl::load_with(|symbol| gl_loader::get_proc_address(symbol) as *const _);
+
+    unsafe {
+      gl::Viewport(0, 0, 800, 600);
+    }
+
+    window.set_framebuffer_size_polling(true);
+    window.set_key_polling(true);
+
+    unsafe {
+      gl::Enable(gl::DEPTH_TEST);
+    }
+
+    if wireframe_mode {
+      unsafe {
+        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+      }
+    }
+
+    // Shader Program
+    let vs_code = include_str!("../shaders/lit.vert");
+    let fs_code = include_str!("../shaders/lit.frag");
+    let mut programs = ProgramVariants::new(&[(ShaderStage::Vertex, vs_code), (ShaderStage::Fragment, fs_code)]);
+    let sp = unsafe {
+      programs
+        .get(&Defines::new().flag("VERTEX_COLOR").flag("SKINNED"))
+        .unwrap()
+    };
+
+    // Skinned meshes only, light gray where they have no colors. Their nodes' transforms don't matter: the joint
+    // matrices place them in the world.
+    let mut meshes = Vec::new();
+    let mut bounds: Option<Bounds> = None;
+    for node in &scene.nodes {
+      if let (Some(mesh), Some(skin)) = (node.mesh, node.skin) {
+        for primitive in &scene.meshes[mesh].primitives {
+          let mut mesh = primitive.mesh.clone();
+          if mesh.colors.is_empty() {
+            mesh.colors = vec![[0.8, 0.8, 0.8, 1.0]; mesh.vertex_count()];
+          }
+          bounds = match (bounds, mesh.bounds()) {
+            (Some(a), Some(b)) => Bounds::from_points(&[a.aabb.min, a.aabb.max, b.aabb.min, b.aabb.max]),
+            (a, b) => a.or(b),
+          };
+          meshes.push(SkinnedMesh {
+            mesh: unsafe { mesh.upload() },
+            skin,
+          });
+        }
+      }
+    }
+    let sphere = bounds.expect("the file has no skinned meshes").sphere;
+
+    // Looks at the bind pose from a distance that fits it in the window, with some room for the animation.
+    let fov = deg_to_rad(45.0);
+    let center = glm::Vec3::from(sphere.center);
+    let distance = sphere.framing_distance(fov, 800.0 / 600.0) * 1.5;
+    let eye = center + glm::vec3(0.3, 0.3, 1.0).normalize() * distance;
+
+    let light_dir: Vec3 = glm::normalize(&glm::vec3(-0.4f32, -1.0, -0.6)).into();
+    let view: Matrix4 = glm::look_at(&eye, &center, &glm::vec3(0.0, 1.0, 0.0)).into();
+    let projection: Matrix4 = glm::perspective_fov(fov, 800.0, 600.0, distance * 0.01, distance * 3.0).into();
+
+    // The joint matrices of the skin being drawn, read by the vertex shader at binding 0.
+    let joint_buffer = unsafe { Buffer::new(gl::SHADER_STORAGE_BUFFER) };
+    let clip = scene.animations.first();
+
+    // Loop
+    while !window.should_close() {
+      glfw.poll_events();
+      for (_, event) in glfw::flush_messages(&events) {
+        match event {
+          glfw::WindowEvent::FramebufferSize(width, height) => unsafe {
+            gl::Viewport(0, 0, width, height);
+          },
+          glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => window.set_should_close(true),
+          _ => {}
+        }
+      }
+
+      // Loops the first animation, or holds the rest pose if there is none.
+      let mut pose = scene.rest_pose();
+      if let Some(clip) = clip {
+        let duration = clip.duration().max(f32::EPSILON);
+        clip.apply(glfw.get_time() as f32 % duration, &mut pose);
+      }
+      let world = scene.world_transforms(&pose);
+
+      unsafe {
+        gl::ClearColor(0.1, 0.1, 0.12, 1.0);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        sp.use_program();
+        sp.set_uniform_value("view", view);
+        sp.set_uniform_value("projection", projection);
+        sp.set_uniform_value("lightDir", light_dir);
+
+        for skinned in &meshes {
+          let joint_matrices = scene.skins[skinned.skin].joint_matrices(&world);
+          joint_buffer.upload(&joint_matrices, gl::STREAM_DRAW);
+          joint_buffer.bind_base(0);
+          skinned.mesh.draw();
+        }
+      }
+
+      window.swap_buffers();
+    }
+  } else {
+    panic!("failed to create GLFW window");
+  }
+}
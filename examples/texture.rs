@@ -1,9 +1,15 @@
 use glfw::Context;
 use glfw::{Action, Key, OpenGlProfileHint, WindowHint, WindowMode};
-use std::ffi::c_void;
-use std::mem;
 
-use strugl::ShaderProgram;
+use learn_opengl::{Buffer, IndexBuffer, ShaderProgram, Texture2D, TextureUnit, Vertex, VertexArray};
+
+#[derive(Clone, Copy, Vertex)]
+#[repr(C)]
+struct TexturedVertex {
+  position: [f32; 3],
+  color: [f32; 3],
+  tex_coord: [f32; 2],
+}
 
 fn main() {
   let mut wireframe_mode = false;
@@ -37,176 +43,72 @@ fn main() {
     }
 
     // Vertex Shader
-    let vs_code = "
-#version 460 core
-layout (location = 0) in vec3 aPos;
-layout (location = 1) in vec3 aColor;
-layout (location = 2) in vec2 aTexCoord;
-
-out vec3 ourColor;
-out vec2 TexCoord;
-
-void main()
-{
-    gl_Position = vec4(aPos, 1.0);
-    ourColor = aColor;
-    TexCoord = aTexCoord;
-}";
+    let vs_code = include_str!("../shaders/texture.vert");
 
     // Fragment Shader
-    let fs_code = "
-#version 460 core
-out vec4 FragColor;
-
-in vec3 ourColor;
-in vec2 TexCoord;
-
-uniform sampler2D texture1;
-uniform sampler2D texture2;
-
-uniform float mixValue;
-
-void main()
-{
-    FragColor = mix(texture(texture1, TexCoord), texture(texture2, TexCoord), mixValue);
-}";
+    let fs_code = include_str!("../shaders/texture.frag");
 
     // Shader Program
     let sp = unsafe { ShaderProgram::from_str(vs_code, fs_code) };
 
     // Vertex Data
-    let vao = unsafe {
-      let mut vertices: Vec<f32> = vec![];
-      // position (xyz), color (rgb), texture coord (xy)
-      vertices.append(&mut vec![0.5, 0.5, 0.0, 1.0, 0.0, 0.0, 2.0, 2.0]);
-      vertices.append(&mut vec![0.5, -0.5, 0.0, 0.0, 1.0, 0.0, 2.0, 0.0]);
-      vertices.append(&mut vec![-0.5, -0.5, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
-      vertices.append(&mut vec![-0.5, 0.5, 0.0, 1.0, 1.0, 0.0, 0.0, 2.0]);
-
-      let indices = vec![0, 1, 3, 1, 2, 3];
-
-      let mut vbo = 0;
-      gl::GenBuffers(1, &mut vbo);
-
-      let mut vao = 0;
-      gl::GenVertexArrays(1, &mut vao);
-
-      gl::BindVertexArray(vao);
-      gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-      gl::BufferData(
-        gl::ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        vertices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      gl::VertexAttribPointer(
-        0,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        8 * mem::size_of::<f32>() as i32,
-        0 as *const c_void,
-      );
-      gl::EnableVertexAttribArray(0);
-
-      gl::VertexAttribPointer(
-        1,
-        3,
-        gl::FLOAT,
-        gl::FALSE,
-        8 * mem::size_of::<f32>() as i32,
-        (3 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(1);
-
-      gl::VertexAttribPointer(
-        2,
-        2,
-        gl::FLOAT,
-        gl::FALSE,
-        8 * mem::size_of::<f32>() as i32,
-        (6 * mem::size_of::<f32>()) as *const c_void,
-      );
-      gl::EnableVertexAttribArray(2);
-
-      let mut ebo = 0;
-      gl::GenBuffers(1, &mut ebo);
-
-      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
-      gl::BufferData(
-        gl::ELEMENT_ARRAY_BUFFER,
-        (mem::size_of::<f32>() * vertices.len()) as isize,
-        indices.as_ptr() as *const c_void,
-        gl::STATIC_DRAW,
-      );
-
-      vao
+    let (_vbo, ebo, vao) = unsafe {
+      let vertices = [
+        TexturedVertex {
+          position: [0.5, 0.5, 0.0],
+          color: [1.0, 0.0, 0.0],
+          tex_coord: [2.0, 2.0],
+        },
+        TexturedVertex {
+          position: [0.5, -0.5, 0.0],
+          color: [0.0, 1.0, 0.0],
+          tex_coord: [2.0, 0.0],
+        },
+        TexturedVertex {
+          position: [-0.5, -0.5, 0.0],
+          color: [0.0, 0.0, 1.0],
+          tex_coord: [0.0, 0.0],
+        },
+        TexturedVertex {
+          position: [-0.5, 0.5, 0.0],
+          color: [1.0, 1.0, 0.0],
+          tex_coord: [0.0, 2.0],
+        },
+      ];
+
+      let indices: [u32; 6] = [0, 1, 3, 1, 2, 3];
+
+      let vbo = Buffer::new(gl::ARRAY_BUFFER);
+      vbo.upload(&vertices, gl::STATIC_DRAW);
+      let ebo = IndexBuffer::new(&indices, gl::STATIC_DRAW);
+
+      let vao = VertexArray::builder()
+        .vertex_buffer::<TexturedVertex>(&vbo)
+        .index_buffer(&ebo)
+        .build();
+
+      (vbo, ebo, vao)
     };
 
     // Texture
     let tex = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/container.jpg")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgb();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGB,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
 
     let tex2 = unsafe {
-      let mut tex = 0;
-      gl::GenTextures(1, &mut tex);
-      gl::BindTexture(gl::TEXTURE_2D, tex);
-
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-      gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
-
       let img = image::open("resources/textures/awesomeface.png")
         .expect("failed to load texture image")
-        .flipv()
-        .into_rgba();
-
-      let (width, height) = img.dimensions();
-
-      gl::TexImage2D(
-        gl::TEXTURE_2D,
-        0,
-        gl::RGB as i32,
-        width as i32,
-        height as i32,
-        0,
-        gl::RGBA,
-        gl::UNSIGNED_BYTE,
-        img.into_raw().as_ptr() as *const c_void,
-      );
-      gl::GenerateMipmap(gl::TEXTURE_2D);
+        .flipv();
+      let tex = Texture2D::from_image(&img).expect("failed to create texture");
+      tex.set_wrap(gl::REPEAT, gl::REPEAT);
+      tex.set_filter(gl::LINEAR, gl::LINEAR);
 
       tex
     };
@@ -249,13 +151,11 @@ void main()
         sp.use_program();
         sp.set_uniform_value("mixValue", mix_value);
 
-        gl::ActiveTexture(gl::TEXTURE0);
-        gl::BindTexture(gl::TEXTURE_2D, tex);
-        gl::ActiveTexture(gl::TEXTURE1);
-        gl::BindTexture(gl::TEXTURE_2D, tex2);
+        tex.bind_to_unit(TextureUnit(0));
+        tex2.bind_to_unit(TextureUnit(1));
 
-        gl::BindVertexArray(vao);
-        gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const c_void);
+        let _vao = vao.bind();
+        ebo.draw(gl::TRIANGLES);
       }
 
       window.swap_buffers();
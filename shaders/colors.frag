@@ -0,0 +1,17 @@
+#version 460 core
+out vec4 FragColor;
+
+uniform vec3 objectColor;
+uniform vec3 lightColor;
+
+#ifdef LIGHT_SOURCE
+void main()
+{
+  FragColor = vec4(1.0);
+}
+#else
+void main()
+{
+  FragColor = vec4(lightColor * objectColor, 1.0);
+}
+#endif
@@ -0,0 +1,6 @@
+#version 460 core
+out vec4 FragColor;
+
+void main() {
+  FragColor = vec4(0.99607f, 0.87450f, 0.88235f, 1.0f);
+}
@@ -0,0 +1,13 @@
+#version 460 core
+out vec4 FragColor;
+
+in vec3 Normal;
+in vec4 Color;
+
+uniform vec3 lightDir;
+
+void main()
+{
+  float diffuse = max(dot(normalize(Normal), -lightDir), 0.0);
+  FragColor = vec4(Color.rgb * (0.15 + 0.85 * diffuse), Color.a);
+}
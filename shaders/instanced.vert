@@ -0,0 +1,19 @@
+#version 460 core
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+layout (location = 3) in mat4 aModel;
+layout (location = 7) in vec4 aColor;
+
+out vec3 Normal;
+out vec4 Color;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+void main()
+{
+  gl_Position = projection * view * aModel * vec4(aPos, 1.0);
+  // Instances are only scaled uniformly, so the model matrix can transform normals too.
+  Normal = mat3(aModel) * aNormal;
+  Color = aColor;
+}
@@ -0,0 +1,14 @@
+#version 460 core
+out vec4 FragColor;
+
+in vec3 Normal;
+in vec4 Color;
+
+uniform vec3 lightDir;
+
+void main()
+{
+  // Scans often have inconsistent winding, so both sides are lit.
+  float diffuse = abs(dot(normalize(Normal), -lightDir));
+  FragColor = vec4(Color.rgb * (0.25 + 0.75 * diffuse), Color.a);
+}
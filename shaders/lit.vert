@@ -0,0 +1,115 @@
+#version 460 core
+// A mesh lit by `lightDir`, in `objectColor` unless VERTEX_COLOR is defined. SKINNED places it with the joint
+// matrices of a skin instead of `model`, and MORPHED adds the offsets of its morph targets first.
+layout (location = 0) in vec3 aPos;
+layout (location = 1) in vec3 aNormal;
+
+out vec3 Normal;
+out vec4 Color;
+
+uniform mat4 view;
+uniform mat4 projection;
+
+#ifdef VERTEX_COLOR
+layout (location = 8) in vec4 aColor;
+
+vec4 vertexColor()
+{
+  return aColor;
+}
+#else
+uniform vec4 objectColor;
+
+vec4 vertexColor()
+{
+  return objectColor;
+}
+#endif
+
+#ifdef SKINNED
+layout (location = 9) in uvec4 aJoints;
+layout (location = 10) in vec4 aWeights;
+
+// One matrix per joint of the skin, taking bind pose vertices into the world.
+layout (std430, binding = 0) readonly buffer JointMatrices
+{
+  mat4 joints[];
+};
+
+mat4 vertexTransform()
+{
+  return aWeights.x * joints[aJoints.x]
+       + aWeights.y * joints[aJoints.y]
+       + aWeights.z * joints[aJoints.z]
+       + aWeights.w * joints[aJoints.w];
+}
+
+// Joints rotate, translate and scale uniformly, so normals go through the blended matrix as they are.
+mat3 normalMatrix(mat4 transform)
+{
+  return mat3(transform);
+}
+#else
+uniform mat4 model;
+
+mat4 vertexTransform()
+{
+  return model;
+}
+
+mat3 normalMatrix(mat4 transform)
+{
+  return mat3(transpose(inverse(transform)));
+}
+#endif
+
+#ifdef MORPHED
+struct MorphDelta
+{
+  vec4 position;
+  vec4 normal;
+};
+
+// The offsets of every vertex for the first target, then for the second one and so on.
+layout (std430, binding = 1) readonly buffer MorphTargets
+{
+  MorphDelta deltas[];
+};
+
+// One weight per target, targets without one are left out.
+layout (std430, binding = 2) readonly buffer MorphWeights
+{
+  float weights[];
+};
+
+// The stride of the deltas and how many targets they hold.
+uniform int vertexCount;
+uniform int targetCount;
+
+void morph(inout vec3 position, inout vec3 normal)
+{
+  int targets = min(targetCount, weights.length());
+  for (int target = 0; target < targets; target++)
+  {
+    MorphDelta delta = deltas[target * vertexCount + gl_VertexID];
+    position += weights[target] * delta.position.xyz;
+    normal += weights[target] * delta.normal.xyz;
+  }
+}
+#else
+void morph(inout vec3 position, inout vec3 normal)
+{
+}
+#endif
+
+void main()
+{
+  vec3 position = aPos;
+  vec3 normal = aNormal;
+  morph(position, normal);
+
+  mat4 transform = vertexTransform();
+  gl_Position = projection * view * transform * vec4(position, 1.0);
+  Normal = normalMatrix(transform) * normal;
+  Color = vertexColor();
+}
@@ -0,0 +1,9 @@
+#version 460 core
+in vec3 vtxColor;
+uniform float uAlpha;
+
+out vec4 FragColor;
+
+void main() {
+  FragColor = vec4(vtxColor, uAlpha);
+}
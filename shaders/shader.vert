@@ -0,0 +1,12 @@
+#version 460 core
+layout (location=0) in vec3 aPos;
+layout (location=1) in vec3 aColor;
+
+uniform float xOffset;
+
+out vec3 vtxColor;
+
+void main() {
+  gl_Position = vec4(aPos.x + xOffset, -aPos.y, aPos.z, 1.0);
+  vtxColor = aColor;
+}
@@ -0,0 +1,11 @@
+#version 460 core
+// Only used as SPIR-V: the color is picked when the module is specialized.
+layout (constant_id = 0) const float red = 1.0;
+layout (constant_id = 1) const float green = 1.0;
+layout (constant_id = 2) const float blue = 1.0;
+
+layout (location = 0) out vec4 FragColor;
+
+void main() {
+  FragColor = vec4(red, green, blue, 1.0f);
+}
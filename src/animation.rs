@@ -0,0 +1,421 @@
+use nalgebra::{Matrix4, Quaternion, UnitQuaternion, Vector3};
+
+/// A node transform split into translation, rotation and scale, which animation channels target separately. The
+/// matrix applies the scale first, then the rotation, then the translation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+  pub translation: [f32; 3],
+  /// A unit quaternion, `[x, y, z, w]`.
+  pub rotation: [f32; 4],
+  pub scale: [f32; 3],
+}
+
+impl Transform {
+  pub const IDENTITY: Self = Self {
+    translation: [0.0; 3],
+    rotation: [0.0, 0.0, 0.0, 1.0],
+    scale: [1.0; 3],
+  };
+
+  /// The column-major matrix of the transform.
+  pub fn matrix(&self) -> [[f32; 4]; 4] {
+    let [x, y, z, w] = self.rotation;
+    let rotation = UnitQuaternion::new_unchecked(Quaternion::new(w, x, y, z));
+    let translation = Matrix4::new_translation(&Vector3::from(self.translation));
+    let scale = Matrix4::new_nonuniform_scaling(&Vector3::from(self.scale));
+    (translation * rotation.to_homogeneous() * scale).into()
+  }
+}
+
+impl Default for Transform {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+/// How a channel gets from one keyframe to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+  /// Holds each keyframe until the next one.
+  Step,
+  /// Straight lines between keyframes, spherical ones for rotations.
+  Linear,
+  /// Hermite splines, with an in-tangent and an out-tangent around every keyframe value.
+  CubicSpline,
+}
+
+/// The keyframe values of a channel. With `Interpolation::CubicSpline` there are three per keyframe: the
+/// in-tangent, the value and the out-tangent, like in glTF.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChannelValues {
+  Translation(Vec<[f32; 3]>),
+  Rotation(Vec<[f32; 4]>),
+  Scale(Vec<[f32; 3]>),
+  /// The weight of every morph target of the node's mesh, one after the other for each keyframe.
+  Weights(Vec<f32>),
+}
+
+/// Animates one property of a node's `Transform`, or the morph target weights of its mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+  /// The node animated, an index into the pose passed to `AnimationClip::apply`.
+  pub node: usize,
+  pub interpolation: Interpolation,
+  /// Keyframe times in seconds, increasing.
+  pub times: Vec<f32>,
+  pub values: ChannelValues,
+}
+
+impl Channel {
+  /// Sets the animated property of `transform` to its value at `time`. Before the first keyframe and after the last
+  /// one the property holds their values. Weight channels leave `transform` alone, see `apply_weights`.
+  pub fn apply(&self, time: f32, transform: &mut Transform) {
+    match &self.values {
+      ChannelValues::Translation(values) => transform.translation = sample(self, time, |i| values[i], lerp),
+      ChannelValues::Rotation(values) => transform.rotation = normalize(sample(self, time, |i| values[i], slerp)),
+      ChannelValues::Scale(values) => transform.scale = sample(self, time, |i| values[i], lerp),
+      ChannelValues::Weights(_) => {}
+    }
+  }
+
+  /// Sets `weights` to the morph target weights at `time` for a weight channel, and does nothing for the others.
+  pub fn apply_weights(&self, time: f32, weights: &mut [f32]) {
+    if let ChannelValues::Weights(values) = &self.values {
+      let per_key = if self.interpolation == Interpolation::CubicSpline {
+        3
+      } else {
+        1
+      };
+      let count = values.len() / (self.times.len() * per_key).max(1);
+      for (target, weight) in weights.iter_mut().enumerate().take(count) {
+        *weight = sample(self, time, |i| [values[i * count + target]], lerp)[0];
+      }
+    }
+  }
+}
+
+/// A set of channels played together, like a walk cycle.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnimationClip {
+  pub name: Option<String>,
+  pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+  /// The time of the last keyframe of any channel.
+  pub fn duration(&self) -> f32 {
+    self
+      .channels
+      .iter()
+      .filter_map(|channel| channel.times.last())
+      .fold(0.0, |a, &b| a.max(b))
+  }
+
+  /// Poses the nodes animated by the clip as they are at `time`, leaving the others alone. Loop the clip by
+  /// passing `time % duration()`.
+  pub fn apply(&self, time: f32, pose: &mut [Transform]) {
+    for channel in &self.channels {
+      channel.apply(time, &mut pose[channel.node]);
+    }
+  }
+
+  /// Sets the morph target weights animated by the clip as they are at `time`. Like the pose, `weights` has an entry
+  /// per node, with the weights of the node's mesh.
+  pub fn apply_weights(&self, time: f32, weights: &mut [Vec<f32>]) {
+    for channel in &self.channels {
+      channel.apply_weights(time, &mut weights[channel.node]);
+    }
+  }
+}
+
+/// The joints that deform a skinned mesh.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skin {
+  pub name: Option<String>,
+  /// The node of each joint, which `Mesh::joints` index into.
+  pub joints: Vec<usize>,
+  /// Per joint, the transform from the mesh's space to the joint's space in the bind pose. There is one for every
+  /// joint.
+  pub inverse_bind_matrices: Vec<[[f32; 4]; 4]>,
+}
+
+impl Skin {
+  /// The matrix of each joint, taking skinned vertices from the mesh's bind pose to their place in the world, given
+  /// the world transforms of every node. These are what the vertex shader blends with the vertex weights.
+  pub fn joint_matrices(&self, world_transforms: &[[[f32; 4]; 4]]) -> Vec<[[f32; 4]; 4]> {
+    self
+      .joints
+      .iter()
+      .zip(&self.inverse_bind_matrices)
+      .map(|(&joint, &inverse_bind)| (Matrix4::from(world_transforms[joint]) * Matrix4::from(inverse_bind)).into())
+      .collect()
+  }
+}
+
+/// The world transform of every node of a hierarchy, given each node's parent and local transform.
+///
+/// Panics if the parents form a cycle.
+pub fn world_transforms(parents: &[Option<usize>], pose: &[Transform]) -> Vec<[[f32; 4]; 4]> {
+  let mut world: Vec<Option<[[f32; 4]; 4]>> = vec![None; pose.len()];
+  let mut chain = Vec::new();
+  for node in 0..pose.len() {
+    // Climbs to the first ancestor already resolved, then resolves the nodes on the way back down.
+    let mut ancestor = Some(node);
+    while let Some(i) = ancestor.filter(|&i| world[i].is_none()) {
+      assert!(
+        chain.len() < pose.len(),
+        "the node hierarchy has a cycle through node {}",
+        i
+      );
+      chain.push(i);
+      ancestor = parents[i];
+    }
+    let mut transform = ancestor.and_then(|i| world[i]);
+    while let Some(i) = chain.pop() {
+      let local = pose[i].matrix();
+      let resolved = transform.map_or(local, |parent| (Matrix4::from(parent) * Matrix4::from(local)).into());
+      world[i] = Some(resolved);
+      transform = Some(resolved);
+    }
+  }
+  world.into_iter().map(Option::unwrap).collect()
+}
+
+/// The value of `channel` at `time`, interpolating between keyframes with `mix` for linear interpolation.
+/// `values(i)` is the `i`th of the channel's values, counting cubic spline tangents.
+fn sample<const N: usize>(
+  channel: &Channel,
+  time: f32,
+  values: impl Fn(usize) -> [f32; N],
+  mix: fn([f32; N], [f32; N], f32) -> [f32; N],
+) -> [f32; N] {
+  let times = &channel.times;
+  let cubic = channel.interpolation == Interpolation::CubicSpline;
+  let value = |key: usize| if cubic { values(3 * key + 1) } else { values(key) };
+
+  let next = times.partition_point(|&t| t <= time);
+  if next == 0 {
+    return value(0);
+  }
+  if next == times.len() {
+    return value(times.len() - 1);
+  }
+
+  let key = next - 1;
+  let span = times[next] - times[key];
+  let s = (time - times[key]) / span;
+  match channel.interpolation {
+    Interpolation::Step => value(key),
+    Interpolation::Linear => mix(value(key), value(next), s),
+    Interpolation::CubicSpline => {
+      let (s2, s3) = (s * s, s * s * s);
+      let out_tangent = values(3 * key + 2);
+      let in_tangent = values(3 * next);
+      let mut result = [0.0; N];
+      for (k, r) in result.iter_mut().enumerate() {
+        *r = (2.0 * s3 - 3.0 * s2 + 1.0) * value(key)[k]
+          + (s3 - 2.0 * s2 + s) * span * out_tangent[k]
+          + (-2.0 * s3 + 3.0 * s2) * value(next)[k]
+          + (s3 - s2) * span * in_tangent[k];
+      }
+      result
+    }
+  }
+}
+
+fn lerp<const N: usize>(a: [f32; N], b: [f32; N], s: f32) -> [f32; N] {
+  let mut result = a;
+  for (r, b) in result.iter_mut().zip(b) {
+    *r += (b - *r) * s;
+  }
+  result
+}
+
+fn dot4(a: [f32; 4], b: [f32; 4]) -> f32 {
+  (0..4).map(|k| a[k] * b[k]).sum()
+}
+
+fn normalize(q: [f32; 4]) -> [f32; 4] {
+  let length = dot4(q, q).sqrt();
+  q.map(|v| v / length)
+}
+
+/// Spherical interpolation between two unit quaternions, along the shorter arc.
+fn slerp(a: [f32; 4], b: [f32; 4], s: f32) -> [f32; 4] {
+  let mut cos = dot4(a, b);
+  let b = if cos < 0.0 {
+    cos = -cos;
+    b.map(|v| -v)
+  } else {
+    b
+  };
+
+  // Nearly equal rotations would divide by almost zero, and a straight line is as good there.
+  let (wa, wb) = if cos > 0.9995 {
+    (1.0 - s, s)
+  } else {
+    let angle = cos.acos();
+    let sin = angle.sin();
+    (((1.0 - s) * angle).sin() / sin, (s * angle).sin() / sin)
+  };
+  [0, 1, 2, 3].map(|k| a[k] * wa + b[k] * wb)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::f32::consts::FRAC_1_SQRT_2;
+
+  fn channel(interpolation: Interpolation, times: &[f32], values: ChannelValues) -> Channel {
+    Channel {
+      node: 0,
+      interpolation,
+      times: times.to_vec(),
+      values,
+    }
+  }
+
+  fn translation_at(channel: &Channel, time: f32) -> [f32; 3] {
+    let mut transform = Transform::IDENTITY;
+    channel.apply(time, &mut transform);
+    transform.translation
+  }
+
+  fn assert_close<const N: usize>(a: [f32; N], b: [f32; N]) {
+    assert!((0..N).all(|k| (a[k] - b[k]).abs() < 1e-5), "{:?} != {:?}", a, b);
+  }
+
+  #[test]
+  fn step_and_linear_interpolation() {
+    let values = ChannelValues::Translation(vec![[0.0; 3], [2.0, 4.0, 0.0], [2.0, 4.0, 8.0]]);
+    let step = channel(Interpolation::Step, &[1.0, 2.0, 4.0], values.clone());
+    let linear = channel(Interpolation::Linear, &[1.0, 2.0, 4.0], values);
+
+    assert_eq!(translation_at(&step, 0.0), [0.0; 3]);
+    assert_eq!(translation_at(&step, 1.5), [0.0; 3]);
+    assert_eq!(translation_at(&step, 2.0), [2.0, 4.0, 0.0]);
+    assert_eq!(translation_at(&step, 9.0), [2.0, 4.0, 8.0]);
+
+    assert_eq!(translation_at(&linear, 0.5), [0.0; 3]);
+    assert_close(translation_at(&linear, 1.5), [1.0, 2.0, 0.0]);
+    assert_close(translation_at(&linear, 3.0), [2.0, 4.0, 4.0]);
+    assert_eq!(translation_at(&linear, 5.0), [2.0, 4.0, 8.0]);
+  }
+
+  #[test]
+  fn cubic_splines_follow_their_tangents() {
+    // With tangents matching the slope of the line between the keys, the spline is that line.
+    let slope = [2.0, 0.0, -1.0];
+    let values = ChannelValues::Translation(vec![slope, [0.0; 3], slope, slope, [4.0, 0.0, -2.0], slope]);
+    let straight = channel(Interpolation::CubicSpline, &[0.0, 2.0], values);
+    assert_close(translation_at(&straight, 0.5), [1.0, 0.0, -0.5]);
+    assert_close(translation_at(&straight, 2.0), [4.0, 0.0, -2.0]);
+
+    // With flat tangents it eases in and out, passing the middle halfway.
+    let values = ChannelValues::Translation(vec![[0.0; 3], [0.0; 3], [0.0; 3], [0.0; 3], [1.0; 3], [0.0; 3]]);
+    let eased = channel(Interpolation::CubicSpline, &[0.0, 1.0], values);
+    assert_close(translation_at(&eased, 0.5), [0.5; 3]);
+    assert!(translation_at(&eased, 0.1)[0] < 0.1);
+  }
+
+  #[test]
+  fn rotations_take_the_shorter_arc() {
+    let quarter_turn = [0.0, FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2];
+    // The same quarter turn as a negated quaternion.
+    let negated = quarter_turn.map(|v| -v);
+    let rotation = channel(
+      Interpolation::Linear,
+      &[0.0, 1.0],
+      ChannelValues::Rotation(vec![[0.0, 0.0, 0.0, 1.0], negated]),
+    );
+
+    let mut transform = Transform::IDENTITY;
+    rotation.apply(0.5, &mut transform);
+    let eighth_turn = [
+      0.0,
+      (std::f32::consts::PI / 8.0).sin(),
+      0.0,
+      (std::f32::consts::PI / 8.0).cos(),
+    ];
+    assert_close(transform.rotation, eighth_turn);
+  }
+
+  #[test]
+  fn weight_channels_set_every_target() {
+    let weights = channel(
+      Interpolation::Step,
+      &[0.0, 1.0],
+      ChannelValues::Weights(vec![1.0, 0.0, 0.5, 0.5]),
+    );
+    let mut targets = [0.25; 3];
+    weights.apply_weights(0.5, &mut targets);
+    assert_eq!(targets, [1.0, 0.0, 0.25]);
+    weights.apply_weights(2.0, &mut targets);
+    assert_eq!(targets, [0.5, 0.5, 0.25]);
+
+    // Weights don't touch transforms, nor transform channels weights.
+    let mut transform = Transform::IDENTITY;
+    weights.apply(0.5, &mut transform);
+    assert_eq!(transform, Transform::IDENTITY);
+    let translation = channel(Interpolation::Step, &[0.0], ChannelValues::Translation(vec![[1.0; 3]]));
+    translation.apply_weights(0.0, &mut targets);
+    assert_eq!(targets, [0.5, 0.5, 0.25]);
+  }
+
+  #[test]
+  fn transform_matrices() {
+    let transform = Transform {
+      translation: [1.0, 2.0, 3.0],
+      rotation: [0.0, 0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2],
+      scale: [2.0, 2.0, 2.0],
+    };
+    // Scaled to 2, turned a quarter around z, then moved.
+    let m = transform.matrix();
+    assert_close(m[0], [0.0, 2.0, 0.0, 0.0]);
+    assert_close(m[1], [-2.0, 0.0, 0.0, 0.0]);
+    assert_close(m[2], [0.0, 0.0, 2.0, 0.0]);
+    assert_eq!(m[3], [1.0, 2.0, 3.0, 1.0]);
+    assert_eq!(Transform::IDENTITY.matrix(), Transform::default().matrix());
+  }
+
+  #[test]
+  fn joint_matrices_are_identity_in_the_bind_pose() {
+    // A two-bone chain, with the child listed before its parent.
+    let parents = [Some(1), None];
+    let pose = [
+      Transform {
+        translation: [0.0, 1.0, 0.0],
+        ..Transform::IDENTITY
+      },
+      Transform {
+        translation: [0.0, 0.0, -2.0],
+        rotation: [0.0, FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2],
+        ..Transform::IDENTITY
+      },
+    ];
+    let world = world_transforms(&parents, &pose);
+    assert_close(world[0][3], [0.0, 1.0, -2.0, 1.0]);
+
+    let skin = Skin {
+      name: None,
+      joints: vec![1, 0],
+      inverse_bind_matrices: vec![
+        Matrix4::from(world[1]).try_inverse().unwrap().into(),
+        Matrix4::from(world[0]).try_inverse().unwrap().into(),
+      ],
+    };
+    for matrix in skin.joint_matrices(&world) {
+      for (k, column) in matrix.iter().enumerate() {
+        let mut identity = [0.0; 4];
+        identity[k] = 1.0;
+        assert_close(*column, identity);
+      }
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "cycle")]
+  fn cycles_panic_instead_of_overflowing() {
+    world_transforms(&[None, Some(2), Some(1)], &[Transform::IDENTITY; 3]);
+  }
+}
@@ -0,0 +1,424 @@
+use nalgebra::{Matrix4, Point3};
+
+/// An axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+  pub min: [f32; 3],
+  pub max: [f32; 3],
+}
+
+/// A sphere around an object, cheaper than an `Aabb` to test and to transform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+  pub center: [f32; 3],
+  pub radius: f32,
+}
+
+/// Both bounding volumes of an object, for tests that prefer one or the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+  pub aabb: Aabb,
+  pub sphere: BoundingSphere,
+}
+
+impl Aabb {
+  /// The smallest box around `points`, `None` when there are none.
+  pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+    let (first, rest) = points.split_first()?;
+    let point = Self {
+      min: *first,
+      max: *first,
+    };
+    Some(rest.iter().fold(point, |aabb, &p| aabb.union(&Self { min: p, max: p })))
+  }
+
+  pub fn center(&self) -> [f32; 3] {
+    [0, 1, 2].map(|k| (self.min[k] + self.max[k]) / 2.0)
+  }
+
+  /// Half the size along each axis.
+  pub fn extents(&self) -> [f32; 3] {
+    [0, 1, 2].map(|k| (self.max[k] - self.min[k]) / 2.0)
+  }
+
+  pub fn contains(&self, point: [f32; 3]) -> bool {
+    (0..3).all(|k| self.min[k] <= point[k] && point[k] <= self.max[k])
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    Self {
+      min: [0, 1, 2].map(|k| self.min[k].min(other.min[k])),
+      max: [0, 1, 2].map(|k| self.max[k].max(other.max[k])),
+    }
+  }
+
+  /// The box around this one once transformed by `model`. It's larger than the box around the transformed
+  /// contents when `model` rotates.
+  pub fn transformed(&self, model: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+    let m = Matrix4::from(model.into());
+    let center = transform_point(&m, self.center());
+    let extents = self.extents();
+    // Each axis of the new box spans the absolute projections of the old box's edges onto it.
+    let new_extents = [0, 1, 2].map(|row| (0..3).map(|col| m[(row, col)].abs() * extents[col]).sum::<f32>());
+    Self {
+      min: [0, 1, 2].map(|k| center[k] - new_extents[k]),
+      max: [0, 1, 2].map(|k| center[k] + new_extents[k]),
+    }
+  }
+}
+
+impl BoundingSphere {
+  /// A sphere around `points`, `None` when there are none.
+  ///
+  /// This is Ritter's approximation, or the sphere around the bounding box when that's smaller, so it can be a few
+  /// percent larger than the smallest possible sphere.
+  pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+    let aabb = Aabb::from_points(points)?;
+    let box_center = aabb.center();
+    let box_sphere = Self {
+      center: box_center,
+      radius: points.iter().map(|&p| distance(p, box_center)).fold(0.0, f32::max),
+    };
+
+    // Start from two points far apart, then grow the sphere to take in every point outside of it.
+    let farthest_from = |from: [f32; 3]| -> [f32; 3] {
+      *points
+        .iter()
+        .max_by(|a, b| distance(**a, from).total_cmp(&distance(**b, from)))
+        .unwrap()
+    };
+    let a = farthest_from(points[0]);
+    let b = farthest_from(a);
+    let mut ritter = Self {
+      center: [0, 1, 2].map(|k| (a[k] + b[k]) / 2.0),
+      radius: distance(a, b) / 2.0,
+    };
+    for &p in points {
+      let d = distance(p, ritter.center);
+      if d > ritter.radius {
+        let radius = (ritter.radius + d) / 2.0;
+        let shift = (radius - ritter.radius) / d;
+        ritter.center = [0, 1, 2].map(|k| ritter.center[k] + (p[k] - ritter.center[k]) * shift);
+        ritter.radius = radius;
+      }
+    }
+
+    Some(if ritter.radius < box_sphere.radius {
+      ritter
+    } else {
+      box_sphere
+    })
+  }
+
+  pub fn contains(&self, point: [f32; 3]) -> bool {
+    distance(point, self.center) <= self.radius
+  }
+
+  /// The sphere around this one once transformed by `model`, scaled by the largest scale of `model`.
+  pub fn transformed(&self, model: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+    let m = Matrix4::from(model.into());
+    let scale = (0..3).map(|col| m.column(col).xyz().norm()).fold(0.0, f32::max);
+    Self {
+      center: transform_point(&m, self.center),
+      radius: self.radius * scale,
+    }
+  }
+
+  /// How far from the center a perspective camera with a vertical field of view of `fov_y` radians and an
+  /// `aspect` ratio (width over height) has to be to see the whole sphere.
+  pub fn framing_distance(&self, fov_y: f32, aspect: f32) -> f32 {
+    let fov_x = 2.0 * ((fov_y / 2.0).tan() * aspect).atan();
+    self.radius / (fov_y.min(fov_x) / 2.0).sin()
+  }
+}
+
+impl Bounds {
+  /// The bounds of `points`, `None` when there are none.
+  pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+    Some(Self {
+      aabb: Aabb::from_points(points)?,
+      sphere: BoundingSphere::from_points(points)?,
+    })
+  }
+
+  pub fn transformed(&self, model: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+    let model = model.into();
+    Self {
+      aabb: self.aabb.transformed(model),
+      sphere: self.sphere.transformed(model),
+    }
+  }
+}
+
+/// The six planes of a camera's view volume, for culling objects outside of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frustum {
+  /// `[a, b, c, d]` with the unit normal `[a, b, c]` pointing inside, so inside points have `a x + b y + c z + d >= 0`.
+  pub planes: [[f32; 4]; 6],
+}
+
+impl Frustum {
+  /// The frustum of `view_projection`, the projection matrix times the view matrix, in world space.
+  pub fn from_matrix(view_projection: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+    let m = Matrix4::from(view_projection.into());
+    let row = |i: usize| m.row(i).transpose();
+    let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+    // Gribb and Hartmann: a point is inside when -w <= x, y, z <= w in clip space.
+    let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| (plane / plane.xyz().norm()).into());
+    Self { planes }
+  }
+
+  pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+    self
+      .planes
+      .iter()
+      .all(|plane| plane_distance(plane, sphere.center) >= -sphere.radius)
+  }
+
+  /// Whether some of `aabb` may be inside. Boxes near the corners of the frustum can pass without being inside.
+  pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+    self.planes.iter().all(|plane| {
+      // The corner furthest along the plane normal.
+      let corner = [0, 1, 2].map(|k| if plane[k] >= 0.0 { aabb.max[k] } else { aabb.min[k] });
+      plane_distance(plane, corner) >= 0.0
+    })
+  }
+}
+
+/// A half-line for picking, with a unit `direction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+  pub origin: [f32; 3],
+  pub direction: [f32; 3],
+}
+
+impl Ray {
+  pub fn new(origin: [f32; 3], direction: [f32; 3]) -> Self {
+    let length = distance(direction, [0.0; 3]);
+    Self {
+      origin,
+      direction: direction.map(|v| v / length),
+    }
+  }
+
+  /// The world space ray through the pixel at (`x`, `y`), counted from the top left like cursor positions, of a
+  /// `width`×`height` viewport showing `view_projection`. `None` if `view_projection` can't be inverted.
+  pub fn from_cursor(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    view_projection: impl Into<mint::ColumnMatrix4<f32>>,
+  ) -> Option<Self> {
+    let inverse = Matrix4::from(view_projection.into()).try_inverse()?;
+    let (ndc_x, ndc_y) = (2.0 * x / width - 1.0, 1.0 - 2.0 * y / height);
+    let unproject = |ndc_z: f32| transform_point(&inverse, [ndc_x, ndc_y, ndc_z]);
+    let (near, far) = (unproject(-1.0), unproject(1.0));
+    Some(Self::new(near, [0, 1, 2].map(|k| far[k] - near[k])))
+  }
+
+  /// The distance along the ray to where it enters `aabb`, 0 if it starts inside.
+  pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+    let (mut near, mut far) = (0.0f32, f32::INFINITY);
+    for k in 0..3 {
+      let inverse = 1.0 / self.direction[k];
+      let t0 = (aabb.min[k] - self.origin[k]) * inverse;
+      let t1 = (aabb.max[k] - self.origin[k]) * inverse;
+      // A NaN comes from a ray parallel to a slab starting on its boundary, which counts as inside.
+      if t0.is_nan() || t1.is_nan() {
+        continue;
+      }
+      near = near.max(t0.min(t1));
+      far = far.min(t0.max(t1));
+    }
+    if near <= far {
+      Some(near)
+    } else {
+      None
+    }
+  }
+
+  /// The distance along the ray to where it enters `sphere`, 0 if it starts inside.
+  pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+    let to_center = [0, 1, 2].map(|k| sphere.center[k] - self.origin[k]);
+    let along = (0..3).map(|k| to_center[k] * self.direction[k]).sum::<f32>();
+    let off_ray_squared = (0..3).map(|k| to_center[k] * to_center[k]).sum::<f32>() - along * along;
+    let half_chord_squared = sphere.radius * sphere.radius - off_ray_squared;
+    if half_chord_squared < 0.0 {
+      return None;
+    }
+    let (enter, exit) = (along - half_chord_squared.sqrt(), along + half_chord_squared.sqrt());
+    if exit < 0.0 {
+      None
+    } else {
+      Some(enter.max(0.0))
+    }
+  }
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+  (0..3).map(|k| (a[k] - b[k]) * (a[k] - b[k])).sum::<f32>().sqrt()
+}
+
+/// `p` transformed by `m`, divided by `w` for projections.
+fn transform_point(m: &Matrix4<f32>, p: [f32; 3]) -> [f32; 3] {
+  m.transform_point(&Point3::from(p)).coords.into()
+}
+
+fn plane_distance(plane: &[f32; 4], point: [f32; 3]) -> f32 {
+  plane[0] * point[0] + plane[1] * point[1] + plane[2] * point[2] + plane[3]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Mesh;
+
+  fn assert_close(a: [f32; 3], b: [f32; 3]) {
+    assert!(distance(a, b) < 1e-4, "{:?} != {:?}", a, b);
+  }
+
+  fn rotation_y(angle: f32) -> [[f32; 4]; 4] {
+    let (s, c) = angle.sin_cos();
+    [
+      [c, 0.0, -s, 0.0],
+      [0.0, 1.0, 0.0, 0.0],
+      [s, 0.0, c, 0.0],
+      [0.0, 0.0, 0.0, 1.0],
+    ]
+  }
+
+  fn translation_scale(t: [f32; 3], s: f32) -> [[f32; 4]; 4] {
+    [
+      [s, 0.0, 0.0, 0.0],
+      [0.0, s, 0.0, 0.0],
+      [0.0, 0.0, s, 0.0],
+      [t[0], t[1], t[2], 1.0],
+    ]
+  }
+
+  /// A GL perspective projection looking down -z from the origin, like `glm::perspective`.
+  fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> [[f32; 4]; 4] {
+    let f = 1.0 / (fov_y / 2.0).tan();
+    [
+      [f / aspect, 0.0, 0.0, 0.0],
+      [0.0, f, 0.0, 0.0],
+      [0.0, 0.0, (far + near) / (near - far), -1.0],
+      [0.0, 0.0, 2.0 * far * near / (near - far), 0.0],
+    ]
+  }
+
+  #[test]
+  fn mesh_bounds() {
+    let bounds = Mesh::cube(2.0).bounds().unwrap();
+    assert_eq!(bounds.aabb.min, [-1.0; 3]);
+    assert_eq!(bounds.aabb.max, [1.0; 3]);
+    assert_close(bounds.sphere.center, [0.0; 3]);
+    assert!((bounds.sphere.radius - 3f32.sqrt()).abs() < 1e-4);
+
+    let sphere = Mesh::uv_sphere(2.0, 32, 16).bounds().unwrap().sphere;
+    assert!(sphere.radius >= 2.0 - 1e-5 && sphere.radius < 2.0 * 1.02);
+    assert!(Mesh::default().bounds().is_none());
+  }
+
+  #[test]
+  fn transformed_volumes_contain_transformed_points() {
+    let mesh = Mesh::torus(1.0, 0.25, 16, 8);
+    let bounds = mesh.bounds().unwrap();
+    for model in [rotation_y(0.7), translation_scale([3.0, -1.0, 2.0], 2.5)].iter() {
+      let moved = bounds.transformed(*model);
+      for &p in &mesh.positions {
+        let p = transform_point(&Matrix4::from(*model), p);
+        let slack = [0, 1, 2].map(|k| p[k] - (p[k] - moved.sphere.center[k]) * 1e-5);
+        assert!(moved.aabb.contains(slack) && moved.sphere.contains(slack));
+      }
+    }
+
+    // A quarter turn keeps a box a box.
+    let aabb = Aabb {
+      min: [-1.0, -2.0, -3.0],
+      max: [1.0, 2.0, 3.0],
+    };
+    let turned = aabb.transformed(rotation_y(std::f32::consts::FRAC_PI_2));
+    assert_close(turned.min, [-3.0, -2.0, -1.0]);
+    assert_close(turned.max, [3.0, 2.0, 1.0]);
+  }
+
+  #[test]
+  fn frustum_culling() {
+    let frustum = Frustum::from_matrix(perspective(1.0, 1.5, 0.1, 100.0));
+    let sphere = |center, radius| BoundingSphere { center, radius };
+
+    assert!(frustum.intersects_sphere(&sphere([0.0, 0.0, -10.0], 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere([0.0, 0.0, 10.0], 1.0)), "behind");
+    assert!(
+      !frustum.intersects_sphere(&sphere([0.0, 0.0, -200.0], 1.0)),
+      "beyond far"
+    );
+    assert!(!frustum.intersects_sphere(&sphere([50.0, 0.0, -10.0], 1.0)), "right");
+    assert!(
+      frustum.intersects_sphere(&sphere([0.0, 0.0, 0.5], 1.0)),
+      "around the eye"
+    );
+
+    let unit = |c: [f32; 3]| Aabb {
+      min: c.map(|v| v - 0.5),
+      max: c.map(|v| v + 0.5),
+    };
+    assert!(frustum.intersects_aabb(&unit([0.0, 0.0, -5.0])));
+    assert!(!frustum.intersects_aabb(&unit([0.0, -30.0, -5.0])));
+  }
+
+  #[test]
+  fn picking_rays() {
+    let projection = perspective(1.0, 2.0, 0.1, 100.0);
+    let ray = Ray::from_cursor(400.0, 200.0, 800.0, 400.0, projection).unwrap();
+    assert_close(ray.origin, [0.0, 0.0, -0.1]);
+    assert_close(ray.direction, [0.0, 0.0, -1.0]);
+
+    // The top left corner looks up and to the left.
+    let corner = Ray::from_cursor(0.0, 0.0, 800.0, 400.0, projection).unwrap();
+    assert!(corner.direction[0] < 0.0 && corner.direction[1] > 0.0);
+
+    let aabb = Aabb {
+      min: [-1.0, -1.0, -6.0],
+      max: [1.0, 1.0, -4.0],
+    };
+    assert!((ray.intersect_aabb(&aabb).unwrap() - 3.9).abs() < 1e-4);
+    assert_eq!(corner.intersect_aabb(&aabb), None);
+
+    let sphere = BoundingSphere {
+      center: [0.0, 0.0, -5.0],
+      radius: 1.0,
+    };
+    assert!((ray.intersect_sphere(&sphere).unwrap() - 3.9).abs() < 1e-4);
+    assert_eq!(corner.intersect_sphere(&sphere), None);
+    let inside = Ray::new([0.0, 0.0, -5.0], [1.0, 0.0, 0.0]);
+    assert_eq!(inside.intersect_sphere(&sphere), Some(0.0));
+    assert_eq!(inside.intersect_aabb(&aabb), Some(0.0));
+  }
+
+  #[test]
+  fn framed_spheres_touch_the_narrower_side() {
+    let sphere = BoundingSphere {
+      center: [0.0; 3],
+      radius: 1.0,
+    };
+    let (fov_y, aspect) = (1.0f32, 1.5);
+    let d = sphere.framing_distance(fov_y, aspect);
+    // Seen from `d`, the sphere spans exactly the vertical field of view.
+    assert!(((1.0 / d).asin() - fov_y / 2.0).abs() < 1e-5);
+
+    // In portrait, the width is the limit.
+    let fov_x = 2.0 * ((fov_y / 2.0).tan() * 0.5).atan();
+    let d = sphere.framing_distance(fov_y, 0.5);
+    assert!(((1.0 / d).asin() - fov_x / 2.0).abs() < 1e-5);
+  }
+
+  #[test]
+  fn nan_points_dont_panic() {
+    let points = [[0.0, 0.0, 0.0], [f32::NAN, 0.0, 0.0], [1.0, 0.0, 0.0]];
+    assert!(Bounds::from_points(&points).is_some());
+  }
+}
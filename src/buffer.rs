@@ -0,0 +1,106 @@
+use gl::types::{GLenum, GLintptr, GLsizeiptr, GLuint};
+use std::ffi::c_void;
+use std::mem;
+
+/// A GL buffer object, deleted on drop. Edited through direct state access, so only drawing needs it bound.
+pub struct Buffer {
+  id: GLuint,
+  target: GLenum,
+}
+
+impl Buffer {
+  /// Creates a buffer that will be bound to `target`, e.g. `gl::ARRAY_BUFFER`.
+  pub unsafe fn new(target: GLenum) -> Self {
+    let mut id = 0;
+    gl::CreateBuffers(1, &mut id);
+    Self { id, target }
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+
+  pub fn target(&self) -> GLenum {
+    self.target
+  }
+
+  /// Binds the buffer to its target until the returned guard is dropped.
+  pub unsafe fn bind(&self) -> BufferBinding<'_> {
+    let mut previous = 0;
+    gl::GetIntegerv(binding_query(self.target), &mut previous);
+    gl::BindBuffer(self.target, self.id);
+
+    BufferBinding {
+      buffer: self,
+      previous: previous as GLuint,
+    }
+  }
+
+  /// Binds the buffer to binding point `index` of its target, which has to be an indexed one like
+  /// `gl::UNIFORM_BUFFER` or `gl::SHADER_STORAGE_BUFFER`. The binding stays until something else takes the index.
+  pub unsafe fn bind_base(&self, index: u32) {
+    gl::BindBufferBase(self.target, index, self.id);
+  }
+
+  /// Allocates the buffer and fills it with `data`.
+  pub unsafe fn upload<T: Copy>(&self, data: &[T], usage: GLenum) {
+    gl::NamedBufferData(
+      self.id,
+      mem::size_of_val(data) as GLsizeiptr,
+      data.as_ptr() as *const c_void,
+      usage,
+    );
+  }
+
+  /// Overwrites part of the buffer, starting at element `offset` counted in `T`s.
+  pub unsafe fn update<T: Copy>(&self, offset: usize, data: &[T]) {
+    gl::NamedBufferSubData(
+      self.id,
+      (offset * mem::size_of::<T>()) as GLintptr,
+      mem::size_of_val(data) as GLsizeiptr,
+      data.as_ptr() as *const c_void,
+    );
+  }
+}
+
+impl Drop for Buffer {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteBuffers(1, &self.id);
+    }
+  }
+}
+
+/// Restores the previously bound buffer of the same target when dropped.
+#[must_use]
+pub struct BufferBinding<'a> {
+  buffer: &'a Buffer,
+  previous: GLuint,
+}
+
+impl Drop for BufferBinding<'_> {
+  fn drop(&mut self) {
+    unsafe {
+      gl::BindBuffer(self.buffer.target, self.previous);
+    }
+  }
+}
+
+fn binding_query(target: GLenum) -> GLenum {
+  match target {
+    gl::ARRAY_BUFFER => gl::ARRAY_BUFFER_BINDING,
+    gl::ELEMENT_ARRAY_BUFFER => gl::ELEMENT_ARRAY_BUFFER_BINDING,
+    gl::UNIFORM_BUFFER => gl::UNIFORM_BUFFER_BINDING,
+    gl::SHADER_STORAGE_BUFFER => gl::SHADER_STORAGE_BUFFER_BINDING,
+    gl::DRAW_INDIRECT_BUFFER => gl::DRAW_INDIRECT_BUFFER_BINDING,
+    gl::DISPATCH_INDIRECT_BUFFER => gl::DISPATCH_INDIRECT_BUFFER_BINDING,
+    gl::COPY_READ_BUFFER => gl::COPY_READ_BUFFER_BINDING,
+    gl::COPY_WRITE_BUFFER => gl::COPY_WRITE_BUFFER_BINDING,
+    gl::PIXEL_PACK_BUFFER => gl::PIXEL_PACK_BUFFER_BINDING,
+    gl::PIXEL_UNPACK_BUFFER => gl::PIXEL_UNPACK_BUFFER_BINDING,
+    gl::TRANSFORM_FEEDBACK_BUFFER => gl::TRANSFORM_FEEDBACK_BUFFER_BINDING,
+    gl::ATOMIC_COUNTER_BUFFER => gl::ATOMIC_COUNTER_BUFFER_BINDING,
+    gl::QUERY_BUFFER => gl::QUERY_BUFFER_BINDING,
+    _ => panic!("unsupported buffer target {:#06x}", target),
+  }
+}
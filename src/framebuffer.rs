@@ -0,0 +1,142 @@
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use std::error::Error;
+use std::fmt;
+
+use crate::texture::Texture2D;
+
+/// A GL framebuffer object, deleted on drop. Attachments are set through direct state access, without binding it.
+pub struct Framebuffer {
+  id: GLuint,
+}
+
+impl Framebuffer {
+  pub unsafe fn new() -> Self {
+    let mut id = 0;
+    gl::CreateFramebuffers(1, &mut id);
+    Self { id }
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+
+  /// Renders into mip level `level` of `texture` through `attachment`, e.g. `gl::COLOR_ATTACHMENT0`.
+  pub unsafe fn attach_texture(&self, attachment: GLenum, texture: &Texture2D, level: u32) {
+    gl::NamedFramebufferTexture(self.id, attachment, texture.id(), level as GLint);
+  }
+
+  pub unsafe fn attach_renderbuffer(&self, attachment: GLenum, renderbuffer: &Renderbuffer) {
+    gl::NamedFramebufferRenderbuffer(self.id, attachment, gl::RENDERBUFFER, renderbuffer.id());
+  }
+
+  /// Selects the color attachments fragment shader outputs 0, 1, … are written to.
+  pub unsafe fn set_draw_buffers(&self, attachments: &[GLenum]) {
+    gl::NamedFramebufferDrawBuffers(self.id, attachments.len() as GLsizei, attachments.as_ptr());
+  }
+
+  /// Checks that the attachments make a framebuffer that can be drawn to.
+  pub unsafe fn check(&self) -> Result<(), FramebufferError> {
+    match gl::CheckNamedFramebufferStatus(self.id, gl::FRAMEBUFFER) {
+      gl::FRAMEBUFFER_COMPLETE => Ok(()),
+      status => Err(FramebufferError { status }),
+    }
+  }
+
+  /// Binds the framebuffer for drawing and reading until the returned guard is dropped.
+  pub unsafe fn bind(&self) -> FramebufferBinding<'_> {
+    let (mut draw, mut read) = (0, 0);
+    gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut draw);
+    gl::GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut read);
+    gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+
+    FramebufferBinding {
+      _framebuffer: self,
+      previous_draw: draw as GLuint,
+      previous_read: read as GLuint,
+    }
+  }
+}
+
+impl Drop for Framebuffer {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteFramebuffers(1, &self.id);
+    }
+  }
+}
+
+/// Restores the previously bound draw and read framebuffers when dropped.
+#[must_use]
+pub struct FramebufferBinding<'a> {
+  _framebuffer: &'a Framebuffer,
+  previous_draw: GLuint,
+  previous_read: GLuint,
+}
+
+impl Drop for FramebufferBinding<'_> {
+  fn drop(&mut self) {
+    unsafe {
+      gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, self.previous_draw);
+      gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.previous_read);
+    }
+  }
+}
+
+/// A GL renderbuffer, for framebuffer attachments that are never sampled like depth buffers. Deleted on drop.
+pub struct Renderbuffer {
+  id: GLuint,
+}
+
+impl Renderbuffer {
+  /// Allocates storage of `internal_format`, e.g. `gl::DEPTH24_STENCIL8`, with `samples` samples per pixel or
+  /// none for `0`.
+  pub unsafe fn new(width: u32, height: u32, internal_format: GLenum, samples: u32) -> Self {
+    let mut id = 0;
+    gl::CreateRenderbuffers(1, &mut id);
+    gl::NamedRenderbufferStorageMultisample(
+      id,
+      samples as GLsizei,
+      internal_format,
+      width as GLsizei,
+      height as GLsizei,
+    );
+    Self { id }
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+}
+
+impl Drop for Renderbuffer {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteRenderbuffers(1, &self.id);
+    }
+  }
+}
+
+/// Returned by `Framebuffer::check` for an incomplete framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FramebufferError {
+  pub status: GLenum,
+}
+
+impl fmt::Display for FramebufferError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let reason = match self.status {
+      gl::FRAMEBUFFER_UNDEFINED => "GL_FRAMEBUFFER_UNDEFINED",
+      gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+      gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => "GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT",
+      gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "GL_FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER",
+      gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "GL_FRAMEBUFFER_INCOMPLETE_READ_BUFFER",
+      gl::FRAMEBUFFER_UNSUPPORTED => "GL_FRAMEBUFFER_UNSUPPORTED",
+      gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "GL_FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+      gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => "GL_FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS",
+      _ => return write!(f, "framebuffer is incomplete (status {:#06x})", self.status),
+    };
+    write!(f, "framebuffer is incomplete: {}", reason)
+  }
+}
+
+impl Error for FramebufferError {}
@@ -0,0 +1,32 @@
+//! OpenGL 4.6 entry points missing from the 4.5 bindings generated by the `gl` crate.
+
+use gl::types::{GLchar, GLenum, GLuint};
+use std::ffi::c_void;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const SHADER_BINARY_FORMAT_SPIR_V: GLenum = 0x9551;
+
+type SpecializeShaderFn = extern "system" fn(GLuint, *const GLchar, GLuint, *const GLuint, *const GLuint);
+
+static SPECIALIZE_SHADER: AtomicUsize = AtomicUsize::new(0);
+
+pub fn load_with<F: FnMut(&'static str) -> *const c_void>(mut loadfn: F) {
+  let mut f = loadfn("glSpecializeShader");
+  if f.is_null() {
+    f = loadfn("glSpecializeShaderARB");
+  }
+  SPECIALIZE_SHADER.store(f as usize, Ordering::Relaxed);
+}
+
+pub unsafe fn specialize_shader(
+  shader: GLuint,
+  entry_point: *const GLchar,
+  num_constants: GLuint,
+  constant_index: *const GLuint,
+  constant_value: *const GLuint,
+) {
+  let f = SPECIALIZE_SHADER.load(Ordering::Relaxed);
+  assert!(f != 0, "glSpecializeShader isn't loaded, use learn_opengl::load_with");
+  mem::transmute::<usize, SpecializeShaderFn>(f)(shader, entry_point, num_constants, constant_index, constant_value)
+}
@@ -0,0 +1,12 @@
+pub mod gltf;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+pub use self::gltf::{
+  AlphaMode, GltfCamera, GltfError, GltfImage, GltfMaterial, GltfMesh, GltfNode, GltfPrimitive, GltfProjection,
+  GltfScene, GltfTexture,
+};
+pub use obj::{ObjError, ObjMaterial, ObjMesh, ObjModel};
+pub use ply::{load_ply, parse_ply, PlyError};
+pub use stl::{load_stl, parse_stl, StlError};
@@ -0,0 +1,602 @@
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use gl::types::GLenum;
+use gltf::animation::util::ReadOutputs;
+use gltf::mesh::Mode;
+use nalgebra::Matrix4;
+
+use crate::animation::{self, AnimationClip, Channel, ChannelValues, Interpolation, Skin, Transform};
+use crate::mesh::{Mesh, MorphTarget};
+
+/// A glTF 2.0 file (`.gltf` with its buffers and images, or `.glb`) converted to the crate's own types.
+///
+/// Everything keeps its glTF index, so `GltfNode::mesh` indexes `meshes`, `GltfMaterial::base_color_texture`
+/// indexes `textures` and so on.
+#[derive(Clone, Debug, Default)]
+pub struct GltfScene {
+  pub meshes: Vec<GltfMesh>,
+  pub materials: Vec<GltfMaterial>,
+  pub textures: Vec<GltfTexture>,
+  pub images: Vec<GltfImage>,
+  pub cameras: Vec<GltfCamera>,
+  pub nodes: Vec<GltfNode>,
+  pub skins: Vec<Skin>,
+  pub animations: Vec<AnimationClip>,
+  /// The top-level nodes of the default scene, or of the first scene if none is marked as default.
+  pub roots: Vec<usize>,
+}
+
+/// A glTF mesh, with one `Mesh` per primitive since each primitive has its own material.
+#[derive(Clone, Debug)]
+pub struct GltfMesh {
+  pub name: Option<String>,
+  pub primitives: Vec<GltfPrimitive>,
+  /// The default weight of each morph target, shared by all the primitives.
+  pub weights: Vec<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfPrimitive {
+  /// Index into `GltfScene::materials`, `None` for the glTF default material.
+  pub material: Option<usize>,
+  pub mesh: Mesh,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfNode {
+  pub name: Option<String>,
+  /// The column-major transform relative to the parent node.
+  pub transform: [[f32; 4]; 4],
+  /// `transform` split into the parts animations replace, the rest pose of the node.
+  pub pose: Transform,
+  pub parent: Option<usize>,
+  pub children: Vec<usize>,
+  pub mesh: Option<usize>,
+  /// Index into `GltfScene::skins` for skinned meshes, which the joints place in the world instead of this node.
+  pub skin: Option<usize>,
+  /// Morph target weights replacing the defaults of the mesh for this node.
+  pub weights: Option<Vec<f32>>,
+  pub camera: Option<usize>,
+}
+
+/// A PBR metallic-roughness material. Fields missing from the file have the glTF defaults.
+///
+/// Texture indices point into `GltfScene::textures`, and all of them are sampled with the first set of texture
+/// coordinates, the only one imported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMaterial {
+  pub name: Option<String>,
+  pub base_color: [f32; 4],
+  pub base_color_texture: Option<usize>,
+  pub metallic: f32,
+  pub roughness: f32,
+  /// Roughness in the green channel, metalness in the blue one.
+  pub metallic_roughness_texture: Option<usize>,
+  pub normal_texture: Option<usize>,
+  pub normal_scale: f32,
+  /// Occlusion in the red channel.
+  pub occlusion_texture: Option<usize>,
+  pub occlusion_strength: f32,
+  pub emissive: [f32; 3],
+  pub emissive_texture: Option<usize>,
+  pub alpha_mode: AlphaMode,
+  pub double_sided: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaMode {
+  Opaque,
+  /// Fully opaque where alpha is at least `cutoff`, fully transparent elsewhere.
+  Mask {
+    cutoff: f32,
+  },
+  Blend,
+}
+
+/// An image together with the sampler settings it's used with, as GL enums.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GltfTexture {
+  /// Index into `GltfScene::images`.
+  pub image: usize,
+  pub mag_filter: GLenum,
+  pub min_filter: GLenum,
+  pub wrap_s: GLenum,
+  pub wrap_t: GLenum,
+}
+
+/// Decoded 8-bit pixels, with rows stored bottom first to match the upward `v` of `Mesh` texture coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfImage {
+  pub width: u32,
+  pub height: u32,
+  /// `gl::RED`, `gl::RG`, `gl::RGB`, `gl::RGBA`, `gl::BGR` or `gl::BGRA`.
+  pub format: GLenum,
+  pub pixels: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfCamera {
+  pub name: Option<String>,
+  pub projection: GltfProjection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GltfProjection {
+  /// `aspect_ratio` is `None` when the viewport's should be used, and `zfar` when the projection is infinite.
+  Perspective {
+    yfov: f32,
+    aspect_ratio: Option<f32>,
+    znear: f32,
+    zfar: Option<f32>,
+  },
+  Orthographic {
+    xmag: f32,
+    ymag: f32,
+    znear: f32,
+    zfar: f32,
+  },
+}
+
+#[derive(Debug)]
+pub enum GltfError {
+  /// Reading, parsing or validating the file, or loading one of its buffers or images.
+  Import(gltf::Error),
+  /// Content the importer doesn't handle, like point and line primitives or 16-bit images.
+  Unsupported(String),
+}
+
+impl fmt::Display for GltfError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      GltfError::Import(error) => write!(f, "failed to import glTF: {}", error),
+      GltfError::Unsupported(message) => write!(f, "unsupported glTF content: {}", message),
+    }
+  }
+}
+
+impl Error for GltfError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      GltfError::Import(error) => Some(error),
+      GltfError::Unsupported(_) => None,
+    }
+  }
+}
+
+impl From<gltf::Error> for GltfError {
+  fn from(error: gltf::Error) -> Self {
+    GltfError::Import(error)
+  }
+}
+
+impl GltfScene {
+  /// Loads a `.gltf` file, with buffers and images either next to it or embedded as base64 data URIs, or a
+  /// `.glb` file.
+  ///
+  /// Texture coordinates are flipped to point `v` up like the rest of the crate, and images flipped to match.
+  /// Tangents are kept as they are: the flip mirrors both the texture and the bitangent, which leaves their
+  /// handedness unchanged. Triangle strips and fans become triangle lists, primitives without indices get
+  /// sequential ones, missing normals are generated flat as the glTF specification asks, and missing texture
+  /// coordinates default to zero. Skinned primitives keep their first set of joints and weights, and morph targets
+  /// their position and normal offsets.
+  ///
+  /// Node hierarchies with cycles, skins without one inverse bind matrix per joint and skinned vertices using joints
+  /// their skin doesn't have are `Unsupported`.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+    let (document, buffers, images) = gltf::import(path)?;
+
+    let meshes: Vec<GltfMesh> = document
+      .meshes()
+      .map(|mesh| {
+        let primitives = mesh
+          .primitives()
+          .map(|primitive| convert_primitive(&primitive, &buffers))
+          .collect::<Result<_, _>>()?;
+        let targets = mesh.primitives().next().map_or(0, |p| p.morph_targets().len());
+        Ok(GltfMesh {
+          name: mesh.name().map(String::from),
+          primitives,
+          weights: mesh.weights().map_or_else(|| vec![0.0; targets], <[f32]>::to_vec),
+        })
+      })
+      .collect::<Result<_, GltfError>>()?;
+
+    let images = images
+      .into_iter()
+      .enumerate()
+      .map(|(i, image)| convert_image(i, image))
+      .collect::<Result<_, _>>()?;
+
+    let textures = document
+      .textures()
+      .map(|texture| {
+        let sampler = texture.sampler();
+        GltfTexture {
+          image: texture.source().index(),
+          mag_filter: sampler.mag_filter().map_or(gl::LINEAR, |f| f.as_gl_enum()),
+          min_filter: sampler
+            .min_filter()
+            .map_or(gl::LINEAR_MIPMAP_LINEAR, |f| f.as_gl_enum()),
+          wrap_s: sampler.wrap_s().as_gl_enum(),
+          wrap_t: sampler.wrap_t().as_gl_enum(),
+        }
+      })
+      .collect();
+
+    let materials = document.materials().map(|m| convert_material(&m)).collect();
+
+    let cameras = document
+      .cameras()
+      .map(|camera| GltfCamera {
+        name: camera.name().map(String::from),
+        projection: match camera.projection() {
+          gltf::camera::Projection::Perspective(p) => GltfProjection::Perspective {
+            yfov: p.yfov(),
+            aspect_ratio: p.aspect_ratio(),
+            znear: p.znear(),
+            zfar: p.zfar(),
+          },
+          gltf::camera::Projection::Orthographic(o) => GltfProjection::Orthographic {
+            xmag: o.xmag(),
+            ymag: o.ymag(),
+            znear: o.znear(),
+            zfar: o.zfar(),
+          },
+        },
+      })
+      .collect();
+
+    let mut nodes: Vec<GltfNode> = document
+      .nodes()
+      .map(|node| {
+        let (translation, rotation, scale) = node.transform().decomposed();
+        GltfNode {
+          name: node.name().map(String::from),
+          transform: node.transform().matrix(),
+          pose: Transform {
+            translation,
+            rotation,
+            scale,
+          },
+          parent: None,
+          children: node.children().map(|child| child.index()).collect(),
+          mesh: node.mesh().map(|mesh| mesh.index()),
+          skin: node.skin().map(|skin| skin.index()),
+          weights: node.weights().map(<[f32]>::to_vec),
+          camera: node.camera().map(|camera| camera.index()),
+        }
+      })
+      .collect();
+    for parent in 0..nodes.len() {
+      for child in nodes[parent].children.clone() {
+        nodes[child].parent = Some(parent);
+      }
+    }
+    if let Some(node) = find_cycle(&nodes) {
+      return Err(GltfError::Unsupported(format!("node {} is its own ancestor", node)));
+    }
+
+    let skins: Vec<Skin> = document
+      .skins()
+      .map(|skin| {
+        let reader = skin.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+        let joints: Vec<usize> = skin.joints().map(|joint| joint.index()).collect();
+        let inverse_bind_matrices: Vec<_> = match reader.read_inverse_bind_matrices() {
+          Some(matrices) => matrices.collect(),
+          None => vec![Transform::IDENTITY.matrix(); joints.len()],
+        };
+        if inverse_bind_matrices.len() != joints.len() {
+          return Err(GltfError::Unsupported(format!(
+            "skin {} has {} joints but {} inverse bind matrices",
+            skin.index(),
+            joints.len(),
+            inverse_bind_matrices.len()
+          )));
+        }
+        Ok(Skin {
+          name: skin.name().map(String::from),
+          joints,
+          inverse_bind_matrices,
+        })
+      })
+      .collect::<Result<_, GltfError>>()?;
+
+    // The joint indices of skinned vertices pick matrices out of their node's skin.
+    for node in &nodes {
+      if let (Some(mesh), Some(skin)) = (node.mesh, node.skin) {
+        let count = skins[skin].joints.len();
+        let joints = meshes[mesh]
+          .primitives
+          .iter()
+          .flat_map(|p| p.mesh.joints.iter().flatten());
+        if let Some(joint) = joints.copied().find(|&joint| joint as usize >= count) {
+          return Err(GltfError::Unsupported(format!(
+            "mesh {} uses joint {} of skin {}, which has {} joints",
+            mesh, joint, skin, count
+          )));
+        }
+      }
+    }
+
+    let animations = document
+      .animations()
+      .map(|animation| convert_animation(&animation, &buffers))
+      .collect::<Result<_, _>>()?;
+
+    let roots = match document.default_scene().or_else(|| document.scenes().next()) {
+      Some(scene) => scene.nodes().map(|node| node.index()).collect(),
+      None => (0..nodes.len()).filter(|&i| nodes[i].parent.is_none()).collect(),
+    };
+
+    Ok(Self {
+      meshes,
+      materials,
+      textures,
+      images,
+      cameras,
+      nodes,
+      skins,
+      animations,
+      roots,
+    })
+  }
+
+  /// The pose of every node before any animation is applied.
+  pub fn rest_pose(&self) -> Vec<Transform> {
+    self.nodes.iter().map(|node| node.pose).collect()
+  }
+
+  /// The morph target weights of every node before any animation is applied, empty for nodes without a mesh.
+  pub fn rest_weights(&self) -> Vec<Vec<f32>> {
+    self
+      .nodes
+      .iter()
+      .map(|node| match (&node.weights, node.mesh) {
+        (Some(weights), _) => weights.clone(),
+        (None, Some(mesh)) => self.meshes[mesh].weights.clone(),
+        (None, None) => Vec::new(),
+      })
+      .collect()
+  }
+
+  /// The world transform of every node in `pose`, usually the rest pose after `AnimationClip::apply`.
+  pub fn world_transforms(&self, pose: &[Transform]) -> Vec<[[f32; 4]; 4]> {
+    let parents: Vec<Option<usize>> = self.nodes.iter().map(|node| node.parent).collect();
+    animation::world_transforms(&parents, pose)
+  }
+
+  /// The column-major transform from a node's space to the scene's, through all of its ancestors.
+  ///
+  /// Panics if the node's ancestors form a cycle, which `load` rejects.
+  pub fn world_transform(&self, node: usize) -> [[f32; 4]; 4] {
+    let mut transform = Matrix4::from(self.nodes[node].transform);
+    let mut parent = self.nodes[node].parent;
+    let mut depth = 0;
+    while let Some(i) = parent {
+      depth += 1;
+      assert!(
+        depth <= self.nodes.len(),
+        "the node hierarchy has a cycle through node {}",
+        i
+      );
+      transform = Matrix4::from(self.nodes[i].transform) * transform;
+      parent = self.nodes[i].parent;
+    }
+    transform.into()
+  }
+}
+
+/// A node that is its own ancestor, if the hierarchy has a cycle.
+fn find_cycle(nodes: &[GltfNode]) -> Option<usize> {
+  (0..nodes.len()).find(|&start| {
+    let mut node = start;
+    // A chain longer than the node count has to go around a cycle, which its own nodes are found in.
+    for _ in 0..nodes.len() {
+      match nodes[node].parent {
+        Some(parent) if parent == start => return true,
+        Some(parent) => node = parent,
+        None => return false,
+      }
+    }
+    false
+  })
+}
+
+fn convert_primitive(primitive: &gltf::Primitive, buffers: &[gltf::buffer::Data]) -> Result<GltfPrimitive, GltfError> {
+  let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+
+  let positions: Vec<[f32; 3]> = match reader.read_positions() {
+    Some(positions) => positions.collect(),
+    None => return Err(GltfError::Unsupported("primitive without positions".into())),
+  };
+  let count = positions.len();
+
+  let vertices: Vec<u32> = match reader.read_indices() {
+    Some(indices) => indices.into_u32().collect(),
+    None => (0..count as u32).collect(),
+  };
+  if let Some(&index) = vertices.iter().find(|&&i| i as usize >= count) {
+    return Err(GltfError::Unsupported(format!(
+      "index {} out of range, there are {} vertices",
+      index, count
+    )));
+  }
+
+  let indices = match primitive.mode() {
+    Mode::Triangles => vertices.chunks_exact(3).flatten().copied().collect(),
+    Mode::TriangleStrip => (2..vertices.len())
+      .flat_map(|i| {
+        // Every other triangle of a strip runs the other way around.
+        if i % 2 == 0 {
+          [vertices[i - 2], vertices[i - 1], vertices[i]]
+        } else {
+          [vertices[i - 1], vertices[i - 2], vertices[i]]
+        }
+      })
+      .collect(),
+    Mode::TriangleFan => (2..vertices.len())
+      .flat_map(|i| [vertices[0], vertices[i - 1], vertices[i]])
+      .collect(),
+    mode => return Err(GltfError::Unsupported(format!("{:?} primitives", mode))),
+  };
+
+  let normals = reader.read_normals().map(Iterator::collect);
+  let uvs = match reader.read_tex_coords(0) {
+    Some(uvs) => uvs.into_f32().map(|[u, v]| [u, 1.0 - v]).collect(),
+    None => vec![[0.0; 2]; count],
+  };
+  let tangents = reader.read_tangents().map(Iterator::collect);
+  let colors = reader.read_colors(0).map(|colors| colors.into_rgba_f32().collect());
+  let (joints, weights) = match (reader.read_joints(0), reader.read_weights(0)) {
+    (Some(joints), Some(weights)) => (joints.into_u16().collect(), weights.into_f32().collect()),
+    _ => (Vec::new(), Vec::new()),
+  };
+  let morph_targets = reader
+    .read_morph_targets()
+    .map(|(positions, normals, _)| MorphTarget {
+      positions: positions.map_or_else(|| vec![[0.0; 3]; count], Iterator::collect),
+      normals: normals.map_or_else(|| vec![[0.0; 3]; count], Iterator::collect),
+    })
+    .collect();
+
+  let mut mesh = Mesh {
+    positions,
+    normals: normals.clone().unwrap_or_default(),
+    uvs,
+    tangents: tangents.unwrap_or_default(),
+    colors: colors.unwrap_or_default(),
+    joints,
+    weights,
+    morph_targets,
+    indices,
+  };
+  if normals.is_none() {
+    mesh.compute_flat_normals();
+  }
+
+  Ok(GltfPrimitive {
+    material: primitive.material().index(),
+    mesh,
+  })
+}
+
+fn convert_animation(animation: &gltf::Animation, buffers: &[gltf::buffer::Data]) -> Result<AnimationClip, GltfError> {
+  let mut channels = Vec::new();
+  for channel in animation.channels() {
+    let reader = channel.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+    let times: Vec<f32> = match reader.read_inputs() {
+      Some(times) => times.collect(),
+      None => {
+        return Err(GltfError::Unsupported(
+          "animation channel without keyframe times".into(),
+        ))
+      }
+    };
+    let values = match reader.read_outputs() {
+      Some(ReadOutputs::Translations(values)) => ChannelValues::Translation(values.collect()),
+      Some(ReadOutputs::Rotations(values)) => ChannelValues::Rotation(values.into_f32().collect()),
+      Some(ReadOutputs::Scales(values)) => ChannelValues::Scale(values.collect()),
+      Some(ReadOutputs::MorphTargetWeights(values)) => ChannelValues::Weights(values.into_f32().collect()),
+      None => return Err(GltfError::Unsupported("animation channel without values".into())),
+    };
+    let interpolation = match channel.sampler().interpolation() {
+      gltf::animation::Interpolation::Step => Interpolation::Step,
+      gltf::animation::Interpolation::Linear => Interpolation::Linear,
+      gltf::animation::Interpolation::CubicSpline => Interpolation::CubicSpline,
+    };
+
+    let value_count = match &values {
+      ChannelValues::Translation(values) | ChannelValues::Scale(values) => values.len(),
+      ChannelValues::Rotation(values) => values.len(),
+      ChannelValues::Weights(values) => values.len(),
+    };
+    let per_key = if interpolation == Interpolation::CubicSpline {
+      3
+    } else {
+      1
+    };
+    // Weight channels have a value per morph target for every keyframe.
+    let keyframes_match = match &values {
+      ChannelValues::Weights(_) => value_count > 0 && value_count.is_multiple_of(times.len() * per_key),
+      _ => value_count == times.len() * per_key,
+    };
+    if times.is_empty() || !keyframes_match {
+      return Err(GltfError::Unsupported(format!(
+        "animation channel with {} values for {} keyframes",
+        value_count,
+        times.len()
+      )));
+    }
+
+    channels.push(Channel {
+      node: channel.target().node().index(),
+      interpolation,
+      times,
+      values,
+    });
+  }
+
+  Ok(AnimationClip {
+    name: animation.name().map(String::from),
+    channels,
+  })
+}
+
+fn convert_material(material: &gltf::Material) -> GltfMaterial {
+  let pbr = material.pbr_metallic_roughness();
+  let normal = material.normal_texture();
+  let occlusion = material.occlusion_texture();
+
+  GltfMaterial {
+    name: material.name().map(String::from),
+    base_color: pbr.base_color_factor(),
+    base_color_texture: pbr.base_color_texture().map(|info| info.texture().index()),
+    metallic: pbr.metallic_factor(),
+    roughness: pbr.roughness_factor(),
+    metallic_roughness_texture: pbr.metallic_roughness_texture().map(|info| info.texture().index()),
+    normal_texture: normal.as_ref().map(|normal| normal.texture().index()),
+    normal_scale: normal.map_or(1.0, |normal| normal.scale()),
+    occlusion_texture: occlusion.as_ref().map(|occlusion| occlusion.texture().index()),
+    occlusion_strength: occlusion.map_or(1.0, |occlusion| occlusion.strength()),
+    emissive: material.emissive_factor(),
+    emissive_texture: material.emissive_texture().map(|info| info.texture().index()),
+    alpha_mode: match material.alpha_mode() {
+      gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+      gltf::material::AlphaMode::Mask => AlphaMode::Mask {
+        cutoff: material.alpha_cutoff(),
+      },
+      gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+    },
+    double_sided: material.double_sided(),
+  }
+}
+
+fn convert_image(index: usize, image: gltf::image::Data) -> Result<GltfImage, GltfError> {
+  use gltf::image::Format;
+
+  let (format, channels) = match image.format {
+    Format::R8 => (gl::RED, 1),
+    Format::R8G8 => (gl::RG, 2),
+    Format::R8G8B8 => (gl::RGB, 3),
+    Format::R8G8B8A8 => (gl::RGBA, 4),
+    Format::B8G8R8 => (gl::BGR, 3),
+    Format::B8G8R8A8 => (gl::BGRA, 4),
+    format => {
+      return Err(GltfError::Unsupported(format!(
+        "image {} has {:?} pixels",
+        index, format
+      )))
+    }
+  };
+
+  let row = image.width as usize * channels;
+  let pixels = image.pixels.chunks_exact(row).rev().flatten().copied().collect();
+  Ok(GltfImage {
+    width: image.width,
+    height: image.height,
+    format,
+    pixels,
+  })
+}
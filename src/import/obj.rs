@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::mesh::{triangulate, Mesh};
+
+/// A Wavefront OBJ model with its MTL materials.
+///
+/// Faces are merged into one mesh per material, ignoring `o` and `g` groups.
+#[derive(Clone, Debug, Default)]
+pub struct ObjModel {
+  pub meshes: Vec<ObjMesh>,
+  pub materials: Vec<ObjMaterial>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjMesh {
+  /// Index into `ObjModel::materials`, `None` for faces without a known material.
+  pub material: Option<usize>,
+  pub mesh: Mesh,
+}
+
+/// An MTL material. Fields missing from the file have the defaults of the MTL format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjMaterial {
+  pub name: String,
+  pub ambient: [f32; 3],
+  pub diffuse: [f32; 3],
+  pub specular: [f32; 3],
+  pub emissive: [f32; 3],
+  pub shininess: f32,
+  pub opacity: f32,
+  pub ambient_map: Option<PathBuf>,
+  pub diffuse_map: Option<PathBuf>,
+  pub specular_map: Option<PathBuf>,
+  pub emissive_map: Option<PathBuf>,
+  pub normal_map: Option<PathBuf>,
+  pub opacity_map: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+  fn new(name: String) -> Self {
+    Self {
+      name,
+      ambient: [0.2; 3],
+      diffuse: [0.8; 3],
+      specular: [1.0; 3],
+      emissive: [0.0; 3],
+      shininess: 0.0,
+      opacity: 1.0,
+      ambient_map: None,
+      diffuse_map: None,
+      specular_map: None,
+      emissive_map: None,
+      normal_map: None,
+      opacity_map: None,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum ObjError {
+  Io {
+    path: PathBuf,
+    error: io::Error,
+  },
+  Parse {
+    path: PathBuf,
+    line: usize,
+    message: String,
+  },
+}
+
+impl fmt::Display for ObjError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ObjError::Io { path, error } => write!(f, "failed to read {}: {}", path.display(), error),
+      ObjError::Parse { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+    }
+  }
+}
+
+impl Error for ObjError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      ObjError::Io { error, .. } => Some(error),
+      ObjError::Parse { .. } => None,
+    }
+  }
+}
+
+impl ObjModel {
+  /// Loads an OBJ file together with the MTL files it references, which are looked up next to it.
+  ///
+  /// Polygons are triangulated, negative (relative) indices are resolved, and texture coordinates default to
+  /// zero where missing. Meshes with faces lacking normals get generated ones: smooth if any of their faces is in
+  /// a smoothing group (`s 1`), flat otherwise. Vertex colors written after the position (`v x y z r g b [a]`) fill
+  /// `Mesh::colors`, white for vertices without one.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+    let path = path.as_ref();
+    let source = read(path)?;
+
+    let mut parser = ObjParser::default();
+    for (i, line) in source.lines().enumerate() {
+      let at = Location { path, line: i + 1 };
+      if let Some((keyword, args)) = split_statement(line) {
+        parser.statement(&at, keyword, &args)?;
+      }
+    }
+    Ok(parser.finish())
+  }
+}
+
+/// Where a statement comes from, for error messages.
+struct Location<'a> {
+  path: &'a Path,
+  line: usize,
+}
+
+impl Location<'_> {
+  fn error(&self, message: impl Into<String>) -> ObjError {
+    ObjError::Parse {
+      path: self.path.to_path_buf(),
+      line: self.line,
+      message: message.into(),
+    }
+  }
+
+  fn floats(&self, keyword: &str, args: &[&str], min: usize, max: usize) -> Result<Vec<f32>, ObjError> {
+    if args.len() < min || args.len() > max {
+      let expected = if min == max {
+        min.to_string()
+      } else {
+        format!("{} to {}", min, max)
+      };
+      return Err(self.error(format!(
+        "`{}` takes {} numbers, found {}",
+        keyword,
+        expected,
+        args.len()
+      )));
+    }
+    args
+      .iter()
+      .map(|arg| arg.parse().map_err(|_| self.error(format!("invalid number `{}`", arg))))
+      .collect()
+  }
+
+  fn float(&self, keyword: &str, args: &[&str]) -> Result<f32, ObjError> {
+    Ok(self.floats(keyword, args, 1, 1)?[0])
+  }
+
+  /// An MTL color, where a single value means gray.
+  fn color(&self, keyword: &str, args: &[&str]) -> Result<[f32; 3], ObjError> {
+    match *self.floats(keyword, args, 1, 3)?.as_slice() {
+      [v] => Ok([v; 3]),
+      [r, g, b] => Ok([r, g, b]),
+      _ => Err(self.error(format!("`{}` takes 1 or 3 numbers", keyword))),
+    }
+  }
+}
+
+/// The keyword and arguments of a line, or `None` for blank lines and comments.
+fn split_statement(line: &str) -> Option<(&str, Vec<&str>)> {
+  let line = line.split('#').next().unwrap_or("");
+  let mut words = line.split_whitespace();
+  words.next().map(|keyword| (keyword, words.collect()))
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+  fs::read_to_string(path).map_err(|error| ObjError::Io {
+    path: path.to_path_buf(),
+    error,
+  })
+}
+
+#[derive(Default)]
+struct ObjParser {
+  positions: Vec<[f32; 3]>,
+  /// One per position, white unless the file gives a color.
+  colors: Vec<[f32; 4]>,
+  has_colors: bool,
+  uvs: Vec<[f32; 2]>,
+  normals: Vec<[f32; 3]>,
+  materials: Vec<ObjMaterial>,
+  material: Option<usize>,
+  smooth: bool,
+  meshes: Vec<MeshBuilder>,
+}
+
+struct MeshBuilder {
+  material: Option<usize>,
+  mesh: Mesh,
+  /// The mesh vertex made for each (position, uv, normal) combination.
+  vertices: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+  missing_normals: bool,
+  smooth: bool,
+}
+
+impl ObjParser {
+  fn statement(&mut self, at: &Location, keyword: &str, args: &[&str]) -> Result<(), ObjError> {
+    match keyword {
+      "v" => {
+        // Besides the rarely used `w`, MeshLab and many scanners append an RGB or RGBA color.
+        let v = at.floats(keyword, args, 3, 7)?;
+        let color = match v.len() {
+          3 | 4 => None,
+          6 => Some([v[3], v[4], v[5], 1.0]),
+          7 => Some([v[3], v[4], v[5], v[6]]),
+          n => return Err(at.error(format!("`v` takes 3, 4, 6 or 7 numbers, found {}", n))),
+        };
+        self.positions.push([v[0], v[1], v[2]]);
+        self.colors.push(color.unwrap_or([1.0; 4]));
+        self.has_colors |= color.is_some();
+      }
+      "vt" => {
+        let v = at.floats(keyword, args, 1, 3)?;
+        self.uvs.push([v[0], v.get(1).copied().unwrap_or(0.0)]);
+      }
+      "vn" => {
+        let v = at.floats(keyword, args, 3, 3)?;
+        self.normals.push([v[0], v[1], v[2]]);
+      }
+      "f" => self.face(at, args)?,
+      "s" => self.smooth = !matches!(args, ["off"] | ["0"]),
+      "usemtl" => {
+        let name = args.join(" ");
+        self.material = self.materials.iter().position(|m| m.name == name);
+      }
+      "mtllib" => {
+        for name in args {
+          let path = at.path.with_file_name(name);
+          let source = read(&path)?;
+          parse_mtl(&path, &source, &mut self.materials)?;
+        }
+      }
+      _ => {}
+    }
+    Ok(())
+  }
+
+  fn face(&mut self, at: &Location, args: &[&str]) -> Result<(), ObjError> {
+    if args.len() < 3 {
+      return Err(at.error(format!("a face needs at least 3 vertices, found {}", args.len())));
+    }
+
+    let mut corners = Vec::with_capacity(args.len());
+    for arg in args {
+      let mut parts = arg.split('/');
+      let position = resolve_index(at, parts.next(), self.positions.len(), "position")?;
+      let uv = resolve_index(at, parts.next(), self.uvs.len(), "texture coordinate")?;
+      let normal = resolve_index(at, parts.next(), self.normals.len(), "normal")?;
+      match position {
+        Some(position) => corners.push((position, uv, normal)),
+        None => return Err(at.error(format!("face vertex `{}` has no position", arg))),
+      }
+    }
+
+    let mesh = match self.meshes.iter().position(|m| m.material == self.material) {
+      Some(i) => &mut self.meshes[i],
+      None => {
+        self.meshes.push(MeshBuilder {
+          material: self.material,
+          mesh: Mesh::default(),
+          vertices: HashMap::new(),
+          missing_normals: false,
+          smooth: false,
+        });
+        self.meshes.last_mut().unwrap()
+      }
+    };
+    mesh.smooth |= self.smooth;
+
+    let (positions, colors, uvs, normals) = (&self.positions, &self.colors, &self.uvs, &self.normals);
+    let points: Vec<_> = corners.iter().map(|c| positions[c.0]).collect();
+    for triangle in triangulate(&points) {
+      for &k in &triangle {
+        let key = corners[k];
+        let missing_normals = &mut mesh.missing_normals;
+        let mesh_data = &mut mesh.mesh;
+        let vertex = *mesh.vertices.entry(key).or_insert_with(|| {
+          let (position, uv, normal) = key;
+          *missing_normals |= normal.is_none();
+          mesh_data.colors.push(colors[position]);
+          mesh_data.push_vertex(
+            positions[position],
+            normal.map_or([0.0; 3], |n| normals[n]),
+            uv.map_or([0.0; 2], |t| uvs[t]),
+          )
+        });
+        mesh.mesh.indices.push(vertex);
+      }
+    }
+    Ok(())
+  }
+
+  fn finish(self) -> ObjModel {
+    let has_colors = self.has_colors;
+    let meshes = self
+      .meshes
+      .into_iter()
+      .map(|builder| {
+        let mut mesh = builder.mesh;
+        if !has_colors {
+          mesh.colors.clear();
+        }
+        if builder.missing_normals {
+          if builder.smooth {
+            mesh.compute_smooth_normals(PI);
+          } else {
+            mesh.compute_flat_normals();
+          }
+        }
+        ObjMesh {
+          material: builder.material,
+          mesh,
+        }
+      })
+      .collect();
+
+    ObjModel {
+      meshes,
+      materials: self.materials,
+    }
+  }
+}
+
+/// Turns a 1-based or negative (counted back from the latest) OBJ index into a 0-based one.
+fn resolve_index(at: &Location, part: Option<&str>, count: usize, what: &str) -> Result<Option<usize>, ObjError> {
+  let part = match part {
+    Some(part) if !part.is_empty() => part,
+    _ => return Ok(None),
+  };
+
+  let index: i64 = part
+    .parse()
+    .map_err(|_| at.error(format!("invalid {} index `{}`", what, part)))?;
+  let resolved = if index > 0 { index - 1 } else { count as i64 + index };
+  if index == 0 || resolved < 0 || resolved >= count as i64 {
+    return Err(at.error(format!("{} index {} out of range, there are {}", what, index, count)));
+  }
+  Ok(Some(resolved as usize))
+}
+
+fn parse_mtl(path: &Path, source: &str, materials: &mut Vec<ObjMaterial>) -> Result<(), ObjError> {
+  for (i, line) in source.lines().enumerate() {
+    let at = Location { path, line: i + 1 };
+    let (keyword, args) = match split_statement(line) {
+      Some(statement) => statement,
+      None => continue,
+    };
+
+    if keyword == "newmtl" {
+      materials.push(ObjMaterial::new(args.join(" ")));
+      continue;
+    }
+    let material = match materials.last_mut() {
+      Some(material) => material,
+      None => return Err(at.error(format!("`{}` before any `newmtl`", keyword))),
+    };
+
+    // Texture options like `-bm 0.5` come before the file name, which is taken to be the last word.
+    let texture = || match args.last() {
+      Some(name) => Ok(Some(path.with_file_name(name.replace('\\', "/")))),
+      None => Err(at.error(format!("`{}` needs a file name", keyword))),
+    };
+
+    match keyword {
+      "Ka" => material.ambient = at.color(keyword, &args)?,
+      "Kd" => material.diffuse = at.color(keyword, &args)?,
+      "Ks" => material.specular = at.color(keyword, &args)?,
+      "Ke" => material.emissive = at.color(keyword, &args)?,
+      "Ns" => material.shininess = at.float(keyword, &args)?,
+      "d" => material.opacity = at.float(keyword, &args)?,
+      "Tr" => material.opacity = 1.0 - at.float(keyword, &args)?,
+      "map_Ka" => material.ambient_map = texture()?,
+      "map_Kd" => material.diffuse_map = texture()?,
+      "map_Ks" => material.specular_map = texture()?,
+      "map_Ke" => material.emissive_map = texture()?,
+      "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = texture()?,
+      "map_d" => material.opacity_map = texture()?,
+      _ => {}
+    }
+  }
+  Ok(())
+}
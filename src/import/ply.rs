@@ -0,0 +1,346 @@
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str;
+
+use crate::mesh::{triangulate, Mesh};
+
+#[derive(Debug)]
+pub enum PlyError {
+  Io(io::Error),
+  /// The header or the data doesn't follow the PLY format.
+  Invalid(String),
+}
+
+impl fmt::Display for PlyError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      PlyError::Io(error) => write!(f, "failed to read PLY file: {}", error),
+      PlyError::Invalid(message) => write!(f, "invalid PLY file: {}", message),
+    }
+  }
+}
+
+impl Error for PlyError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      PlyError::Io(error) => Some(error),
+      PlyError::Invalid(_) => None,
+    }
+  }
+}
+
+impl From<io::Error> for PlyError {
+  fn from(error: io::Error) -> Self {
+    PlyError::Io(error)
+  }
+}
+
+/// Loads a PLY mesh, see `parse_ply`.
+pub fn load_ply(path: impl AsRef<Path>) -> Result<Mesh, PlyError> {
+  parse_ply(&fs::read(path)?)
+}
+
+/// Reads a mesh from PLY data in the ASCII, binary little endian or binary big endian format.
+///
+/// Vertices take their position from `x`, `y` and `z`, and their normal, texture coordinates and color from
+/// `nx`/`ny`/`nz`, `u`/`v` (or `s`/`t`) and `red`/`green`/`blue`/`alpha` when present. Integer colors are scaled
+/// from 0–255 to 0–1. Faces are triangulated, and smooth normals are generated when the file has none. Other elements
+/// and properties are skipped. Positions must be finite.
+pub fn parse_ply(data: &[u8]) -> Result<Mesh, PlyError> {
+  let (header, body) = parse_header(data)?;
+  let mut reader = match header.format {
+    Format::Ascii => {
+      let text = str::from_utf8(body).map_err(|_| invalid("ASCII data isn't valid UTF-8"))?;
+      Reader::Ascii(text.split_whitespace())
+    }
+    Format::Binary { big_endian } => Reader::Binary { data: body, big_endian },
+  };
+
+  let mut mesh = Mesh::default();
+  let mut has_normals = false;
+  for element in &header.elements {
+    match element.name.as_str() {
+      "vertex" => has_normals = read_vertices(element, &mut reader, &mut mesh)?,
+      "face" => read_faces(element, &mut reader, &mut mesh)?,
+      _ => {
+        for _ in 0..element.count {
+          for property in &element.properties {
+            reader.property(property)?;
+          }
+        }
+      }
+    }
+  }
+
+  if let Some(vertex) = mesh.positions.iter().position(|p| !p.iter().all(|c| c.is_finite())) {
+    return Err(invalid(format!("vertex {} has a non-finite position", vertex)));
+  }
+  if !has_normals && !mesh.indices.is_empty() {
+    mesh.compute_smooth_normals(PI);
+  }
+  Ok(mesh)
+}
+
+fn invalid(message: impl Into<String>) -> PlyError {
+  PlyError::Invalid(message.into())
+}
+
+enum Format {
+  Ascii,
+  Binary { big_endian: bool },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scalar {
+  I8,
+  U8,
+  I16,
+  U16,
+  I32,
+  U32,
+  F32,
+  F64,
+}
+
+impl Scalar {
+  fn parse(name: &str) -> Result<Self, PlyError> {
+    Ok(match name {
+      "char" | "int8" => Scalar::I8,
+      "uchar" | "uint8" => Scalar::U8,
+      "short" | "int16" => Scalar::I16,
+      "ushort" | "uint16" => Scalar::U16,
+      "int" | "int32" => Scalar::I32,
+      "uint" | "uint32" => Scalar::U32,
+      "float" | "float32" => Scalar::F32,
+      "double" | "float64" => Scalar::F64,
+      _ => return Err(invalid(format!("unknown property type `{}`", name))),
+    })
+  }
+
+  fn size(self) -> usize {
+    match self {
+      Scalar::I8 | Scalar::U8 => 1,
+      Scalar::I16 | Scalar::U16 => 2,
+      Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+      Scalar::F64 => 8,
+    }
+  }
+}
+
+struct Property {
+  name: String,
+  ty: Scalar,
+  /// The type of the item count for list properties.
+  list_count: Option<Scalar>,
+}
+
+struct Element {
+  name: String,
+  count: usize,
+  properties: Vec<Property>,
+}
+
+impl Element {
+  fn position(&self, names: &[&str]) -> Option<usize> {
+    self.properties.iter().position(|p| names.contains(&p.name.as_str()))
+  }
+}
+
+struct Header {
+  format: Format,
+  elements: Vec<Element>,
+}
+
+/// Parses the header, returning it with the data following `end_header`.
+fn parse_header(data: &[u8]) -> Result<(Header, &[u8]), PlyError> {
+  let mut format = None;
+  let mut elements: Vec<Element> = Vec::new();
+  let mut rest = data;
+  let mut first = true;
+  loop {
+    let end = rest
+      .iter()
+      .position(|&b| b == b'\n')
+      .ok_or_else(|| invalid("the header has no `end_header`"))?;
+    let line = str::from_utf8(&rest[..end]).map_err(|_| invalid("the header isn't valid UTF-8"))?;
+    rest = &rest[end + 1..];
+
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if first {
+      if words != ["ply"] {
+        return Err(invalid("missing the `ply` magic number"));
+      }
+      first = false;
+      continue;
+    }
+    match words.as_slice() {
+      ["format", name, _version] => {
+        format = Some(match *name {
+          "ascii" => Format::Ascii,
+          "binary_little_endian" => Format::Binary { big_endian: false },
+          "binary_big_endian" => Format::Binary { big_endian: true },
+          _ => return Err(invalid(format!("unknown format `{}`", name))),
+        })
+      }
+      ["element", name, count] => elements.push(Element {
+        name: name.to_string(),
+        count: count
+          .parse()
+          .map_err(|_| invalid(format!("invalid element count `{}`", count)))?,
+        properties: Vec::new(),
+      }),
+      ["property", "list", count, item, name] => {
+        let element = elements
+          .last_mut()
+          .ok_or_else(|| invalid("property before any element"))?;
+        element.properties.push(Property {
+          name: name.to_string(),
+          ty: Scalar::parse(item)?,
+          list_count: Some(Scalar::parse(count)?),
+        });
+      }
+      ["property", ty, name] => {
+        let element = elements
+          .last_mut()
+          .ok_or_else(|| invalid("property before any element"))?;
+        element.properties.push(Property {
+          name: name.to_string(),
+          ty: Scalar::parse(ty)?,
+          list_count: None,
+        });
+      }
+      ["end_header"] => break,
+      ["comment", ..] | ["obj_info", ..] | [] => {}
+      _ => return Err(invalid(format!("unexpected header line `{}`", line.trim()))),
+    }
+  }
+
+  let format = format.ok_or_else(|| invalid("the header has no `format`"))?;
+  Ok((Header { format, elements }, rest))
+}
+
+/// Reads values one at a time from the data after the header.
+enum Reader<'a> {
+  Ascii(str::SplitWhitespace<'a>),
+  Binary { data: &'a [u8], big_endian: bool },
+}
+
+impl Reader<'_> {
+  fn scalar(&mut self, ty: Scalar) -> Result<f64, PlyError> {
+    match self {
+      Reader::Ascii(words) => {
+        let word = words.next().ok_or_else(|| invalid("the data ends early"))?;
+        word.parse().map_err(|_| invalid(format!("invalid number `{}`", word)))
+      }
+      Reader::Binary { data, big_endian } => {
+        if data.len() < ty.size() {
+          return Err(invalid("the data ends early"));
+        }
+        let (bytes, rest) = data.split_at(ty.size());
+        *data = rest;
+
+        let mut buf = [0; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        if *big_endian {
+          buf[..bytes.len()].reverse();
+        }
+        Ok(match ty {
+          Scalar::I8 => buf[0] as i8 as f64,
+          Scalar::U8 => buf[0] as f64,
+          Scalar::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+          Scalar::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+          Scalar::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+          Scalar::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+          Scalar::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+          Scalar::F64 => f64::from_le_bytes(buf),
+        })
+      }
+    }
+  }
+
+  /// Reads a property, returning the values of a list or the single value of a scalar.
+  fn property(&mut self, property: &Property) -> Result<Vec<f64>, PlyError> {
+    match property.list_count {
+      Some(count_type) => {
+        let count = self.scalar(count_type)? as usize;
+        (0..count).map(|_| self.scalar(property.ty)).collect()
+      }
+      None => Ok(vec![self.scalar(property.ty)?]),
+    }
+  }
+}
+
+/// Reads the vertex element into `mesh`, returning whether it has normals.
+fn read_vertices(element: &Element, reader: &mut Reader, mesh: &mut Mesh) -> Result<bool, PlyError> {
+  let find_all = |names: &[&[&str]]| -> Option<Vec<usize>> { names.iter().map(|n| element.position(n)).collect() };
+  let position = find_all(&[&["x"], &["y"], &["z"]]).ok_or_else(|| invalid("vertices have no x, y and z"))?;
+  let normal = find_all(&[&["nx"], &["ny"], &["nz"]]);
+  let uv = find_all(&[&["u", "s", "texture_u"], &["v", "t", "texture_v"]]);
+  let color = find_all(&[&["red", "r"], &["green", "g"], &["blue", "b"]]);
+  let alpha = element.position(&["alpha", "a"]);
+
+  // Integer colors go from 0 to their type's maximum, usually 255.
+  let color_scale = |i: usize| match element.properties[i].ty {
+    Scalar::F32 | Scalar::F64 => 1.0,
+    Scalar::U16 | Scalar::I16 => 1.0 / 65535.0,
+    _ => 1.0 / 255.0,
+  };
+
+  for _ in 0..element.count {
+    let mut values = Vec::with_capacity(element.properties.len());
+    for property in &element.properties {
+      // Lists can't be vertex attributes, only their first value is kept so indices stay aligned.
+      values.push(reader.property(property)?.first().copied().unwrap_or(0.0) as f32);
+    }
+
+    let get = |indices: &[usize]| -> Vec<f32> { indices.iter().map(|&i| values[i]).collect() };
+    let p = get(&position);
+    let n = normal.as_ref().map_or(vec![0.0; 3], |n| get(n));
+    let t = uv.as_ref().map_or(vec![0.0; 2], |t| get(t));
+    mesh.push_vertex([p[0], p[1], p[2]], [n[0], n[1], n[2]], [t[0], t[1]]);
+
+    if let Some(color) = &color {
+      let [r, g, b] = [color[0], color[1], color[2]].map(|i| values[i] * color_scale(i));
+      let a = alpha.map_or(1.0, |i| values[i] * color_scale(i));
+      mesh.colors.push([r, g, b, a]);
+    }
+  }
+  Ok(normal.is_some())
+}
+
+fn read_faces(element: &Element, reader: &mut Reader, mesh: &mut Mesh) -> Result<(), PlyError> {
+  let indices = element
+    .position(&["vertex_indices", "vertex_index"])
+    .ok_or_else(|| invalid("faces have no vertex_indices"))?;
+
+  for _ in 0..element.count {
+    let mut polygon = Vec::new();
+    for (i, property) in element.properties.iter().enumerate() {
+      let values = reader.property(property)?;
+      if i == indices {
+        polygon = values;
+      }
+    }
+
+    let mut corners = Vec::with_capacity(polygon.len());
+    for &index in &polygon {
+      if index < 0.0 || index as usize >= mesh.vertex_count() {
+        return Err(invalid(format!(
+          "face index {} out of range for {} vertices",
+          index,
+          mesh.vertex_count()
+        )));
+      }
+      corners.push(index as u32);
+    }
+    let points: Vec<_> = corners.iter().map(|&c| mesh.positions[c as usize]).collect();
+    for triangle in triangulate(&points) {
+      mesh.indices.extend(triangle.iter().map(|&k| corners[k]));
+    }
+  }
+  Ok(())
+}
@@ -0,0 +1,163 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str;
+
+use crate::mesh::{cross, sub, try_normalize, Mesh, MeshVertex};
+
+/// The size of the header and of each triangle in a binary STL file.
+const HEADER_SIZE: usize = 84;
+const TRIANGLE_SIZE: usize = 50;
+
+/// Set in the attribute of a binary STL triangle when it holds a VisCAM/SolidView color.
+const COLOR_VALID: u16 = 0x8000;
+
+#[derive(Debug)]
+pub enum StlError {
+  Io(io::Error),
+  /// The data follows neither the ASCII nor the binary STL format.
+  Invalid(String),
+}
+
+impl fmt::Display for StlError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      StlError::Io(error) => write!(f, "failed to read STL file: {}", error),
+      StlError::Invalid(message) => write!(f, "invalid STL file: {}", message),
+    }
+  }
+}
+
+impl Error for StlError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      StlError::Io(error) => Some(error),
+      StlError::Invalid(_) => None,
+    }
+  }
+}
+
+impl From<io::Error> for StlError {
+  fn from(error: io::Error) -> Self {
+    StlError::Io(error)
+  }
+}
+
+/// Loads an STL mesh, see `parse_stl`.
+pub fn load_stl(path: impl AsRef<Path>) -> Result<Mesh, StlError> {
+  parse_stl(&fs::read(path)?)
+}
+
+/// Reads a mesh from ASCII or binary STL data.
+///
+/// STL stores every triangle on its own with a facet normal, so vertices are welded afterwards and the mesh looks
+/// faceted. Facet normals that are missing (zero) or not finite are computed from the winding. Binary files with
+/// VisCAM/SolidView colors in the triangle attributes get vertex colors, white for triangles without one. Positions
+/// must be finite.
+pub fn parse_stl(data: &[u8]) -> Result<Mesh, StlError> {
+  // Binary files may start with `solid` too, but their size always matches the triangle count.
+  let binary_size = |count: u32| HEADER_SIZE + count as usize * TRIANGLE_SIZE;
+  let mut mesh = match data.get(80..84) {
+    Some(count) if data.len() == binary_size(u32::from_le_bytes([count[0], count[1], count[2], count[3]])) => {
+      parse_binary(&data[HEADER_SIZE..])
+    }
+    _ if starts_with_solid(data) => parse_ascii(data)?,
+    _ => return Err(StlError::Invalid("neither ASCII nor binary STL".to_string())),
+  };
+  if let Some(vertex) = mesh.positions.iter().position(|p| !p.iter().all(|c| c.is_finite())) {
+    return Err(StlError::Invalid(format!(
+      "triangle {} has a non-finite vertex",
+      vertex / 3
+    )));
+  }
+  mesh.weld(0.0);
+  Ok(mesh)
+}
+
+fn starts_with_solid(data: &[u8]) -> bool {
+  let start = data.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(data.len());
+  data[start..].starts_with(b"solid")
+}
+
+/// The vertices of a triangle, with the normal computed from the winding if `normal` is zero or not finite.
+fn facet(normal: [f32; 3], corners: [[f32; 3]; 3]) -> [MeshVertex; 3] {
+  let normal = if normal == [0.0; 3] || !normal.iter().all(|c| c.is_finite()) {
+    let [a, b, c] = corners;
+    try_normalize(cross(sub(b, a), sub(c, a))).unwrap_or([0.0, 1.0, 0.0])
+  } else {
+    normal
+  };
+  corners.map(|position| MeshVertex {
+    position,
+    normal,
+    uv: [0.0; 2],
+  })
+}
+
+fn parse_binary(data: &[u8]) -> Mesh {
+  let mut vertices = Vec::with_capacity(data.len() / TRIANGLE_SIZE * 3);
+  let mut colors = Vec::with_capacity(vertices.capacity());
+  for triangle in data.chunks_exact(TRIANGLE_SIZE) {
+    let float = |i: usize| f32::from_le_bytes([triangle[i], triangle[i + 1], triangle[i + 2], triangle[i + 3]]);
+    let vector = |i: usize| [float(i), float(i + 4), float(i + 8)];
+    vertices.extend_from_slice(&facet(vector(0), [vector(12), vector(24), vector(36)]));
+
+    // 5 bits per channel, blue in the lowest ones.
+    let attribute = u16::from_le_bytes([triangle[48], triangle[49]]);
+    let color = (attribute & COLOR_VALID != 0).then(|| {
+      let channel = |shift: u16| ((attribute >> shift) & 0x1f) as f32 / 31.0;
+      [channel(10), channel(5), channel(0), 1.0]
+    });
+    colors.extend_from_slice(&[color; 3]);
+  }
+
+  let mut mesh = Mesh::from_vertices(&vertices);
+  if colors.iter().any(Option::is_some) {
+    mesh.colors = colors.into_iter().map(|color| color.unwrap_or([1.0; 4])).collect();
+  }
+  mesh
+}
+
+fn parse_ascii(data: &[u8]) -> Result<Mesh, StlError> {
+  let text = str::from_utf8(data).map_err(|_| StlError::Invalid("ASCII data isn't valid UTF-8".to_string()))?;
+
+  // Only `facet normal` and `vertex` carry data, the other keywords just delimit them.
+  let mut words = text.split_whitespace();
+  let mut vertices = Vec::new();
+  let mut normal = [0.0; 3];
+  let mut corners = Vec::with_capacity(3);
+  while let Some(word) = words.next() {
+    match word {
+      "normal" => normal = read_vector(&mut words, "normal")?,
+      "vertex" => corners.push(read_vector(&mut words, "vertex")?),
+      "endfacet" => {
+        if corners.len() != 3 {
+          return Err(StlError::Invalid(format!(
+            "a facet has {} vertices instead of 3",
+            corners.len()
+          )));
+        }
+        vertices.extend_from_slice(&facet(normal, [corners[0], corners[1], corners[2]]));
+        corners.clear();
+        normal = [0.0; 3];
+      }
+      _ => {}
+    }
+  }
+  Ok(Mesh::from_vertices(&vertices))
+}
+
+fn read_vector(words: &mut str::SplitWhitespace, what: &str) -> Result<[f32; 3], StlError> {
+  let mut v = [0.0; 3];
+  for component in &mut v {
+    let word = words
+      .next()
+      .ok_or_else(|| StlError::Invalid(format!("the data ends in a {}", what)))?;
+    *component = word
+      .parse()
+      .map_err(|_| StlError::Invalid(format!("invalid number `{}` in a {}", word, what)))?;
+  }
+  Ok(v)
+}
@@ -0,0 +1,132 @@
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
+use std::ptr;
+
+use crate::buffer::Buffer;
+
+/// An integer type usable as an element index.
+pub trait Index: Copy {
+  const GL_TYPE: GLenum;
+}
+
+impl Index for u8 {
+  const GL_TYPE: GLenum = gl::UNSIGNED_BYTE;
+}
+
+impl Index for u16 {
+  const GL_TYPE: GLenum = gl::UNSIGNED_SHORT;
+}
+
+impl Index for u32 {
+  const GL_TYPE: GLenum = gl::UNSIGNED_INT;
+}
+
+/// An element array buffer of `T` indices that remembers how many it holds.
+pub struct IndexBuffer<T: Index> {
+  buffer: Buffer,
+  count: usize,
+  _index: PhantomData<T>,
+}
+
+impl<T: Index> IndexBuffer<T> {
+  pub unsafe fn new(indices: &[T], usage: GLenum) -> Self {
+    let buffer = Buffer::new(gl::ELEMENT_ARRAY_BUFFER);
+    buffer.upload(indices, usage);
+    Self {
+      buffer,
+      count: indices.len(),
+      _index: PhantomData,
+    }
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.buffer.id()
+  }
+
+  pub fn count(&self) -> usize {
+    self.count
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Reallocates the buffer with new indices, which may differ in number.
+  pub unsafe fn upload(&mut self, indices: &[T], usage: GLenum) {
+    self.buffer.upload(indices, usage);
+    self.count = indices.len();
+  }
+
+  /// Overwrites part of the indices, starting at index `offset`.
+  pub unsafe fn update(&self, offset: usize, indices: &[T]) {
+    assert!(
+      offset + indices.len() <= self.count,
+      "index update {}..{} out of bounds for {} indices",
+      offset,
+      offset + indices.len(),
+      self.count
+    );
+    self.buffer.update(offset, indices);
+  }
+
+  /// Draws every index. The vertex array this buffer is attached to must be bound.
+  pub unsafe fn draw(&self, mode: GLenum) {
+    self.draw_range(mode, 0..self.count);
+  }
+
+  /// Draws the indices in `range`. The vertex array this buffer is attached to must be bound.
+  pub unsafe fn draw_range(&self, mode: GLenum, range: Range<usize>) {
+    assert!(
+      range.start <= range.end && range.end <= self.count,
+      "index range {:?} out of bounds for {} indices",
+      range,
+      self.count
+    );
+    debug_assert_eq!(
+      bound_element_array_buffer(),
+      self.id(),
+      "index buffer is not attached to the bound vertex array"
+    );
+
+    gl::DrawElements(
+      mode,
+      range.len() as GLsizei,
+      T::GL_TYPE,
+      (range.start * mem::size_of::<T>()) as *const c_void,
+    );
+  }
+
+  /// Draws all indices `instances` times, see `VertexArray::set_instance_buffer`. The vertex array this buffer is
+  /// attached to must be bound.
+  pub unsafe fn draw_instanced(&self, mode: GLenum, instances: usize) {
+    self.draw_instance_range(mode, 0..instances);
+  }
+
+  /// Like `draw_instanced`, but with per-instance attributes starting at instance `instances.start` of their
+  /// buffers, e.g. the range returned by `StreamBuffer::write`.
+  pub unsafe fn draw_instance_range(&self, mode: GLenum, instances: Range<usize>) {
+    debug_assert_eq!(
+      bound_element_array_buffer(),
+      self.id(),
+      "index buffer is not attached to the bound vertex array"
+    );
+
+    gl::DrawElementsInstancedBaseInstance(
+      mode,
+      self.count as GLsizei,
+      T::GL_TYPE,
+      ptr::null(),
+      instances.len() as GLsizei,
+      instances.start as GLuint,
+    );
+  }
+}
+
+unsafe fn bound_element_array_buffer() -> GLuint {
+  let mut id: GLint = 0;
+  gl::GetIntegerv(gl::ELEMENT_ARRAY_BUFFER_BINDING, &mut id);
+  id as GLuint
+}
@@ -0,0 +1,50 @@
+// Every `unsafe fn` here only requires a current OpenGL context with loaded function pointers.
+#![allow(clippy::missing_safety_doc)]
+
+// Lets the derive macros, which generate `::learn_opengl` paths, be used inside this crate.
+extern crate self as learn_opengl;
+
+pub mod animation;
+pub mod bounds;
+pub mod buffer;
+pub mod framebuffer;
+pub mod gl46;
+pub mod import;
+pub mod index_buffer;
+pub mod lod;
+pub mod mesh;
+pub mod mesh_cache;
+pub mod shader;
+pub mod stream_buffer;
+pub mod texture;
+pub mod uniform;
+pub mod vertex;
+pub mod vertex_array;
+
+pub use animation::{AnimationClip, Channel, ChannelValues, Interpolation, Skin, Transform};
+pub use bounds::{Aabb, BoundingSphere, Bounds, Frustum, Ray};
+pub use buffer::Buffer;
+pub use framebuffer::{Framebuffer, FramebufferError, Renderbuffer};
+pub use index_buffer::{Index, IndexBuffer};
+pub use learn_opengl_derive::{Uniforms, Vertex};
+pub use lod::{Lod, LodLevel};
+pub use mesh::{
+  GpuMesh, Mesh, MeshInstance, MeshVertex, MorphDelta, MorphTarget, OptimizeStats, SkinVertex, VertexColor,
+};
+pub use mesh_cache::{CachedMesh, MeshCache, MeshCacheError};
+pub use shader::{
+  Defines, ProgramBuilder, ProgramCache, ProgramVariants, ShaderError, ShaderProgram, ShaderStage, SpirvModule,
+};
+pub use stream_buffer::StreamBuffer;
+pub use texture::{Texture2D, TextureError, TexturePixel};
+pub use uniform::{TextureUnit, UniformValue, Uniforms};
+pub use vertex::Vertex;
+pub use vertex_array::VertexArray;
+
+use std::ffi::c_void;
+
+/// Loads the `gl` bindings together with the 4.6 functions in `gl46`.
+pub fn load_with<F: FnMut(&'static str) -> *const c_void>(mut loadfn: F) {
+  gl::load_with(&mut loadfn);
+  gl46::load_with(&mut loadfn);
+}
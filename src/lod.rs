@@ -0,0 +1,122 @@
+use crate::mesh::{GpuMesh, Mesh};
+
+/// One version of a mesh in a `Lod`, and how far its surface may be from the original's, in model units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LodLevel<T> {
+  pub mesh: T,
+  pub error: f32,
+}
+
+/// Levels of detail of a mesh, from the original to the coarsest, with growing errors.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lod<T> {
+  pub levels: Vec<LodLevel<T>>,
+}
+
+impl Lod<Mesh> {
+  /// Builds up to `count` levels, the first being `mesh` itself. Each of the others is simplified from `mesh` down
+  /// to `ratio` times the triangles of the one before, and building stops early once a level can't get smaller.
+  pub fn build(mesh: Mesh, count: usize, ratio: f32) -> Self {
+    let mut levels = vec![LodLevel { mesh, error: 0.0 }];
+    while levels.len() < count {
+      let previous = levels.last().unwrap();
+      let previous_triangles = previous.mesh.triangle_count();
+      let target = (previous_triangles as f32 * ratio) as usize;
+      if target == 0 || target >= previous_triangles {
+        break;
+      }
+
+      let mut simplified = levels[0].mesh.clone();
+      let error = simplified.simplify(target, f32::INFINITY);
+      if simplified.triangle_count() >= previous_triangles {
+        break;
+      }
+      // Simplifying further can land closer to the original than the level before, but never counts as finer.
+      let error = error.max(previous.error);
+      levels.push(LodLevel {
+        mesh: simplified,
+        error,
+      });
+    }
+    Self { levels }
+  }
+
+  pub unsafe fn upload(&self) -> Lod<GpuMesh> {
+    Lod {
+      levels: self
+        .levels
+        .iter()
+        .map(|level| LodLevel {
+          mesh: level.mesh.upload(),
+          error: level.error,
+        })
+        .collect(),
+    }
+  }
+}
+
+impl<T> Lod<T> {
+  /// The index of the coarsest level whose error covers at most `max_pixel_error` pixels on screen, given how many
+  /// pixels a model unit covers where the mesh is drawn (see `pixels_per_unit`). The first level when none does.
+  pub fn select(&self, pixels_per_unit: f32, max_pixel_error: f32) -> usize {
+    self
+      .levels
+      .iter()
+      .rposition(|level| level.error * pixels_per_unit <= max_pixel_error)
+      .unwrap_or(0)
+  }
+}
+
+/// How many pixels a length of one model unit covers at `distance` from a perspective camera with a vertical field
+/// of view of `fov_y` radians, drawing to a viewport `viewport_height` pixels high. Scale the result by the model
+/// matrix's scale when it has one.
+pub fn pixels_per_unit(distance: f32, fov_y: f32, viewport_height: f32) -> f32 {
+  viewport_height / (2.0 * distance.max(f32::EPSILON) * (fov_y / 2.0).tan())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn levels_get_coarser() {
+    let sphere = Mesh::uv_sphere(1.0, 32, 16);
+    let lod = Lod::build(sphere.clone(), 4, 0.5);
+    assert_eq!(lod.levels.len(), 4);
+    assert_eq!(
+      lod.levels[0],
+      LodLevel {
+        mesh: sphere,
+        error: 0.0
+      }
+    );
+    for pair in lod.levels.windows(2) {
+      assert!(pair[1].mesh.triangle_count() * 2 <= pair[0].mesh.triangle_count());
+      assert!(pair[1].error >= pair[0].error);
+    }
+    assert!(lod.levels[3].error > 0.0 && lod.levels[3].error < 0.5);
+
+    // A cube has nothing left to remove without losing its corners.
+    let cube = Lod::build(Mesh::cube(1.0), 4, 0.5);
+    assert_eq!(cube.levels.len(), 1);
+  }
+
+  #[test]
+  fn selection_follows_screen_size() {
+    assert!((pixels_per_unit(1.0, std::f32::consts::FRAC_PI_2, 600.0) - 300.0).abs() < 1e-3);
+    assert!((pixels_per_unit(3.0, std::f32::consts::FRAC_PI_2, 600.0) - 100.0).abs() < 1e-3);
+
+    let lod = Lod {
+      levels: [0.0, 0.01, 0.1]
+        .iter()
+        .map(|&error| LodLevel { mesh: (), error })
+        .collect(),
+    };
+    assert_eq!(lod.select(1000.0, 1.0), 0);
+    assert_eq!(lod.select(100.0, 1.0), 1);
+    assert_eq!(lod.select(10.0, 1.0), 2);
+    assert_eq!(lod.select(1.0, 1.0), 2);
+    // Nothing is ever too fine.
+    assert_eq!(lod.select(1000.0, 0.0), 0);
+  }
+}
@@ -0,0 +1,395 @@
+use std::ops::Range;
+
+use crate::bounds::Bounds;
+use crate::buffer::Buffer;
+use crate::index_buffer::IndexBuffer;
+use crate::vertex_array::VertexArray;
+use crate::Vertex;
+
+mod morph;
+mod normals;
+mod optimize;
+mod polygon;
+mod primitives;
+mod simplify;
+mod skinning;
+mod tangents;
+
+pub use optimize::OptimizeStats;
+pub(crate) use polygon::triangulate;
+
+/// An indexed triangle mesh with one normal and texture coordinate per vertex.
+///
+/// Triangles are wound counter-clockwise when seen from the front, and `v` runs up the texture like in GL.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+  pub positions: Vec<[f32; 3]>,
+  pub normals: Vec<[f32; 3]>,
+  pub uvs: Vec<[f32; 2]>,
+  /// Tangent in `xyz` and handedness in `w`, so the bitangent is `w * cross(normal, tangent)`.
+  /// Empty until `compute_tangents` is called.
+  pub tangents: Vec<[f32; 4]>,
+  /// Linear RGBA per vertex, like the colors of scanned PLY data. Empty for meshes without colors.
+  pub colors: Vec<[f32; 4]>,
+  /// Up to four joints per vertex for skinned meshes, as indices into `Skin::joints`. Empty for meshes without a skin.
+  pub joints: Vec<[u16; 4]>,
+  /// How much each of `joints` moves the vertex, summing to 1. Empty for meshes without a skin.
+  pub weights: Vec<[f32; 4]>,
+  /// Blend shapes, weighted and added to the positions and normals by `morphed` or the `MORPHED` variant of
+  /// `shaders/lit.vert`.
+  pub morph_targets: Vec<MorphTarget>,
+  pub indices: Vec<u32>,
+}
+
+/// Offsets of every vertex of a mesh, like a smile on a face, moved in as its weight goes from 0 to 1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MorphTarget {
+  pub positions: Vec<[f32; 3]>,
+  /// Zero for targets that only move positions.
+  pub normals: Vec<[f32; 3]>,
+}
+
+/// The interleaved vertex uploaded by `Mesh::upload`: position, normal and uv at locations 0, 1 and 2.
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[repr(C)]
+pub struct MeshVertex {
+  pub position: [f32; 3],
+  pub normal: [f32; 3],
+  pub uv: [f32; 2],
+}
+
+/// The per-vertex color uploaded by `Mesh::upload` for meshes with colors, at location 8 after the `MeshInstance`
+/// attributes. Shaders reading it get black for meshes without colors.
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[repr(C)]
+pub struct VertexColor {
+  #[vertex(location = 8)]
+  pub color: [f32; 4],
+}
+
+/// The joints and weights uploaded by `Mesh::upload` for skinned meshes, at locations 9 and 10 after `VertexColor`.
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[repr(C)]
+pub struct SkinVertex {
+  #[vertex(location = 9, integer)]
+  pub joints: [u16; 4],
+  #[vertex(location = 10)]
+  pub weights: [f32; 4],
+}
+
+/// A vertex offset of one morph target, laid out for the std430 `MorphTargets` buffer of `shaders/lit.vert`, which
+/// holds the offsets of every vertex of the first target, then of the second one and so on. `w` is unused.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct MorphDelta {
+  pub position: [f32; 4],
+  pub normal: [f32; 4],
+}
+
+/// Per-instance data for drawing a mesh many times with `GpuMesh::draw_instanced`: a model matrix at locations 3
+/// to 6 and a color at 7, after the `MeshVertex` attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[repr(C)]
+pub struct MeshInstance {
+  #[vertex(location = 3)]
+  pub model: mint::ColumnMatrix4<f32>,
+  #[vertex(location = 7)]
+  pub color: [f32; 4],
+}
+
+impl Mesh {
+  /// Builds a mesh from a flat triangle list, three vertices per triangle and none shared. `weld` turns it into
+  /// an indexed one.
+  pub fn from_vertices(vertices: &[MeshVertex]) -> Self {
+    let mut mesh = Self::default();
+    for vertex in vertices {
+      let index = mesh.push_vertex(vertex.position, vertex.normal, vertex.uv);
+      mesh.indices.push(index);
+    }
+    mesh
+  }
+
+  pub fn vertex_count(&self) -> usize {
+    self.positions.len()
+  }
+
+  pub fn triangle_count(&self) -> usize {
+    self.indices.len() / 3
+  }
+
+  pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+    self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+  }
+
+  /// The bounding box and sphere of the positions, `None` for an empty mesh.
+  ///
+  /// They're computed on every call rather than stored, since `positions` can be edited freely: keep the result
+  /// around when it's needed often. `upload` does, and `GpuMesh::bounds` hands out the bounds it computed then.
+  pub fn bounds(&self) -> Option<Bounds> {
+    Bounds::from_points(&self.positions)
+  }
+
+  pub fn vertices(&self) -> Vec<MeshVertex> {
+    (0..self.vertex_count())
+      .map(|i| MeshVertex {
+        position: self.positions[i],
+        normal: self.normals[i],
+        uv: self.uvs[i],
+      })
+      .collect()
+  }
+
+  /// The joints and weights of every vertex, empty for meshes without a skin.
+  pub fn skin_vertices(&self) -> Vec<SkinVertex> {
+    self
+      .joints
+      .iter()
+      .zip(&self.weights)
+      .map(|(&joints, &weights)| SkinVertex { joints, weights })
+      .collect()
+  }
+
+  /// The offsets of every morph target, one target after the other.
+  pub fn morph_deltas(&self) -> Vec<MorphDelta> {
+    self
+      .morph_targets
+      .iter()
+      .flat_map(|target| target.positions.iter().zip(&target.normals))
+      .map(|(p, n)| MorphDelta {
+        position: [p[0], p[1], p[2], 0.0],
+        normal: [n[0], n[1], n[2], 0.0],
+      })
+      .collect()
+  }
+
+  /// Uploads the mesh into a new vertex array with its own vertex and index buffers, plus a buffer of
+  /// `VertexColor`s if the mesh has colors, one of `SkinVertex`es if it has a skin and a storage buffer of
+  /// `MorphDelta`s if it has morph targets. The bounds are computed once here, as the uploaded mesh can't change
+  /// anymore.
+  pub unsafe fn upload(&self) -> GpuMesh {
+    let colors: Vec<VertexColor> = self.colors.iter().map(|&color| VertexColor { color }).collect();
+    GpuMesh::new(
+      &self.vertices(),
+      &colors,
+      &self.skin_vertices(),
+      &self.morph_deltas(),
+      &self.indices,
+      self.bounds(),
+    )
+  }
+
+  pub(crate) fn push_vertex(&mut self, position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) -> u32 {
+    self.positions.push(position);
+    self.normals.push(normal);
+    self.uvs.push(uv);
+    self.positions.len() as u32 - 1
+  }
+
+  /// Appends a copy of vertex `i` of `source` with a new normal, keeping its color, joints and morph target offsets
+  /// if `source` has them.
+  pub(crate) fn push_vertex_of(&mut self, source: &Mesh, i: usize, normal: [f32; 3]) -> u32 {
+    self
+      .morph_targets
+      .resize_with(source.morph_targets.len(), MorphTarget::default);
+    for (target, source) in self.morph_targets.iter_mut().zip(&source.morph_targets) {
+      target.positions.push(source.positions[i]);
+      target.normals.push(source.normals[i]);
+    }
+    if !source.colors.is_empty() {
+      self.colors.push(source.colors[i]);
+    }
+    if !source.joints.is_empty() {
+      self.joints.push(source.joints[i]);
+      self.weights.push(source.weights[i]);
+    }
+    self.push_vertex(source.positions[i], normal, source.uvs[i])
+  }
+}
+
+/// A mesh living in GPU buffers.
+pub struct GpuMesh {
+  _vertices: Buffer,
+  _colors: Option<Buffer>,
+  _skin: Option<Buffer>,
+  morph_targets: Option<Buffer>,
+  indices: IndexBuffer<u32>,
+  vertex_array: VertexArray,
+  bounds: Option<Bounds>,
+}
+
+impl GpuMesh {
+  /// Uploads vertex data as it is laid out in GPU buffers, with no color, skin or morph target buffer if `colors`,
+  /// `skin` or `morph_deltas` is empty.
+  pub(crate) unsafe fn new(
+    vertices: &[MeshVertex],
+    colors: &[VertexColor],
+    skin: &[SkinVertex],
+    morph_deltas: &[MorphDelta],
+    indices: &[u32],
+    bounds: Option<Bounds>,
+  ) -> Self {
+    let vertex_buffer = Buffer::new(gl::ARRAY_BUFFER);
+    vertex_buffer.upload(vertices, gl::STATIC_DRAW);
+    let indices = IndexBuffer::new(indices, gl::STATIC_DRAW);
+
+    let mut builder = VertexArray::builder()
+      .vertex_buffer::<MeshVertex>(&vertex_buffer)
+      .index_buffer(&indices);
+    let colors = if colors.is_empty() {
+      None
+    } else {
+      let buffer = Buffer::new(gl::ARRAY_BUFFER);
+      buffer.upload(colors, gl::STATIC_DRAW);
+      builder = builder.vertex_buffer::<VertexColor>(&buffer);
+      Some(buffer)
+    };
+    let skin = if skin.is_empty() {
+      None
+    } else {
+      let buffer = Buffer::new(gl::ARRAY_BUFFER);
+      buffer.upload(skin, gl::STATIC_DRAW);
+      builder = builder.vertex_buffer::<SkinVertex>(&buffer);
+      Some(buffer)
+    };
+    let vertex_array = builder.build();
+    let morph_targets = if morph_deltas.is_empty() {
+      None
+    } else {
+      let buffer = Buffer::new(gl::SHADER_STORAGE_BUFFER);
+      buffer.upload(morph_deltas, gl::STATIC_DRAW);
+      Some(buffer)
+    };
+
+    Self {
+      _vertices: vertex_buffer,
+      _colors: colors,
+      _skin: skin,
+      morph_targets,
+      indices,
+      vertex_array,
+      bounds,
+    }
+  }
+
+  pub fn vertex_array(&self) -> &VertexArray {
+    &self.vertex_array
+  }
+
+  pub fn index_count(&self) -> usize {
+    self.indices.count()
+  }
+
+  /// The bounds of the mesh in model space, `None` if it was empty. `Bounds::transformed` moves them into the world
+  /// for culling and picking.
+  pub fn bounds(&self) -> Option<&Bounds> {
+    self.bounds.as_ref()
+  }
+
+  /// The storage buffer of `MorphDelta`s, `None` for meshes without morph targets. `shaders/lit.vert` reads it at
+  /// binding 1, see `Buffer::bind_base`.
+  pub fn morph_targets(&self) -> Option<&Buffer> {
+    self.morph_targets.as_ref()
+  }
+
+  pub unsafe fn draw(&self) {
+    let _vao = self.vertex_array.bind();
+    self.indices.draw(gl::TRIANGLES);
+  }
+
+  /// Draws the mesh `instances` times, with per-instance attributes from a buffer attached through
+  /// `vertex_array().set_instance_buffer`, usually a `[MeshInstance]`.
+  pub unsafe fn draw_instanced(&self, instances: usize) {
+    self.draw_instance_range(0..instances);
+  }
+
+  /// Draws the instances in `instances` of the attached instance buffer.
+  pub unsafe fn draw_instance_range(&self, instances: Range<usize>) {
+    let _vao = self.vertex_array.bind();
+    self.indices.draw_instance_range(gl::TRIANGLES, instances);
+  }
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f32; 3], s: f32) -> [f32; 3] {
+  [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+  a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [
+    a[1] * b[2] - a[2] * b[1],
+    a[2] * b[0] - a[0] * b[2],
+    a[0] * b[1] - a[1] * b[0],
+  ]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+  let len = dot(a, a).sqrt();
+  [a[0] / len, a[1] / len, a[2] / len]
+}
+
+/// Like `normalize`, but `None` for vectors too short to have a direction.
+pub(crate) fn try_normalize(a: [f32; 3]) -> Option<[f32; 3]> {
+  let len = dot(a, a).sqrt();
+  if len > 1e-12 {
+    Some(scale(a, 1.0 / len))
+  } else {
+    None
+  }
+}
+
+/// The interior angle of a triangle at `corner`, used to weight its contribution to vertex normals and tangents.
+fn corner_angle(corner: [f32; 3], a: [f32; 3], b: [f32; 3]) -> f32 {
+  match (try_normalize(sub(a, corner)), try_normalize(sub(b, corner))) {
+    (Some(ea), Some(eb)) => dot(ea, eb).clamp(-1.0, 1.0).acos(),
+    _ => 0.0,
+  }
+}
+
+/// Groups vertices whose positions are within `epsilon` of each other on every axis, returning a group per vertex.
+///
+/// Groups are numbered by their lowest vertex, so a vertex with no duplicates is in the group of its own index.
+fn position_groups(positions: &[[f32; 3]], epsilon: f32) -> Vec<u32> {
+  fn find(parents: &mut [u32], i: u32) -> u32 {
+    let mut root = i;
+    while parents[root as usize] != root {
+      root = parents[root as usize];
+    }
+    parents[i as usize] = root;
+    root
+  }
+
+  // Points with a NaN or infinite coordinate aren't near anything, so they stay in their own groups. Left in, a NaN
+  // would sort first or last depending on its sign bit and compare as "close" on the axes it doesn't have.
+  let mut order: Vec<u32> = (0..positions.len() as u32)
+    .filter(|&i| positions[i as usize].iter().all(|c| c.is_finite()))
+    .collect();
+  order.sort_by(|&a, &b| positions[a as usize][0].total_cmp(&positions[b as usize][0]));
+
+  let mut parents: Vec<u32> = (0..positions.len() as u32).collect();
+  for (k, &a) in order.iter().enumerate() {
+    let pa = positions[a as usize];
+    for &b in order[k + 1..].iter() {
+      let pb = positions[b as usize];
+      if pb[0] - pa[0] > epsilon {
+        break;
+      }
+      if (pb[1] - pa[1]).abs() <= epsilon && (pb[2] - pa[2]).abs() <= epsilon {
+        let (ra, rb) = (find(&mut parents, a), find(&mut parents, b));
+        parents[ra.max(rb) as usize] = ra.min(rb);
+      }
+    }
+  }
+
+  (0..positions.len() as u32).map(|i| find(&mut parents, i)).collect()
+}
@@ -0,0 +1,99 @@
+use super::{add, normalize, scale, Mesh};
+
+impl Mesh {
+  /// Applies the morph targets on the CPU the way the `MORPHED` variant of `shaders/lit.vert` does on the GPU, given a
+  /// weight per target.
+  ///
+  /// Every position and normal gets the weighted sum of the targets' offsets added, and normals are normalized
+  /// again. Targets without a weight count as 0. The returned mesh keeps the targets, offsetting it again adds to the
+  /// offsets already applied.
+  pub fn morphed(&self, weights: &[f32]) -> Mesh {
+    let mut morphed = self.clone();
+    let mut moved_normals = false;
+    for (target, &weight) in self.morph_targets.iter().zip(weights) {
+      if weight == 0.0 {
+        continue;
+      }
+      for (position, &delta) in morphed.positions.iter_mut().zip(&target.positions) {
+        *position = add(*position, scale(delta, weight));
+      }
+      for (normal, &delta) in morphed.normals.iter_mut().zip(&target.normals) {
+        *normal = add(*normal, scale(delta, weight));
+      }
+      moved_normals = true;
+    }
+
+    if moved_normals {
+      for normal in &mut morphed.normals {
+        *normal = normalize(*normal);
+      }
+    }
+    morphed
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mesh::MorphTarget;
+
+  fn plane_with_targets() -> Mesh {
+    let mut mesh = Mesh::plane(2.0, 2.0);
+    let count = mesh.vertex_count();
+    // Lifts the first vertex, and bends every normal towards +x.
+    let mut lift = MorphTarget {
+      positions: vec![[0.0; 3]; count],
+      normals: vec![[0.0; 3]; count],
+    };
+    lift.positions[0] = [0.0, 1.0, 0.0];
+    let tilt = MorphTarget {
+      positions: vec![[0.5, 0.0, 0.0]; count],
+      normals: vec![[1.0, -1.0, 0.0]; count],
+    };
+    mesh.morph_targets = vec![lift, tilt];
+    mesh
+  }
+
+  #[test]
+  fn targets_add_their_weighted_offsets() {
+    let mesh = plane_with_targets();
+    assert_eq!(mesh.morphed(&[]), mesh);
+    assert_eq!(mesh.morphed(&[0.0, 0.0]), mesh);
+
+    let lifted = mesh.morphed(&[0.5]);
+    let [x, y, z] = mesh.positions[0];
+    assert_eq!(lifted.positions[0], [x, y + 0.5, z]);
+    assert_eq!(lifted.positions[1..], mesh.positions[1..]);
+    assert_eq!(lifted.normals, mesh.normals);
+
+    let both = mesh.morphed(&[1.0, 0.5]);
+    assert_eq!(both.positions[0], [x + 0.25, y + 1.0, z]);
+    // The normals start at +y: halfway into the tilt, they point between +x and +y.
+    let [nx, ny, _] = both.normals[1];
+    assert!((nx - ny).abs() < 1e-6 && (nx * nx + ny * ny - 1.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn welding_keeps_targets_with_their_vertices() {
+    let mesh = plane_with_targets();
+    let vertices = mesh.vertices();
+    let mut flat = Mesh::from_vertices(&mesh.indices.iter().map(|&i| vertices[i as usize]).collect::<Vec<_>>());
+    flat.morph_targets = mesh
+      .morph_targets
+      .iter()
+      .map(|target| MorphTarget {
+        positions: mesh.indices.iter().map(|&i| target.positions[i as usize]).collect(),
+        normals: mesh.indices.iter().map(|&i| target.normals[i as usize]).collect(),
+      })
+      .collect();
+
+    flat.weld(1e-6);
+    assert_eq!(flat.vertex_count(), mesh.vertex_count());
+    let lifted = flat.morphed(&[1.0]);
+    let moved: Vec<_> = (0..flat.vertex_count())
+      .filter(|&i| lifted.positions[i] != flat.positions[i])
+      .collect();
+    assert_eq!(moved.len(), 1);
+    assert_eq!(flat.positions[moved[0]], mesh.positions[0]);
+  }
+}
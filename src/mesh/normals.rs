@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use super::{add, corner_angle, cross, dot, position_groups, scale, sub, try_normalize, Mesh};
+
+/// Positions closer than this on every axis count as the same point when smoothing normals.
+const POSITION_EPSILON: f32 = 1e-5;
+
+impl Mesh {
+  /// Gives every triangle its own three vertices carrying the face normal, for a faceted look.
+  ///
+  /// Clears `tangents`, which depend on the normals.
+  pub fn compute_flat_normals(&mut self) {
+    let mut flat = Self::default();
+    for triangle in self.triangles() {
+      let [pa, pb, pc] = triangle.map(|i| self.positions[i as usize]);
+      let normal = try_normalize(cross(sub(pb, pa), sub(pc, pa))).unwrap_or([0.0, 1.0, 0.0]);
+      for &i in &triangle {
+        let vertex = flat.push_vertex_of(self, i as usize, normal);
+        flat.indices.push(vertex);
+      }
+    }
+    *self = flat;
+  }
+
+  /// Sets each vertex normal to the average of the face normals around its position, weighted by the angle of
+  /// each face at that corner.
+  ///
+  /// Faces meeting at more than `max_angle` radians don't share normals, which keeps hard edges sharp: with a
+  /// threshold below 90° a cube keeps its flat faces, with `PI` everything is smoothed. Vertices on hard edges are
+  /// split as needed, while vertices duplicated for texture seams get the same normal on both sides.
+  ///
+  /// Clears `tangents`, which depend on the normals.
+  pub fn compute_smooth_normals(&mut self, max_angle: f32) {
+    let triangles: Vec<[u32; 3]> = self.triangles().collect();
+    let mut face_normals = Vec::with_capacity(triangles.len());
+    let mut corner_weights = Vec::with_capacity(triangles.len() * 3);
+    for triangle in &triangles {
+      let [pa, pb, pc] = triangle.map(|i| self.positions[i as usize]);
+      face_normals.push(try_normalize(cross(sub(pb, pa), sub(pc, pa))));
+      corner_weights.extend_from_slice(&[
+        corner_angle(pa, pb, pc),
+        corner_angle(pb, pc, pa),
+        corner_angle(pc, pa, pb),
+      ]);
+    }
+
+    let groups = position_groups(&self.positions, POSITION_EPSILON);
+    let mut corners_at: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (corner, &vertex) in self.indices.iter().enumerate() {
+      corners_at.entry(groups[vertex as usize]).or_default().push(corner);
+    }
+
+    let min_cos = max_angle.cos();
+    let mut smooth = Self::default();
+    let mut split_vertices = HashMap::new();
+    for (corner, &vertex) in self.indices.iter().enumerate() {
+      let face_normal = face_normals[corner / 3];
+      let normal = face_normal.and_then(|face_normal| {
+        let mut sum = [0.0; 3];
+        for &other in &corners_at[&groups[vertex as usize]] {
+          if let Some(other_normal) = face_normals[other / 3] {
+            if dot(other_normal, face_normal) >= min_cos {
+              sum = add(sum, scale(other_normal, corner_weights[other]));
+            }
+          }
+        }
+        try_normalize(sum)
+      });
+      // Degenerate faces keep the vertex's normal, which files without normals leave at zero.
+      let normal = normal.unwrap_or_else(|| try_normalize(self.normals[vertex as usize]).unwrap_or([0.0, 1.0, 0.0]));
+
+      // Corners that gathered the same faces sum them in the same order, so they match bit for bit.
+      let key = (vertex, normal.map(f32::to_bits));
+      let new_vertex = *split_vertices
+        .entry(key)
+        .or_insert_with(|| smooth.push_vertex_of(self, vertex as usize, normal));
+      smooth.indices.push(new_vertex);
+    }
+    *self = smooth;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mesh::{normalize, MeshVertex};
+  use std::f32::consts::PI;
+
+  fn assert_close(a: [f32; 3], b: [f32; 3]) {
+    assert!(dot(sub(a, b), sub(a, b)) < 1e-8, "{:?} != {:?}", a, b);
+  }
+
+  #[test]
+  fn flat_normals_are_face_normals() {
+    let mut mesh = Mesh::uv_sphere(1.0, 12, 6);
+    let triangles = mesh.triangle_count();
+    mesh.compute_flat_normals();
+
+    assert_eq!(mesh.vertex_count(), 3 * triangles);
+    for [a, b, c] in mesh.triangles() {
+      let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+      let face = normalize(cross(sub(pb, pa), sub(pc, pa)));
+      for &i in &[a, b, c] {
+        assert_close(mesh.normals[i as usize], face);
+      }
+      assert!(dot(face, add(add(pa, pb), pc)) > 0.0, "face normal points inwards");
+    }
+  }
+
+  #[test]
+  fn hard_edges_below_the_threshold_stay_hard() {
+    let cube = Mesh::cube(1.0);
+    let mut smoothed = cube.clone();
+    smoothed.compute_smooth_normals(PI / 4.0);
+
+    assert_eq!(smoothed.vertex_count(), cube.vertex_count());
+    for (a, b) in smoothed.normals.iter().zip(&cube.normals) {
+      assert_close(*a, *b);
+    }
+  }
+
+  #[test]
+  fn smoothing_everything_rounds_the_cube_corners() {
+    let mut cube = Mesh::cube(1.0);
+    cube.compute_smooth_normals(PI);
+
+    // Every corner sees three faces at right angles, so the normals point away from the center.
+    assert_eq!(cube.vertex_count(), 24);
+    for (p, n) in cube.positions.iter().zip(&cube.normals) {
+      assert_close(*n, normalize(*p));
+    }
+  }
+
+  #[test]
+  fn smoothing_a_faceted_sphere_recovers_radial_normals() {
+    let mut sphere = Mesh::uv_sphere(1.0, 32, 16);
+    sphere.compute_flat_normals();
+    sphere.compute_smooth_normals(PI / 3.0);
+
+    for (p, n) in sphere.positions.iter().zip(&sphere.normals) {
+      assert!(dot(normalize(*p), *n) > 0.99, "normal {:?} at {:?}", n, p);
+    }
+
+    // The faceted sphere is welded back to one normal per position, plus the copies on the texture seam.
+    let groups = position_groups(&sphere.positions, POSITION_EPSILON);
+    let mut normals_at: HashMap<u32, Vec<[f32; 3]>> = HashMap::new();
+    for (vertex, &group) in groups.iter().enumerate() {
+      normals_at.entry(group).or_default().push(sphere.normals[vertex]);
+    }
+    for normals in normals_at.values() {
+      for n in normals {
+        assert_close(*n, normals[0]);
+      }
+    }
+  }
+
+  #[test]
+  fn cylinder_keeps_its_rims_sharp() {
+    let mut cylinder = Mesh::cylinder(1.0, 2.0, 16);
+    cylinder.compute_flat_normals();
+    cylinder.compute_smooth_normals(PI / 4.0);
+
+    for (p, n) in cylinder.positions.iter().zip(&cylinder.normals) {
+      let on_cap = (n[1].abs() - 1.0).abs() < 1e-5;
+      let on_side = n[1].abs() < 1e-5 && dot(normalize([p[0], 0.0, p[2]]), *n) > 0.99;
+      assert!(on_cap || on_side, "normal {:?} at {:?}", n, p);
+    }
+  }
+
+  #[test]
+  fn zero_area_triangles_get_a_valid_normal() {
+    // A triangle collapsed to a line, with the zero normals of a file that has none.
+    let vertex = |x| MeshVertex {
+      position: [x, 0.0, 0.0],
+      normal: [0.0; 3],
+      uv: [0.0; 2],
+    };
+    let mut mesh = Mesh::from_vertices(&[vertex(0.0), vertex(1.0), vertex(2.0)]);
+    mesh.compute_smooth_normals(PI);
+    assert_eq!(mesh.normals, vec![[0.0, 1.0, 0.0]; 3]);
+
+    // Next to a real face, the degenerate one keeps the placeholder while the face's own corners are unaffected.
+    let mut mesh = Mesh::from_vertices(&[
+      vertex(0.0),
+      vertex(1.0),
+      vertex(2.0),
+      vertex(0.0),
+      vertex(1.0),
+      MeshVertex {
+        position: [0.0, 0.0, -1.0],
+        ..vertex(0.0)
+      },
+    ]);
+    mesh.compute_smooth_normals(PI);
+    assert!(mesh.normals.iter().all(|n| n.iter().all(|c| c.is_finite())));
+    assert_close(mesh.normals[mesh.indices[0] as usize], [0.0, 1.0, 0.0]);
+    assert_close(mesh.normals[mesh.indices[3] as usize], [0.0, 1.0, 0.0]);
+  }
+
+  #[test]
+  fn nan_positions_dont_panic() {
+    let mut mesh = Mesh::plane(1.0, 1.0);
+    mesh.positions[0] = [f32::NAN, 0.0, 0.0];
+    mesh.compute_smooth_normals(PI);
+    assert_eq!(mesh.triangle_count(), 2);
+
+    // NaNs of either sign are in groups of their own, and don't join the points sharing their other coordinates.
+    let positions = [
+      [-f32::NAN, 0.0, 0.0],
+      [1.0, 0.0, 0.0],
+      [f32::NAN, 0.0, 0.0],
+      [f32::NAN, 0.0, 0.0],
+      [5.0, 0.0, 0.0],
+    ];
+    assert_eq!(position_groups(&positions, POSITION_EPSILON), [0, 1, 2, 3, 4]);
+
+    // Two faces at right angles, each with a corner level with a NaN point, keep their own normals.
+    let vertex = |position| MeshVertex {
+      position,
+      normal: [0.0; 3],
+      uv: [0.0; 2],
+    };
+    let mut mesh = Mesh::from_vertices(&[
+      vertex([-f32::NAN, 0.0, 0.0]),
+      vertex([0.0, 1.0, 0.0]),
+      vertex([0.0, 0.0, 1.0]),
+      vertex([1.0, 0.0, 0.0]),
+      vertex([2.0, 0.0, 0.0]),
+      vertex([1.0, 1.0, 0.0]),
+      vertex([5.0, 0.0, 0.0]),
+      vertex([5.0, 0.0, -1.0]),
+      vertex([5.0, 1.0, 0.0]),
+    ]);
+    mesh.compute_smooth_normals(PI);
+    assert_close(mesh.normals[mesh.indices[3] as usize], [0.0, 0.0, 1.0]);
+    assert_close(mesh.normals[mesh.indices[6] as usize], [1.0, 0.0, 0.0]);
+  }
+}
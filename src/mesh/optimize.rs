@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{position_groups, Mesh};
+
+/// The size of the LRU vertex cache that `optimize_vertex_cache` orders for and `acmr` simulates.
+const CACHE_SIZE: usize = 32;
+
+// The scoring constants from Tom Forsyth's "Linear-Speed Vertex Cache Optimisation".
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+/// What `Mesh::optimize` did to a mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OptimizeStats {
+  pub vertices_before: usize,
+  pub vertices_after: usize,
+  /// Lower after welding when triangles collapse to a line or a point.
+  pub triangles_before: usize,
+  pub triangles_after: usize,
+  pub acmr_before: f32,
+  pub acmr_after: f32,
+}
+
+impl OptimizeStats {
+  /// The fraction of vertices removed, between 0 and 1.
+  pub fn vertex_reduction(&self) -> f32 {
+    if self.vertices_before == 0 {
+      0.0
+    } else {
+      1.0 - self.vertices_after as f32 / self.vertices_before as f32
+    }
+  }
+}
+
+impl fmt::Display for OptimizeStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} -> {} vertices ({:.1}% fewer), {} -> {} triangles, ACMR {:.3} -> {:.3}",
+      self.vertices_before,
+      self.vertices_after,
+      self.vertex_reduction() * 100.0,
+      self.triangles_before,
+      self.triangles_after,
+      self.acmr_before,
+      self.acmr_after
+    )
+  }
+}
+
+impl Mesh {
+  /// Welds vertices together and reorders the result for the vertex cache, see `weld` and
+  /// `optimize_vertex_cache`.
+  pub fn optimize(&mut self, epsilon: f32) -> OptimizeStats {
+    let (vertices_before, triangles_before, acmr_before) = (self.vertex_count(), self.triangle_count(), self.acmr());
+    self.weld(epsilon);
+    self.optimize_vertex_cache();
+    OptimizeStats {
+      vertices_before,
+      vertices_after: self.vertex_count(),
+      triangles_before,
+      triangles_after: self.triangle_count(),
+      acmr_before,
+      acmr_after: self.acmr(),
+    }
+  }
+
+  /// Merges vertices whose position, normal, texture coordinates, tangent, color, weights and morph target offsets
+  /// (if any) all differ by at most `epsilon` per component and whose joints are the same, keeping the first one in
+  /// index order.
+  ///
+  /// Vertices no triangle uses are dropped, and so are triangles left with two corners on the same vertex.
+  pub fn weld(&mut self, epsilon: f32) {
+    let has_tangents = !self.tangents.is_empty();
+    let has_colors = !self.colors.is_empty();
+    let has_skin = !self.joints.is_empty();
+    let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(a, b)| (a - b).abs() <= epsilon);
+    let groups = position_groups(&self.positions, epsilon);
+
+    let mut welded = Self::default();
+    let mut remap = vec![None; self.vertex_count()];
+    let mut welded_in_group: HashMap<u32, Vec<u32>> = HashMap::new();
+    for &vertex in &self.indices {
+      let i = vertex as usize;
+      if remap[i].is_some() {
+        continue;
+      }
+
+      let candidates = welded_in_group.entry(groups[i]).or_default();
+      let existing = candidates.iter().copied().find(|&w| {
+        let w = w as usize;
+        close(&self.positions[i], &welded.positions[w])
+          && close(&self.normals[i], &welded.normals[w])
+          && close(&self.uvs[i], &welded.uvs[w])
+          && (!has_tangents || close(&self.tangents[i], &welded.tangents[w]))
+          && (!has_colors || close(&self.colors[i], &welded.colors[w]))
+          && (!has_skin || (self.joints[i] == welded.joints[w] && close(&self.weights[i], &welded.weights[w])))
+          && self
+            .morph_targets
+            .iter()
+            .zip(&welded.morph_targets)
+            .all(|(target, welded)| {
+              close(&target.positions[i], &welded.positions[w]) && close(&target.normals[i], &welded.normals[w])
+            })
+      });
+      remap[i] = Some(existing.unwrap_or_else(|| {
+        let new_vertex = welded.push_vertex_of(self, i, self.normals[i]);
+        if has_tangents {
+          welded.tangents.push(self.tangents[i]);
+        }
+        candidates.push(new_vertex);
+        new_vertex
+      }));
+    }
+
+    for triangle in self.triangles() {
+      let [a, b, c] = triangle.map(|i| remap[i as usize].unwrap());
+      if a != b && b != c && c != a {
+        welded.indices.extend_from_slice(&[a, b, c]);
+      }
+    }
+    *self = welded;
+  }
+
+  /// Reorders triangles so that vertices are reused while still in the post-transform cache, using Tom Forsyth's
+  /// algorithm, then renumbers vertices in the order they are first used so that they're also fetched in order.
+  ///
+  /// Only the order changes: every triangle keeps its vertices and winding.
+  pub fn optimize_vertex_cache(&mut self) {
+    let triangles: Vec<[u32; 3]> = self.triangles().collect();
+    let order = forsyth_order(&triangles, self.vertex_count());
+
+    let mut reordered = Self::default();
+    let has_tangents = !self.tangents.is_empty();
+    let mut remap = vec![None; self.vertex_count()];
+    for &t in &order {
+      for &vertex in &triangles[t] {
+        let i = vertex as usize;
+        let new_vertex = *remap[i].get_or_insert_with(|| {
+          if has_tangents {
+            reordered.tangents.push(self.tangents[i]);
+          }
+          reordered.push_vertex_of(self, i, self.normals[i])
+        });
+        reordered.indices.push(new_vertex);
+      }
+    }
+    *self = reordered;
+  }
+
+  /// The average cache miss ratio: vertex shader runs per triangle with a 32-entry LRU post-transform cache.
+  ///
+  /// It ranges from 3 when no vertex is reused down to about 0.5 for large regular grids.
+  pub fn acmr(&self) -> f32 {
+    if self.indices.is_empty() {
+      return 0.0;
+    }
+
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 1);
+    let mut misses = 0;
+    for &vertex in &self.indices {
+      match cache.iter().position(|&v| v == vertex) {
+        Some(position) => {
+          cache.remove(position);
+        }
+        None => misses += 1,
+      }
+      cache.insert(0, vertex);
+      cache.truncate(CACHE_SIZE);
+    }
+    misses as f32 / self.triangle_count() as f32
+  }
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+  if remaining_triangles == 0 {
+    return -1.0;
+  }
+  let cache_score = match cache_position {
+    // The triangle just drawn gets a fixed score so that it isn't simply continued as a strip.
+    Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+    Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(CACHE_DECAY_POWER),
+    None => 0.0,
+  };
+  cache_score + VALENCE_BOOST_SCALE * (remaining_triangles as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// The order to draw `triangles` in. The next triangle is the best scoring one around the cached vertices, or when
+/// there's none, the first one not drawn yet.
+fn forsyth_order(triangles: &[[u32; 3]], vertex_count: usize) -> Vec<usize> {
+  // The triangles around each vertex that are still to be drawn.
+  let mut remaining: Vec<Vec<usize>> = vec![Vec::new(); vertex_count];
+  for (t, triangle) in triangles.iter().enumerate() {
+    for &vertex in triangle {
+      remaining[vertex as usize].push(t);
+    }
+  }
+
+  let mut vertex_scores: Vec<f32> = remaining.iter().map(|r| vertex_score(None, r.len())).collect();
+  let triangle_score =
+    |vertex_scores: &[f32], t: usize| -> f32 { triangles[t].iter().map(|&v| vertex_scores[v as usize]).sum() };
+
+  let mut drawn = vec![false; triangles.len()];
+  let mut order = Vec::with_capacity(triangles.len());
+  let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+  let mut next_undrawn = 0;
+  let mut best = (0..triangles.len()).max_by(|&a, &b| {
+    triangle_score(&vertex_scores, a)
+      .partial_cmp(&triangle_score(&vertex_scores, b))
+      .unwrap()
+  });
+
+  while order.len() < triangles.len() {
+    let t = match best {
+      Some(t) => t,
+      None => {
+        while drawn[next_undrawn] {
+          next_undrawn += 1;
+        }
+        next_undrawn
+      }
+    };
+    drawn[t] = true;
+    order.push(t);
+
+    let triangle = triangles[t];
+    for &vertex in &triangle {
+      remaining[vertex as usize].retain(|&other| other != t);
+    }
+
+    let mut new_cache = triangle.to_vec();
+    new_cache.extend(cache.iter().copied().filter(|v| !triangle.contains(v)));
+    let evicted = if new_cache.len() > CACHE_SIZE {
+      new_cache.split_off(CACHE_SIZE)
+    } else {
+      vec![]
+    };
+    for &vertex in &evicted {
+      vertex_scores[vertex as usize] = vertex_score(None, remaining[vertex as usize].len());
+    }
+    for (position, &vertex) in new_cache.iter().enumerate() {
+      vertex_scores[vertex as usize] = vertex_score(Some(position), remaining[vertex as usize].len());
+    }
+
+    best = None;
+    let mut best_score = f32::NEG_INFINITY;
+    for &vertex in &new_cache {
+      for &candidate in &remaining[vertex as usize] {
+        let score = triangle_score(&vertex_scores, candidate);
+        if score > best_score {
+          best = Some(candidate);
+          best_score = score;
+        }
+      }
+    }
+    cache = new_cache;
+  }
+  order
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Each triangle as its three positions, rotated to start at the smallest, in a canonical order.
+  fn triangle_set(mesh: &Mesh) -> Vec<[[u32; 3]; 3]> {
+    let mut triangles: Vec<_> = mesh
+      .triangles()
+      .map(|triangle| {
+        let mut corners = triangle.map(|i| mesh.positions[i as usize].map(f32::to_bits));
+        let first = (0..3).min_by_key(|&k| corners[k]).unwrap();
+        corners.rotate_left(first);
+        corners
+      })
+      .collect();
+    triangles.sort_unstable();
+    triangles
+  }
+
+  fn unweld(mesh: &Mesh) -> Mesh {
+    let vertices = mesh.vertices();
+    let flat: Vec<_> = mesh.indices.iter().map(|&i| vertices[i as usize]).collect();
+    Mesh::from_vertices(&flat)
+  }
+
+  #[test]
+  fn colors_keep_vertices_apart_and_follow_them() {
+    let mut flat = unweld(&Mesh::cube(1.0));
+    // One color per triangle, so the two triangles of a face can't share their diagonal anymore.
+    flat.colors = (0..flat.vertex_count())
+      .map(|i| [(i / 3) as f32, 0.0, 0.0, 1.0])
+      .collect();
+    let expected: Vec<_> = flat.indices.iter().map(|&i| flat.colors[i as usize]).collect();
+
+    flat.optimize(1e-6);
+    assert_eq!(flat.vertex_count(), 36);
+    assert_eq!(flat.colors.len(), 36);
+    let mut colors: Vec<_> = flat
+      .indices
+      .iter()
+      .map(|&i| flat.colors[i as usize][0] as u32)
+      .collect();
+    let mut expected: Vec<_> = expected.iter().map(|c| c[0] as u32).collect();
+    colors.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(colors, expected);
+  }
+
+  #[test]
+  fn welding_a_flat_cube_shares_corners_within_faces() {
+    let cube = Mesh::cube(1.0);
+    let mut flat = unweld(&cube);
+    assert_eq!(flat.vertex_count(), 36);
+
+    let stats = flat.optimize(1e-6);
+    assert_eq!((stats.vertices_before, stats.vertices_after), (36, 24));
+    assert_eq!((stats.triangles_before, stats.triangles_after), (12, 12));
+    assert!((stats.vertex_reduction() - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(stats.acmr_before, 3.0);
+    assert_eq!(stats.acmr_after, 2.0);
+    assert_eq!(triangle_set(&flat), triangle_set(&cube));
+  }
+
+  #[test]
+  fn welding_respects_epsilon_and_attributes() {
+    let mut grid = Mesh::grid(1.0, 1.0, 1, 1);
+    let mut flat = unweld(&grid);
+    flat.positions[3][0] += 1e-4;
+    flat.weld(1e-3);
+    assert_eq!(flat.vertex_count(), 4);
+
+    // A different texture coordinate keeps the vertices apart, like on a seam.
+    let mut flat = unweld(&grid);
+    flat.uvs[3] = [0.5, 0.5];
+    flat.weld(1e-3);
+    assert_eq!(flat.vertex_count(), 5);
+
+    // Triangles collapsing within epsilon are dropped along with their vertices.
+    grid.positions[1] = grid.positions[0];
+    grid.normals[1] = grid.normals[0];
+    grid.uvs[1] = grid.uvs[0];
+    grid.weld(1e-3);
+    assert_eq!((grid.vertex_count(), grid.triangle_count()), (3, 1));
+  }
+
+  #[test]
+  fn cache_order_beats_a_shuffled_grid() {
+    let mut grid = Mesh::grid(1.0, 1.0, 40, 40);
+    let mut triangles: Vec<[u32; 3]> = grid.triangles().collect();
+    let mut seed = 12345u32;
+    for i in (1..triangles.len()).rev() {
+      seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+      triangles.swap(i, (seed >> 8) as usize % (i + 1));
+    }
+    grid.indices = triangles.iter().flatten().copied().collect();
+    let before = triangle_set(&grid);
+
+    let stats = grid.optimize(0.0);
+    assert_eq!(stats.vertices_after, stats.vertices_before);
+    assert!(stats.acmr_before > 2.0, "{}", stats);
+    assert!(stats.acmr_after < 0.8, "{}", stats);
+    assert_eq!(triangle_set(&grid), before);
+
+    // Vertices are numbered by first use.
+    let mut next = 0;
+    for &i in &grid.indices {
+      assert!(i <= next);
+      if i == next {
+        next += 1;
+      }
+    }
+  }
+}
@@ -0,0 +1,100 @@
+use super::{cross, dot, sub, try_normalize};
+
+/// Splits a planar polygon, given by its corners in order, into triangles with the same winding.
+///
+/// Concave polygons are handled by ear clipping in the plane of the polygon. Polygons too degenerate for
+/// that, like self-intersecting ones, fall back to a fan from the first corner.
+pub(crate) fn triangulate(points: &[[f32; 3]]) -> Vec<[usize; 3]> {
+  let n = points.len();
+  if n < 3 {
+    return vec![];
+  }
+
+  // Newell's method gives a usable normal even for concave and slightly non-planar polygons.
+  let mut normal = [0.0; 3];
+  for i in 0..n {
+    let (a, b) = (points[i], points[(i + 1) % n]);
+    normal[0] += (a[1] - b[1]) * (a[2] + b[2]);
+    normal[1] += (a[2] - b[2]) * (a[0] + b[0]);
+    normal[2] += (a[0] - b[0]) * (a[1] + b[1]);
+  }
+
+  let mut remaining: Vec<usize> = (0..n).collect();
+  let mut triangles = Vec::with_capacity(n - 2);
+  if let Some(normal) = try_normalize(normal) {
+    while remaining.len() > 3 {
+      let m = remaining.len();
+      let corners = |i: usize| (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+      let is_ear = |i: usize| {
+        let (prev, cur, next) = corners(i);
+        let (a, b, c) = (points[prev], points[cur], points[next]);
+        dot(cross(sub(b, a), sub(c, b)), normal) > 0.0
+          && !remaining
+            .iter()
+            .filter(|&&j| j != prev && j != cur && j != next)
+            .any(|&j| in_triangle(points[j], a, b, c, normal))
+      };
+
+      match (0..m).find(|&i| is_ear(i)) {
+        Some(i) => {
+          let (prev, cur, next) = corners(i);
+          triangles.push([prev, cur, next]);
+          remaining.remove(i);
+        }
+        None => break,
+      }
+    }
+  }
+
+  for k in 1..remaining.len() - 1 {
+    triangles.push([remaining[0], remaining[k], remaining[k + 1]]);
+  }
+  triangles
+}
+
+/// Whether `p` lies inside or on the edges of the counter-clockwise triangle `abc`, seen from `normal`.
+fn in_triangle(p: [f32; 3], a: [f32; 3], b: [f32; 3], c: [f32; 3], normal: [f32; 3]) -> bool {
+  [(a, b), (b, c), (c, a)]
+    .iter()
+    .all(|&(from, to)| dot(cross(sub(to, from), sub(p, from)), normal) >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn area(points: &[[f32; 3]], triangles: &[[usize; 3]]) -> f32 {
+    triangles
+      .iter()
+      .map(|&[a, b, c]| cross(sub(points[b], points[a]), sub(points[c], points[a]))[2] / 2.0)
+      .sum()
+  }
+
+  #[test]
+  fn convex_polygons_become_a_fan_worth_of_triangles() {
+    let quad = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+    let triangles = triangulate(&quad);
+    assert_eq!(triangles.len(), 2);
+    assert!((area(&quad, &triangles) - 1.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn concave_polygons_are_not_filled_outside() {
+    // An L shape whose fan from the first corner would cover the notch.
+    let l_shape = [
+      [2.0, 1.0, 0.0],
+      [1.0, 1.0, 0.0],
+      [1.0, 2.0, 0.0],
+      [0.0, 2.0, 0.0],
+      [0.0, 0.0, 0.0],
+      [2.0, 0.0, 0.0],
+    ];
+    let triangles = triangulate(&l_shape);
+    assert_eq!(triangles.len(), 4);
+    assert!((area(&l_shape, &triangles) - 3.0).abs() < 1e-6);
+    for &[a, b, c] in &triangles {
+      let z = cross(sub(l_shape[b], l_shape[a]), sub(l_shape[c], l_shape[a]))[2];
+      assert!(z > 0.0, "triangle {:?} is flipped", [a, b, c]);
+    }
+  }
+}
@@ -0,0 +1,551 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use super::{normalize, Mesh};
+
+/// One ring of a surface of revolution around the Y axis.
+struct ProfilePoint {
+  radius: f32,
+  y: f32,
+  /// The normal in the (radial, Y) plane.
+  normal: [f32; 2],
+  v: f32,
+}
+
+impl ProfilePoint {
+  fn new(radius: f32, y: f32, normal: [f32; 2], v: f32) -> Self {
+    Self { radius, y, normal, v }
+  }
+}
+
+/// Primitives are centered on the origin with Y up. Round ones split into `segments` around the Y axis.
+impl Mesh {
+  /// An axis-aligned cube with every face mapped to the whole texture.
+  pub fn cube(size: f32) -> Self {
+    // (normal, u axis, v axis) with u × v = normal, so the quads come out counter-clockwise.
+    let faces = [
+      ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+      ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+      ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+      ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+      ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+      ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ];
+
+    let half = size / 2.0;
+    let mut mesh = Self::default();
+    for (normal, u, v) in faces.iter() {
+      let corner = [0, 1, 2].map(|k| (normal[k] - u[k] - v[k]) * half);
+      let u = u.map(|c| c * size);
+      let v = v.map(|c| c * size);
+      mesh.add_patch(corner, u, v, *normal, 1, 1);
+    }
+    mesh
+  }
+
+  /// A single quad in the XZ plane, facing up.
+  pub fn plane(width: f32, depth: f32) -> Self {
+    Self::grid(width, depth, 1, 1)
+  }
+
+  /// A plane in XZ facing up, split into `x_segments` by `z_segments` quads. `v` runs towards -Z.
+  pub fn grid(width: f32, depth: f32, x_segments: u32, z_segments: u32) -> Self {
+    assert!(
+      x_segments >= 1 && z_segments >= 1,
+      "a grid needs at least one segment per side"
+    );
+
+    let mut mesh = Self::default();
+    mesh.add_patch(
+      [-width / 2.0, 0.0, depth / 2.0],
+      [width, 0.0, 0.0],
+      [0.0, 0.0, -depth],
+      [0.0, 1.0, 0.0],
+      x_segments,
+      z_segments,
+    );
+    mesh
+  }
+
+  /// A sphere of `rings` latitude bands, with the texture wrapped around it once.
+  pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+    assert!(
+      segments >= 3 && rings >= 2,
+      "a sphere needs at least 3 segments and 2 rings"
+    );
+
+    let profile: Vec<_> = (0..=rings)
+      .map(|i| {
+        let t = i as f32 / rings as f32;
+        let (sin, cos) = pole_sin_cos(PI * t, i == 0 || i == rings);
+        ProfilePoint::new(radius * sin, radius * cos, [sin, cos], 1.0 - t)
+      })
+      .collect();
+
+    let mut mesh = Self::default();
+    mesh.add_lathe(&profile, segments);
+    mesh
+  }
+
+  /// A subdivided icosahedron, whose triangles are all about the same size.
+  ///
+  /// Vertices on the texture seam and at the poles are duplicated so that no triangle wraps around the texture.
+  pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+    let t = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut points: Vec<[f32; 3]> = [
+      [-1.0, t, 0.0],
+      [1.0, t, 0.0],
+      [-1.0, -t, 0.0],
+      [1.0, -t, 0.0],
+      [0.0, -1.0, t],
+      [0.0, 1.0, t],
+      [0.0, -1.0, -t],
+      [0.0, 1.0, -t],
+      [t, 0.0, -1.0],
+      [t, 0.0, 1.0],
+      [-t, 0.0, -1.0],
+      [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&p| normalize(p))
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+      [0, 11, 5],
+      [0, 5, 1],
+      [0, 1, 7],
+      [0, 7, 10],
+      [0, 10, 11],
+      [1, 5, 9],
+      [5, 11, 4],
+      [11, 10, 2],
+      [10, 7, 6],
+      [7, 1, 8],
+      [3, 9, 4],
+      [3, 4, 2],
+      [3, 2, 6],
+      [3, 6, 8],
+      [3, 8, 9],
+      [4, 9, 5],
+      [2, 4, 11],
+      [6, 2, 10],
+      [8, 6, 7],
+      [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+      let mut midpoints = HashMap::new();
+      let mut midpoint = |a: u32, b: u32| {
+        *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+          let (pa, pb) = (points[a as usize], points[b as usize]);
+          points.push(normalize([pa[0] + pb[0], pa[1] + pb[1], pa[2] + pb[2]]));
+          points.len() as u32 - 1
+        })
+      };
+
+      let mut subdivided = Vec::with_capacity(faces.len() * 4);
+      for &[a, b, c] in &faces {
+        let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+        subdivided.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+      }
+      faces = subdivided;
+    }
+
+    let mut mesh = Self::default();
+    for &p in &points {
+      mesh.push_vertex(p.map(|c| c * radius), p, sphere_uv(p));
+    }
+
+    let mut seam_copies = HashMap::new();
+    for face in faces {
+      let mut triangle = face;
+
+      let us = triangle.map(|i| mesh.uvs[i as usize][0]);
+      if us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min) > 0.5 {
+        for k in 0..3 {
+          if us[k] < 0.5 {
+            let i = triangle[k];
+            triangle[k] = *seam_copies.entry(i).or_insert_with(|| {
+              let [u, v] = mesh.uvs[i as usize];
+              mesh.push_vertex(mesh.positions[i as usize], mesh.normals[i as usize], [u + 1.0, v])
+            });
+          }
+        }
+      }
+
+      // A pole has no meaningful `u`, so each triangle gets its own copy in the middle of the other two.
+      for k in 0..3 {
+        let pole = triangle[k];
+        if mesh.normals[pole as usize][1].abs() > 0.9999 {
+          let u = (us_of(&mesh, triangle[(k + 1) % 3]) + us_of(&mesh, triangle[(k + 2) % 3])) / 2.0;
+          let v = mesh.uvs[pole as usize][1];
+          triangle[k] = mesh.push_vertex(mesh.positions[pole as usize], mesh.normals[pole as usize], [u, v]);
+        }
+      }
+
+      mesh.indices.extend_from_slice(&triangle);
+    }
+    mesh
+  }
+
+  /// A capped cylinder along the Y axis.
+  pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+    assert!(segments >= 3, "a cylinder needs at least 3 segments");
+
+    let half = height / 2.0;
+    let mut mesh = Self::default();
+    mesh.add_lathe(
+      &[
+        ProfilePoint::new(radius, half, [1.0, 0.0], 1.0),
+        ProfilePoint::new(radius, -half, [1.0, 0.0], 0.0),
+      ],
+      segments,
+    );
+    mesh.add_disk(half, radius, segments, true);
+    mesh.add_disk(-half, radius, segments, false);
+    mesh
+  }
+
+  /// A cone with its apex at `height / 2` and a capped base at `-height / 2`.
+  pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+    assert!(segments >= 3, "a cone needs at least 3 segments");
+
+    let half = height / 2.0;
+    let slope = height.hypot(radius);
+    let normal = [height / slope, radius / slope];
+
+    let mut mesh = Self::default();
+    mesh.add_lathe(
+      &[
+        ProfilePoint::new(0.0, half, normal, 1.0),
+        ProfilePoint::new(radius, -half, normal, 0.0),
+      ],
+      segments,
+    );
+    mesh.add_disk(-half, radius, segments, false);
+    mesh
+  }
+
+  /// A torus lying in the XZ plane. `minor_segments` split the tube, `u` runs around Y and `v` around the tube.
+  pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Self {
+    assert!(
+      major_segments >= 3 && minor_segments >= 3,
+      "a torus needs at least 3 segments each way"
+    );
+
+    // Walk the tube from its outer equator downwards, which winds the lathe outwards.
+    let profile: Vec<_> = (0..=minor_segments)
+      .map(|j| {
+        let t = j as f32 / minor_segments as f32;
+        let (sin, cos) = (-2.0 * PI * t).sin_cos();
+        ProfilePoint::new(
+          major_radius + minor_radius * cos,
+          minor_radius * sin,
+          [cos, sin],
+          1.0 - t,
+        )
+      })
+      .collect();
+
+    let mut mesh = Self::default();
+    mesh.add_lathe(&profile, major_segments);
+    mesh
+  }
+
+  /// A cylinder of `height` with hemispheres of `rings` latitude bands on both ends.
+  pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+    assert!(
+      segments >= 3 && rings >= 1,
+      "a capsule needs at least 3 segments and 1 ring"
+    );
+
+    // `v` follows the arc length of the outline, so the texture isn't squashed on the caps.
+    let half = height / 2.0;
+    let length = PI * radius + height;
+    let mut profile = vec![];
+    for i in 0..=rings {
+      let theta = PI / 2.0 * i as f32 / rings as f32;
+      let (sin, cos) = pole_sin_cos(theta, i == 0);
+      profile.push(ProfilePoint::new(
+        radius * sin,
+        half + radius * cos,
+        [sin, cos],
+        1.0 - radius * theta / length,
+      ));
+    }
+    for i in 0..=rings {
+      let theta = PI / 2.0 * (1.0 + i as f32 / rings as f32);
+      let (sin, cos) = pole_sin_cos(theta, i == rings);
+      profile.push(ProfilePoint::new(
+        radius * sin,
+        -half + radius * cos,
+        [sin, cos],
+        1.0 - (radius * theta + height) / length,
+      ));
+    }
+
+    let mut mesh = Self::default();
+    mesh.add_lathe(&profile, segments);
+    mesh
+  }
+
+  /// Adds a grid of quads spanning `corner + s * u + t * v` for `s` and `t` in `[0, 1]`.
+  fn add_patch(
+    &mut self,
+    corner: [f32; 3],
+    u: [f32; 3],
+    v: [f32; 3],
+    normal: [f32; 3],
+    u_segments: u32,
+    v_segments: u32,
+  ) {
+    let first = self.positions.len() as u32;
+    for j in 0..=v_segments {
+      for i in 0..=u_segments {
+        let s = i as f32 / u_segments as f32;
+        let t = j as f32 / v_segments as f32;
+        let position = [0, 1, 2].map(|k| corner[k] + s * u[k] + t * v[k]);
+        self.push_vertex(position, normal, [s, t]);
+      }
+    }
+
+    let columns = u_segments + 1;
+    for j in 0..v_segments {
+      for i in 0..u_segments {
+        let a = first + j * columns + i;
+        let c = a + columns;
+        self.indices.extend_from_slice(&[a, a + 1, c + 1, a, c + 1, c]);
+      }
+    }
+  }
+
+  /// Revolves `profile`, given from top to bottom, around the Y axis.
+  ///
+  /// The first and last column share positions so the texture can wrap. Triangles collapsing onto a point of
+  /// zero radius are left out.
+  fn add_lathe(&mut self, profile: &[ProfilePoint], segments: u32) {
+    let first = self.positions.len() as u32;
+    for p in profile {
+      for j in 0..=segments {
+        let u = j as f32 / segments as f32;
+        let (sin, cos) = (2.0 * PI * u).sin_cos();
+        self.push_vertex(
+          [p.radius * cos, p.y, -p.radius * sin],
+          [p.normal[0] * cos, p.normal[1], -p.normal[0] * sin],
+          [u, p.v],
+        );
+      }
+    }
+
+    let columns = segments + 1;
+    for (i, rows) in profile.windows(2).enumerate() {
+      for j in 0..segments {
+        let a = first + i as u32 * columns + j;
+        let b = a + columns;
+        if rows[0].radius != 0.0 {
+          self.indices.extend_from_slice(&[a, b, a + 1]);
+        }
+        if rows[1].radius != 0.0 {
+          self.indices.extend_from_slice(&[a + 1, b, b + 1]);
+        }
+      }
+    }
+  }
+
+  /// Adds a flat disk at height `y`, facing up or down.
+  fn add_disk(&mut self, y: f32, radius: f32, segments: u32, up: bool) {
+    let facing = if up { 1.0 } else { -1.0 };
+    let normal = [0.0, facing, 0.0];
+    let center = self.push_vertex([0.0, y, 0.0], normal, [0.5, 0.5]);
+    for j in 0..segments {
+      let (sin, cos) = (2.0 * PI * j as f32 / segments as f32).sin_cos();
+      self.push_vertex(
+        [radius * cos, y, -radius * sin],
+        normal,
+        [0.5 + 0.5 * cos, 0.5 + 0.5 * sin * facing],
+      );
+    }
+
+    for j in 0..segments {
+      let k = center + 1 + j;
+      let next = center + 1 + (j + 1) % segments;
+      if up {
+        self.indices.extend_from_slice(&[center, k, next]);
+      } else {
+        self.indices.extend_from_slice(&[center, next, k]);
+      }
+    }
+  }
+}
+
+/// `sin` and `cos` of a polar angle, snapped onto the axis at the poles so the lathe can spot them.
+fn pole_sin_cos(theta: f32, pole: bool) -> (f32, f32) {
+  if pole {
+    (0.0, theta.cos().signum())
+  } else {
+    theta.sin_cos()
+  }
+}
+
+/// The equirectangular texture coordinate of a point on the unit sphere, matching `Mesh::uv_sphere`.
+fn sphere_uv(p: [f32; 3]) -> [f32; 2] {
+  let u = (-p[2]).atan2(p[0]) / (2.0 * PI);
+  let u = if u < 0.0 { u + 1.0 } else { u };
+  [u, 1.0 - p[1].clamp(-1.0, 1.0).acos() / PI]
+}
+
+fn us_of(mesh: &Mesh, i: u32) -> f32 {
+  mesh.uvs[i as usize][0]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::{cross, dot, sub};
+  use super::*;
+
+  fn closed_primitives() -> Vec<(&'static str, Mesh)> {
+    vec![
+      ("cube", Mesh::cube(2.0)),
+      ("uv_sphere", Mesh::uv_sphere(1.5, 16, 8)),
+      ("icosphere", Mesh::icosphere(1.5, 2)),
+      ("cylinder", Mesh::cylinder(0.5, 2.0, 12)),
+      ("cone", Mesh::cone(0.5, 2.0, 12)),
+      ("torus", Mesh::torus(1.0, 0.25, 24, 12)),
+      ("capsule", Mesh::capsule(0.5, 1.0, 12, 4)),
+    ]
+  }
+
+  fn all_primitives() -> Vec<(&'static str, Mesh)> {
+    let mut meshes = closed_primitives();
+    meshes.push(("grid", Mesh::grid(2.0, 3.0, 4, 6)));
+    meshes
+  }
+
+  fn counts(mesh: &Mesh) -> (usize, usize) {
+    (mesh.vertex_count(), mesh.triangle_count())
+  }
+
+  #[test]
+  fn vertex_and_triangle_counts() {
+    assert_eq!(counts(&Mesh::cube(1.0)), (24, 12));
+    assert_eq!(counts(&Mesh::plane(1.0, 1.0)), (4, 2));
+    assert_eq!(counts(&Mesh::grid(1.0, 1.0, 4, 3)), (5 * 4, 2 * 4 * 3));
+    assert_eq!(counts(&Mesh::uv_sphere(1.0, 16, 8)), (17 * 9, 16 * (2 * 8 - 2)));
+    assert_eq!(counts(&Mesh::cylinder(1.0, 1.0, 12)), (4 * 13, 4 * 12));
+    assert_eq!(counts(&Mesh::cone(1.0, 1.0, 12)), (3 * 12 + 3, 2 * 12));
+    assert_eq!(counts(&Mesh::torus(1.0, 0.25, 24, 12)), (25 * 13, 2 * 24 * 12));
+    assert_eq!(counts(&Mesh::capsule(1.0, 1.0, 12, 4)), (2 * 5 * 13, 4 * 12 * 4));
+
+    for subdivisions in 0..4 {
+      let mesh = Mesh::icosphere(1.0, subdivisions);
+      assert_eq!(mesh.triangle_count(), 20 * 4usize.pow(subdivisions));
+      assert!(mesh.vertex_count() >= 10 * 4usize.pow(subdivisions) + 2);
+    }
+  }
+
+  #[test]
+  fn attributes_are_consistent() {
+    for (name, mesh) in all_primitives() {
+      assert_eq!(mesh.normals.len(), mesh.vertex_count(), "{}", name);
+      assert_eq!(mesh.uvs.len(), mesh.vertex_count(), "{}", name);
+      assert_eq!(mesh.indices.len() % 3, 0, "{}", name);
+      assert!(
+        mesh.indices.iter().all(|&i| (i as usize) < mesh.vertex_count()),
+        "{}",
+        name
+      );
+      for n in &mesh.normals {
+        assert!(
+          (dot(*n, *n) - 1.0).abs() < 1e-4,
+          "{} has a normal of length {}",
+          name,
+          dot(*n, *n).sqrt()
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn triangles_are_counter_clockwise_around_their_normals() {
+    for (name, mesh) in all_primitives() {
+      for [a, b, c] in mesh.triangles() {
+        let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+        let face = cross(sub(pb, pa), sub(pc, pa));
+        assert!(dot(face, face) > 1e-12, "{} has a degenerate triangle", name);
+
+        let [na, nb, nc] = [a, b, c].map(|i| mesh.normals[i as usize]);
+        let normal = [na[0] + nb[0] + nc[0], na[1] + nb[1] + nc[1], na[2] + nb[2] + nc[2]];
+        assert!(
+          dot(face, normal) > 0.0,
+          "{} triangle {:?} is wound clockwise",
+          name,
+          [a, b, c]
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn closed_primitives_are_watertight() {
+    // Every edge, compared by position to see through duplicated seam vertices, is shared by exactly two
+    // triangles running it in opposite directions.
+    for (name, mesh) in closed_primitives() {
+      let key = |i: u32| mesh.positions[i as usize].map(|c| (c * 1e4).round() as i64);
+      let mut edges = HashMap::new();
+      for [a, b, c] in mesh.triangles() {
+        for &(from, to) in &[(a, b), (b, c), (c, a)] {
+          *edges.entry((key(from), key(to))).or_insert(0) += 1;
+        }
+      }
+      for (&(from, to), &count) in &edges {
+        assert_eq!(count, 1, "{} runs an edge the same way twice", name);
+        assert_eq!(edges.get(&(to, from)), Some(&1), "{} has an open edge", name);
+      }
+    }
+  }
+
+  #[test]
+  fn normals_point_outwards() {
+    for (name, mesh) in closed_primitives().into_iter().filter(|(name, _)| *name != "torus") {
+      for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+        assert!(dot(*p, *n) > 0.0, "{} normal {:?} at {:?} points inwards", name, n, p);
+      }
+    }
+
+    let torus = Mesh::torus(1.0, 0.25, 24, 12);
+    for (p, n) in torus.positions.iter().zip(&torus.normals) {
+      let ring = normalize([p[0], 0.0, p[2]]);
+      let from_ring = sub(*p, ring);
+      assert!(
+        (dot(from_ring, *n) - 0.25).abs() < 1e-4,
+        "torus normal {:?} at {:?}",
+        n,
+        p
+      );
+    }
+
+    for n in &Mesh::grid(1.0, 1.0, 2, 2).normals {
+      assert_eq!(*n, [0.0, 1.0, 0.0]);
+    }
+  }
+
+  #[test]
+  fn sphere_normals_are_radial() {
+    for mesh in &[Mesh::uv_sphere(2.0, 16, 8), Mesh::icosphere(2.0, 2)] {
+      for (p, n) in mesh.positions.iter().zip(&mesh.normals) {
+        assert!((dot(*p, *p).sqrt() - 2.0).abs() < 1e-4);
+        assert!(dot(sub(p.map(|c| c / 2.0), *n), sub(p.map(|c| c / 2.0), *n)) < 1e-8);
+      }
+    }
+  }
+
+  #[test]
+  fn icosphere_triangles_do_not_wrap_around_the_texture() {
+    let mesh = Mesh::icosphere(1.0, 3);
+    for triangle in mesh.triangles() {
+      let us = triangle.map(|i| mesh.uvs[i as usize][0]);
+      let spread = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min);
+      assert!(spread < 0.5, "triangle {:?} spans u {:?}", triangle, us);
+    }
+  }
+}
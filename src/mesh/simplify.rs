@@ -0,0 +1,355 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::mem;
+
+use super::{cross, dot, position_groups, sub, try_normalize, Mesh};
+
+/// How much more moving a vertex away from an open border costs than moving it away from a face.
+const BORDER_WEIGHT: f64 = 10.0;
+
+impl Mesh {
+  /// Simplifies the mesh down to `target_triangles` triangles or fewer by collapsing edges, cheapest first, using
+  /// Garland and Heckbert's quadric error metric: the sum of the squared distances from a vertex to the planes of
+  /// the original triangles around it. Returns the square root of the largest error reached, which is roughly how
+  /// far the surface moved, in model units.
+  ///
+  /// Collapses costing more than `max_error` squared are never made, so the mesh may keep more triangles than asked
+  /// for; pass `f32::INFINITY` to always reach the target. Each collapse moves a vertex onto a neighbour, so every
+  /// vertex keeps its attributes. Open borders resist moving, vertices split along a normal or texture seam only
+  /// collapse along it, and collapses that would flip a triangle over are skipped. Triangles already collapsed to a
+  /// line are dropped, and unused vertices removed.
+  pub fn simplify(&mut self, target_triangles: usize, max_error: f32) -> f32 {
+    if self.triangle_count() <= target_triangles {
+      return 0.0;
+    }
+
+    // Vertices sharing a position move together, so collapses happen between groups of them, numbered by their
+    // lowest vertex. Each group's position is that vertex's.
+    let groups = position_groups(&self.positions, 0.0);
+    let group_of = |v: u32| groups[v as usize] as usize;
+    let position = |group: usize| self.positions[group];
+
+    let mut triangles: Vec<[u32; 3]> = self
+      .triangles()
+      .filter(|t| {
+        let [a, b, c] = t.map(group_of);
+        a != b && b != c && c != a
+      })
+      .collect();
+
+    let count = self.vertex_count();
+
+    let mut group_triangles: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut quadrics = vec![Quadric::default(); count];
+    let mut edge_triangles: HashMap<(usize, usize), usize> = HashMap::new();
+    for (t, triangle) in triangles.iter().enumerate() {
+      let corners = triangle.map(group_of);
+      let [pa, pb, pc] = corners.map(position);
+      let plane = Quadric::plane(cross(sub(pb, pa), sub(pc, pa)), pa, 1.0);
+      for (k, &group) in corners.iter().enumerate() {
+        group_triangles[group].push(t);
+        quadrics[group].add(&plane);
+        *edge_triangles.entry(edge(group, corners[(k + 1) % 3])).or_default() += 1;
+      }
+    }
+
+    // Borders get a plane through them, upright on the triangle, that keeps their vertices from sliding inwards.
+    for triangle in &triangles {
+      let corners = triangle.map(group_of);
+      let [pa, pb, pc] = corners.map(position);
+      let normal = cross(sub(pb, pa), sub(pc, pa));
+      for k in 0..3 {
+        let (a, b) = (corners[k], corners[(k + 1) % 3]);
+        if edge_triangles[&edge(a, b)] == 1 {
+          let plane = Quadric::plane(cross(sub(position(b), position(a)), normal), position(a), BORDER_WEIGHT);
+          quadrics[a].add(&plane);
+          quadrics[b].add(&plane);
+        }
+      }
+    }
+
+    let mut versions = vec![0; count];
+    let best_collapse = |a: usize, b: usize, quadrics: &[Quadric], versions: &[u32]| {
+      let mut quadric = quadrics[a];
+      quadric.add(&quadrics[b]);
+      let (a_to_b, b_to_a) = (quadric.error(position(b)), quadric.error(position(a)));
+      let (from, to, cost) = if a_to_b <= b_to_a {
+        (a, b, a_to_b)
+      } else {
+        (b, a, b_to_a)
+      };
+      Collapse {
+        cost: cost.max(0.0),
+        from,
+        to,
+        versions: (versions[from], versions[to]),
+      }
+    };
+    let mut edges: Vec<(usize, usize)> = edge_triangles.keys().copied().collect();
+    edges.sort_unstable();
+    let mut heap: BinaryHeap<Collapse> = edges
+      .into_iter()
+      .map(|(a, b)| best_collapse(a, b, &quadrics, &versions))
+      .collect();
+
+    let mut live_triangles = vec![true; triangles.len()];
+    let mut live_count = triangles.len();
+    let mut live_groups = vec![true; count];
+    let limit = (max_error as f64).powi(2);
+    let mut error: f64 = 0.0;
+    while live_count > target_triangles {
+      let collapse = match heap.pop() {
+        Some(collapse) => collapse,
+        None => break,
+      };
+      let (from, to) = (collapse.from, collapse.to);
+      if !live_groups[from] || !live_groups[to] || collapse.versions != (versions[from], versions[to]) {
+        continue;
+      }
+      if collapse.cost > limit {
+        break;
+      }
+
+      // Every vertex of `from` still in use moves onto the vertex of `to` it shares a triangle with, which keeps
+      // seams apart. Collapsing across a seam would leave a vertex without one.
+      let around: Vec<usize> = group_triangles[from]
+        .iter()
+        .copied()
+        .filter(|&t| live_triangles[t])
+        .collect();
+      let mut remap = HashMap::new();
+      let mut used = Vec::new();
+      for &t in &around {
+        for &v in &triangles[t] {
+          if group_of(v) == from {
+            used.push(v);
+            if let Some(&w) = triangles[t].iter().find(|&&w| group_of(w) == to) {
+              remap.insert(v, w);
+            }
+          }
+        }
+      }
+      if used.iter().any(|v| !remap.contains_key(v)) {
+        continue;
+      }
+
+      let flips = around.iter().any(|&t| {
+        let corners = triangles[t].map(group_of);
+        if corners.contains(&to) {
+          return false;
+        }
+        let [pa, pb, pc] = corners.map(position);
+        let moved = corners.map(|g| if g == from { position(to) } else { position(g) });
+        let before = cross(sub(pb, pa), sub(pc, pa));
+        let after = cross(sub(moved[1], moved[0]), sub(moved[2], moved[0]));
+        dot(before, after) <= 0.0
+      });
+      if flips {
+        continue;
+      }
+
+      for t in mem::take(&mut group_triangles[from]) {
+        if !live_triangles[t] {
+          continue;
+        }
+        let triangle = &mut triangles[t];
+        let collapsed = triangle.iter().any(|&v| group_of(v) == to);
+        if collapsed {
+          live_triangles[t] = false;
+          live_count -= 1;
+        } else {
+          for v in triangle.iter_mut() {
+            if let Some(&w) = remap.get(v) {
+              *v = w;
+            }
+          }
+          group_triangles[to].push(t);
+        }
+      }
+      group_triangles[to].retain(|&t| live_triangles[t]);
+
+      let merged = quadrics[from];
+      quadrics[to].add(&merged);
+      live_groups[from] = false;
+      versions[to] += 1;
+      error = error.max(collapse.cost);
+
+      // The costs around `to` changed, and so did the triangles around its neighbours, which may unblock collapses
+      // skipped earlier for flipping one.
+      for neighbour in neighbours(to, &triangles, &groups, &group_triangles, &live_triangles) {
+        for other in neighbours(neighbour, &triangles, &groups, &group_triangles, &live_triangles) {
+          if other != to {
+            heap.push(best_collapse(neighbour, other, &quadrics, &versions));
+          }
+        }
+        heap.push(best_collapse(to, neighbour, &quadrics, &versions));
+      }
+    }
+
+    let mut simplified = Self::default();
+    let has_tangents = !self.tangents.is_empty();
+    let mut new_index = vec![None; count];
+    for (triangle, _) in triangles.iter().zip(&live_triangles).filter(|(_, &live)| live) {
+      for &vertex in triangle {
+        let i = vertex as usize;
+        let index = *new_index[i].get_or_insert_with(|| {
+          if has_tangents {
+            simplified.tangents.push(self.tangents[i]);
+          }
+          simplified.push_vertex_of(self, i, self.normals[i])
+        });
+        simplified.indices.push(index);
+      }
+    }
+    *self = simplified;
+    error.sqrt() as f32
+  }
+}
+
+/// The groups sharing a live triangle with `group`, in order.
+fn neighbours(
+  group: usize,
+  triangles: &[[u32; 3]],
+  groups: &[u32],
+  group_triangles: &[Vec<usize>],
+  live_triangles: &[bool],
+) -> Vec<usize> {
+  let mut neighbours: Vec<usize> = group_triangles[group]
+    .iter()
+    .filter(|&&t| live_triangles[t])
+    .flat_map(|&t| triangles[t].iter().map(|&v| groups[v as usize] as usize))
+    .filter(|&g| g != group)
+    .collect();
+  neighbours.sort_unstable();
+  neighbours.dedup();
+  neighbours
+}
+
+fn edge(a: usize, b: usize) -> (usize, usize) {
+  (a.min(b), a.max(b))
+}
+
+/// The symmetric matrix of a sum of squared distances to planes, with the upper triangle stored row by row.
+#[derive(Clone, Copy, Debug, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+  /// The squared distance to the plane through `point` facing `normal`, times `weight`. Zero for a zero normal.
+  fn plane(normal: [f32; 3], point: [f32; 3], weight: f64) -> Self {
+    let [a, b, c] = match try_normalize(normal) {
+      Some(n) => n.map(f64::from),
+      None => return Self::default(),
+    };
+    let d = -(a * point[0] as f64 + b * point[1] as f64 + c * point[2] as f64);
+    Self([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|v| v * weight))
+  }
+
+  fn add(&mut self, other: &Self) {
+    for (a, b) in self.0.iter_mut().zip(&other.0) {
+      *a += b;
+    }
+  }
+
+  fn error(&self, p: [f32; 3]) -> f64 {
+    let [x, y, z] = p.map(f64::from);
+    let q = &self.0;
+    q[0] * x * x
+      + q[4] * y * y
+      + q[7] * z * z
+      + q[9]
+      + 2.0 * (q[1] * x * y + q[2] * x * z + q[3] * x + q[5] * y * z + q[6] * y + q[8] * z)
+  }
+}
+
+/// Moving every vertex of group `from` onto group `to`, as it was when both had the given versions.
+#[derive(Debug)]
+struct Collapse {
+  cost: f64,
+  from: usize,
+  to: usize,
+  versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Collapse {
+  /// Reversed, so that `BinaryHeap` pops the cheapest collapse first, and the one of the lowest vertices among equal
+  /// ones so that the result doesn't depend on the order collapses were found in.
+  fn cmp(&self, other: &Self) -> Ordering {
+    other
+      .cost
+      .total_cmp(&self.cost)
+      .then((other.from, other.to).cmp(&(self.from, self.to)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn flat_grids_simplify_without_error() {
+    let mut grid = Mesh::grid(4.0, 2.0, 16, 8);
+    let bounds = grid.bounds();
+    let error = grid.simplify(2, f32::INFINITY);
+    assert_eq!(grid.triangle_count(), 2);
+    assert_eq!(grid.vertex_count(), 4);
+    assert!(error < 1e-4, "{}", error);
+    // The corners stay where they were.
+    assert_eq!(grid.bounds(), bounds);
+    assert!(grid.normals.iter().all(|&n| n == [0.0, 1.0, 0.0]));
+  }
+
+  #[test]
+  fn spheres_stay_round() {
+    let sphere = Mesh::uv_sphere(1.0, 48, 24);
+    let mut simplified = sphere.clone();
+    let error = simplified.simplify(sphere.triangle_count() / 8, f32::INFINITY);
+
+    assert!(simplified.triangle_count() <= sphere.triangle_count() / 8);
+    assert!(simplified.triangle_count() > sphere.triangle_count() / 16);
+    assert!(error > 0.0 && error < 0.2, "{}", error);
+    // Vertices only move onto others, which are all on the sphere.
+    assert!(simplified
+      .positions
+      .iter()
+      .all(|&p| (dot(p, p).sqrt() - 1.0).abs() < 1e-5));
+    // Every triangle still faces outwards.
+    for [a, b, c] in simplified.triangles() {
+      let [pa, pb, pc] = [a, b, c].map(|i| simplified.positions[i as usize]);
+      assert!(dot(cross(sub(pb, pa), sub(pc, pa)), pa) > 0.0);
+    }
+  }
+
+  #[test]
+  fn max_error_stops_early_and_attributes_follow() {
+    let mut cube = Mesh::cube(2.0);
+    cube.colors = (0..cube.vertex_count()).map(|i| [i as f32, 0.0, 0.0, 1.0]).collect();
+    let expected = cube.clone();
+
+    // Every edge collapse would bend a face, so nothing happens within a small error.
+    assert_eq!(cube.simplify(4, 0.01), 0.0);
+    assert_eq!(cube.triangle_count(), expected.triangle_count());
+    assert_eq!(cube.vertex_count(), expected.vertex_count());
+
+    let mut sphere = Mesh::uv_sphere(1.0, 32, 16);
+    sphere.colors = sphere.positions.iter().map(|p| [p[0], p[1], p[2], 1.0]).collect();
+    sphere.simplify(100, f32::INFINITY);
+    assert_eq!(sphere.colors.len(), sphere.vertex_count());
+    for (p, c) in sphere.positions.iter().zip(&sphere.colors) {
+      assert_eq!([p[0], p[1], p[2], 1.0], *c);
+    }
+  }
+}
@@ -0,0 +1,77 @@
+use super::{add, normalize, scale, Mesh};
+use nalgebra::{Matrix4, Vector4};
+
+impl Mesh {
+  /// Deforms the mesh on the CPU the way the `SKINNED` variant of `shaders/lit.vert` does on the GPU, given the matrix
+  /// of each joint from `Skin::joint_matrices`. Useful for picking, physics or checking the shader.
+  ///
+  /// Every position, normal and tangent becomes the weighted sum of what each of its joints makes of it, and normals
+  /// and tangents are normalized again. Meshes without a skin are returned unchanged.
+  pub fn skinned(&self, joint_matrices: &[[[f32; 4]; 4]]) -> Mesh {
+    let mut skinned = self.clone();
+    if self.joints.is_empty() {
+      return skinned;
+    }
+
+    let blend = |i: usize, v: [f32; 3], w: f32| {
+      let mut sum = [0.0; 3];
+      for (&joint, &weight) in self.joints[i].iter().zip(&self.weights[i]) {
+        if weight != 0.0 {
+          let moved = Matrix4::from(joint_matrices[joint as usize]) * Vector4::new(v[0], v[1], v[2], w);
+          sum = add(sum, scale(moved.xyz().into(), weight));
+        }
+      }
+      sum
+    };
+
+    for i in 0..self.vertex_count() {
+      skinned.positions[i] = blend(i, self.positions[i], 1.0);
+      skinned.normals[i] = normalize(blend(i, self.normals[i], 0.0));
+      if let Some(&[x, y, z, w]) = self.tangents.get(i) {
+        let [x, y, z] = normalize(blend(i, [x, y, z], 0.0));
+        skinned.tangents[i] = [x, y, z, w];
+      }
+    }
+    skinned
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::animation::Transform;
+
+  #[test]
+  fn vertices_follow_their_weighted_joints() {
+    let mut mesh = Mesh::default();
+    mesh.push_vertex([1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0; 2]);
+    mesh.push_vertex([0.0, 2.0, 0.0], [1.0, 0.0, 0.0], [0.0; 2]);
+    mesh.joints = vec![[0, 0, 0, 0], [0, 1, 0, 0]];
+    mesh.weights = vec![[1.0, 0.0, 0.0, 0.0], [0.5, 0.5, 0.0, 0.0]];
+
+    let moved = Transform {
+      translation: [0.0, 0.0, 4.0],
+      ..Transform::IDENTITY
+    };
+    let turned = Transform {
+      rotation: [
+        0.0,
+        0.0,
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+      ],
+      ..Transform::IDENTITY
+    };
+    let skinned = mesh.skinned(&[moved.matrix(), turned.matrix()]);
+
+    assert_eq!(skinned.positions[0], [1.0, 0.0, 4.0]);
+    assert_eq!(skinned.normals[0], [0.0, 1.0, 0.0]);
+    // Halfway between moving to (0, 2, 4) and turning a quarter around z to (-2, 0, 0).
+    let [x, y, z] = skinned.positions[1];
+    assert!((x + 1.0).abs() < 1e-6 && (y - 1.0).abs() < 1e-6 && (z - 2.0).abs() < 1e-6);
+    let [nx, ny, _] = skinned.normals[1];
+    assert!((nx - ny).abs() < 1e-6 && (nx * nx + ny * ny - 1.0).abs() < 1e-6);
+
+    assert_eq!(Mesh::cube(1.0).skinned(&[moved.matrix()]), Mesh::cube(1.0));
+  }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+
+use super::{add, corner_angle, cross, dot, scale, sub, try_normalize, Mesh};
+
+impl Mesh {
+  /// Fills `tangents` from the normals and texture coordinates, following the MikkTSpace conventions used by
+  /// glTF and most bakers.
+  ///
+  /// Each triangle's tangent is the direction of increasing `u`, made orthogonal to the vertex normal and
+  /// weighted by the triangle's angle at the vertex. `w` is -1 where the texture is mirrored. A vertex shared
+  /// by mirrored and unmirrored triangles is split in two. This matches MikkTSpace on meshes whose vertices
+  /// are already split along seams and hard edges, which is what the other `Mesh` functions produce, but
+  /// doesn't reproduce its own grouping of corners bit for bit.
+  pub fn compute_tangents(&mut self) {
+    assert_eq!(self.normals.len(), self.vertex_count(), "tangents need normals");
+    assert_eq!(self.uvs.len(), self.vertex_count(), "tangents need texture coordinates");
+
+    // Sums of weighted tangents per (vertex, handedness), kept in vertex order.
+    let mut sums: BTreeMap<(u32, bool), [f32; 3]> = BTreeMap::new();
+    let mut corner_keys = Vec::with_capacity(self.indices.len());
+    for triangle in self.triangles() {
+      let [pa, pb, pc] = triangle.map(|i| self.positions[i as usize]);
+      let [ta, tb, tc] = triangle.map(|i| self.uvs[i as usize]);
+      let (e1, e2) = (sub(pb, pa), sub(pc, pa));
+      let (du1, dv1, du2, dv2) = (tb[0] - ta[0], tb[1] - ta[1], tc[0] - ta[0], tc[1] - ta[1]);
+
+      // Directions of increasing u and v on the triangle. Triangles with collapsed texture coordinates have
+      // neither and only take part through their handedness.
+      let det = du1 * dv2 - du2 * dv1;
+      let (u_dir, v_dir) = if det.abs() > 1e-12 {
+        (
+          scale(sub(scale(e1, dv2), scale(e2, dv1)), 1.0 / det),
+          scale(sub(scale(e2, du1), scale(e1, du2)), 1.0 / det),
+        )
+      } else {
+        ([0.0; 3], [0.0; 3])
+      };
+
+      let weights = [
+        corner_angle(pa, pb, pc),
+        corner_angle(pb, pc, pa),
+        corner_angle(pc, pa, pb),
+      ];
+      for (k, &vertex) in triangle.iter().enumerate() {
+        let normal = self.normals[vertex as usize];
+        let right_handed = dot(cross(normal, u_dir), v_dir) >= 0.0;
+        let tangent = try_normalize(sub(u_dir, scale(normal, dot(normal, u_dir)))).unwrap_or([0.0; 3]);
+
+        let sum = sums.entry((vertex, right_handed)).or_insert([0.0; 3]);
+        *sum = add(*sum, scale(tangent, weights[k]));
+        corner_keys.push((vertex, right_handed));
+      }
+    }
+
+    let mut new_vertices = BTreeMap::new();
+    let mut mesh = Self::default();
+    for vertex in 0..self.vertex_count() as u32 {
+      let mut handednesses: Vec<bool> = [true, false]
+        .iter()
+        .copied()
+        .filter(|&right_handed| sums.contains_key(&(vertex, right_handed)))
+        .collect();
+      if handednesses.is_empty() {
+        handednesses.push(true);
+      }
+
+      let normal = self.normals[vertex as usize];
+      for right_handed in handednesses {
+        let sum = sums.get(&(vertex, right_handed)).copied().unwrap_or([0.0; 3]);
+        let tangent =
+          try_normalize(sub(sum, scale(normal, dot(normal, sum)))).unwrap_or_else(|| any_orthogonal(normal));
+        let w = if right_handed { 1.0 } else { -1.0 };
+
+        let new_vertex = mesh.push_vertex_of(self, vertex as usize, normal);
+        mesh.tangents.push([tangent[0], tangent[1], tangent[2], w]);
+        new_vertices.insert((vertex, right_handed), new_vertex);
+      }
+    }
+
+    mesh.indices = corner_keys.iter().map(|key| new_vertices[key]).collect();
+    *self = mesh;
+  }
+}
+
+/// Some unit vector orthogonal to `normal`, for vertices whose triangles give no tangent direction.
+fn any_orthogonal(normal: [f32; 3]) -> [f32; 3] {
+  let axis = if normal[0].abs() < 0.9 {
+    [1.0, 0.0, 0.0]
+  } else {
+    [0.0, 1.0, 0.0]
+  };
+  try_normalize(cross(cross(normal, axis), normal)).unwrap_or(axis)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assert_close(a: [f32; 4], b: [f32; 4]) {
+    let d: f32 = (0..4).map(|k| (a[k] - b[k]).powi(2)).sum();
+    assert!(d < 1e-8, "{:?} != {:?}", a, b);
+  }
+
+  #[test]
+  fn grid_tangents_follow_u() {
+    let mut grid = Mesh::grid(2.0, 2.0, 3, 3);
+    grid.compute_tangents();
+
+    assert_eq!(grid.tangents.len(), grid.vertex_count());
+    for t in &grid.tangents {
+      assert_close(*t, [1.0, 0.0, 0.0, 1.0]);
+    }
+  }
+
+  #[test]
+  fn mirrored_texture_flips_handedness() {
+    let mut grid = Mesh::grid(2.0, 2.0, 1, 1);
+    for uv in &mut grid.uvs {
+      uv[0] = 1.0 - uv[0];
+    }
+    grid.compute_tangents();
+
+    for t in &grid.tangents {
+      assert_close(*t, [-1.0, 0.0, 0.0, -1.0]);
+    }
+  }
+
+  #[test]
+  fn vertices_on_a_mirror_seam_are_split() {
+    // Two quads side by side sharing the middle column, with the texture mirrored on the right one.
+    let mut mesh = Mesh::grid(2.0, 1.0, 2, 1);
+    for (p, uv) in mesh.positions.iter().zip(&mut mesh.uvs) {
+      uv[0] = 1.0 - p[0].abs();
+    }
+    let before = mesh.vertex_count();
+    mesh.compute_tangents();
+
+    assert_eq!(mesh.vertex_count(), before + 2);
+    for [a, b, c] in mesh.triangles() {
+      let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+      let left = pa[0] + pb[0] + pc[0] < 0.0;
+      for &i in &[a, b, c] {
+        let expected = if left {
+          [1.0, 0.0, 0.0, 1.0]
+        } else {
+          [-1.0, 0.0, 0.0, -1.0]
+        };
+        assert_close(mesh.tangents[i as usize], expected);
+      }
+    }
+  }
+
+  #[test]
+  fn primitive_tangents_are_orthonormal_and_follow_u() {
+    let primitives = [
+      Mesh::cube(1.0),
+      Mesh::uv_sphere(1.0, 16, 8),
+      Mesh::icosphere(1.0, 2),
+      Mesh::cylinder(0.5, 2.0, 12),
+      Mesh::cone(0.5, 2.0, 12),
+      Mesh::torus(1.0, 0.25, 24, 12),
+      Mesh::capsule(0.5, 1.0, 12, 4),
+    ];
+    for mesh in primitives.iter() {
+      let mut mesh = mesh.clone();
+      let before = mesh.vertex_count();
+      mesh.compute_tangents();
+
+      assert_eq!(
+        mesh.vertex_count(),
+        before,
+        "primitives have no mirrored texture coordinates"
+      );
+      for (t, n) in mesh.tangents.iter().zip(&mesh.normals) {
+        let tangent = [t[0], t[1], t[2]];
+        assert!((dot(tangent, tangent) - 1.0).abs() < 1e-4);
+        assert!(dot(tangent, *n).abs() < 1e-4);
+        assert_eq!(t[3], 1.0);
+      }
+
+      for [a, b, c] in mesh.triangles() {
+        let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+        let [ta, tb, tc] = [a, b, c].map(|i| mesh.uvs[i as usize]);
+        let (e1, e2) = (sub(pb, pa), sub(pc, pa));
+        let (du1, dv1, du2, dv2) = (tb[0] - ta[0], tb[1] - ta[1], tc[0] - ta[0], tc[1] - ta[1]);
+        let u_dir = sub(scale(e1, dv2), scale(e2, dv1));
+        let u_dir = scale(u_dir, (du1 * dv2 - du2 * dv1).signum());
+        for &i in &[a, b, c] {
+          let t = mesh.tangents[i as usize];
+          assert!(
+            dot([t[0], t[1], t[2]], u_dir) > 0.0,
+            "tangent {:?} against u {:?}",
+            t,
+            u_dir
+          );
+        }
+      }
+    }
+  }
+}
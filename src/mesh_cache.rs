@@ -0,0 +1,497 @@
+use memmap2::Mmap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::slice;
+
+use crate::bounds::{Aabb, BoundingSphere, Bounds};
+use crate::import::{load_ply, load_stl, GltfScene, ObjModel};
+use crate::mesh::{GpuMesh, Mesh, MeshVertex, MorphDelta, MorphTarget, SkinVertex, VertexColor};
+
+/// The first bytes of every mesh cache file.
+pub const MAGIC: [u8; 8] = *b"LOGLMESH";
+/// Bumped whenever the layout changes, older caches then have to be converted again.
+pub const VERSION: u32 = 4;
+
+/// Magic, version, mesh count and the CRC-32 of the mesh count and everything after the header.
+const HEADER_SIZE: usize = 20;
+/// Vertex and index counts, flags, data offset, bounds and morph target count.
+const ENTRY_SIZE: usize = 64;
+
+const HAS_TANGENTS: u32 = 1;
+const HAS_COLORS: u32 = 2;
+const HAS_BOUNDS: u32 = 4;
+const HAS_SKIN: u32 = 8;
+
+#[derive(Debug)]
+pub enum MeshCacheError {
+  Io {
+    path: PathBuf,
+    error: io::Error,
+  },
+  /// The file doesn't start with `MAGIC`.
+  NotACache,
+  /// The file was written with another version of the format.
+  Version(u32),
+  /// The contents don't match the stored checksum, e.g. after an interrupted write.
+  Checksum {
+    stored: u32,
+    computed: u32,
+  },
+  /// The checksum matches but the mesh table points outside of the file.
+  Corrupt(String),
+  /// The source of `convert` couldn't be loaded.
+  Import(Box<dyn Error>),
+}
+
+impl fmt::Display for MeshCacheError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      MeshCacheError::Io { path, error } => write!(f, "failed to access {}: {}", path.display(), error),
+      MeshCacheError::NotACache => write!(f, "not a mesh cache file"),
+      MeshCacheError::Version(version) => write!(
+        f,
+        "mesh cache version {} isn't supported, expected {}",
+        version, VERSION
+      ),
+      MeshCacheError::Checksum { stored, computed } => write!(
+        f,
+        "mesh cache checksum mismatch: stored {:#010x}, computed {:#010x}",
+        stored, computed
+      ),
+      MeshCacheError::Corrupt(message) => write!(f, "corrupt mesh cache: {}", message),
+      MeshCacheError::Import(error) => write!(f, "failed to import the source mesh: {}", error),
+    }
+  }
+}
+
+impl Error for MeshCacheError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      MeshCacheError::Io { error, .. } => Some(error),
+      MeshCacheError::Import(error) => Some(error.as_ref()),
+      _ => None,
+    }
+  }
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> MeshCacheError + '_ {
+  move |error| MeshCacheError::Io {
+    path: path.to_path_buf(),
+    error,
+  }
+}
+
+/// Where a mesh lives in a cache file.
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+  vertex_count: usize,
+  index_count: usize,
+  flags: u32,
+  offset: usize,
+  morph_target_count: usize,
+  bounds: Option<Bounds>,
+}
+
+/// A memory-mapped file of meshes stored the way `GpuMesh` keeps them: interleaved `MeshVertex`es, then tangents,
+/// `VertexColor`s and `SkinVertex`es if the mesh has any, `MorphDelta`s for each morph target, then `u32` indices,
+/// together with precomputed bounds.
+///
+/// All numbers are little endian and every array is 4-byte aligned, so the mapping is used in place. Only geometry
+/// is cached: materials and node hierarchies still come from the source file.
+pub struct MeshCache {
+  mmap: Mmap,
+  entries: Vec<Entry>,
+}
+
+/// A mesh borrowed from a `MeshCache`.
+#[derive(Clone, Copy, Debug)]
+pub struct CachedMesh<'a> {
+  pub vertices: &'a [MeshVertex],
+  /// Empty if the mesh had no tangents.
+  pub tangents: &'a [[f32; 4]],
+  /// Empty if the mesh had no colors.
+  pub colors: &'a [VertexColor],
+  /// Empty if the mesh had no skin.
+  pub skin: &'a [SkinVertex],
+  /// The offsets of every vertex for each morph target in turn, empty if the mesh had none.
+  pub morph_deltas: &'a [MorphDelta],
+  pub morph_target_count: usize,
+  pub indices: &'a [u32],
+  /// `None` for an empty mesh.
+  pub bounds: Option<Bounds>,
+}
+
+impl MeshCache {
+  /// Maps the cache at `path` and checks its version and checksum, which reads through the whole file once.
+  pub fn open(path: impl AsRef<Path>) -> Result<Self, MeshCacheError> {
+    let path = path.as_ref();
+    if cfg!(target_endian = "big") {
+      return Err(MeshCacheError::Corrupt(
+        "mesh caches are little endian and can't be mapped on this machine".to_string(),
+      ));
+    }
+
+    let file = File::open(path).map_err(io_error(path))?;
+    // The mapping stays valid only as long as nobody truncates the file. `write` never does, it replaces the file
+    // instead, so the mapping keeps the old contents.
+    let mmap = unsafe { Mmap::map(&file) }.map_err(io_error(path))?;
+    let entries = parse_header(&mmap)?;
+    Ok(Self { mmap, entries })
+  }
+
+  /// Converts `source` into a cache at `cache` with `convert`, unless `cache` is already newer than `source`, then
+  /// opens it. A cache of another version or with a bad checksum is converted again.
+  pub fn open_or_convert(source: impl AsRef<Path>, cache: impl AsRef<Path>) -> Result<Self, MeshCacheError> {
+    let (source, cache) = (source.as_ref(), cache.as_ref());
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let source_modified = modified(source);
+    let fresh = match (modified(cache), source_modified) {
+      (Some(cache), Some(source)) => cache >= source,
+      _ => false,
+    };
+
+    if fresh {
+      match Self::open(cache) {
+        Ok(cache) => return Ok(cache),
+        Err(MeshCacheError::Version(_)) | Err(MeshCacheError::Checksum { .. }) | Err(MeshCacheError::NotACache) => {}
+        Err(error) => return Err(error),
+      }
+    }
+    Self::convert(source, cache)?;
+    Self::open(cache)
+  }
+
+  /// Loads the meshes of an OBJ, glTF, PLY or STL file, picked by extension, and writes them to a cache at
+  /// `cache`. The meshes of OBJ files come in material order and the primitives of glTF meshes in mesh order.
+  pub fn convert(source: impl AsRef<Path>, cache: impl AsRef<Path>) -> Result<(), MeshCacheError> {
+    let meshes = load_source(source.as_ref()).map_err(MeshCacheError::Import)?;
+    Self::write(cache, &meshes)
+  }
+
+  /// Writes `meshes` to a new cache file at `path`. The file is written next to `path` first and then renamed over
+  /// it, so caches that are already open and readers racing the write never see a partial file.
+  pub fn write<'a>(path: impl AsRef<Path>, meshes: impl IntoIterator<Item = &'a Mesh>) -> Result<(), MeshCacheError> {
+    let path = path.as_ref();
+    let meshes: Vec<&Mesh> = meshes.into_iter().collect();
+
+    let mut table = Vec::with_capacity(meshes.len() * ENTRY_SIZE);
+    let mut data = Vec::new();
+    let data_start = HEADER_SIZE + meshes.len() * ENTRY_SIZE;
+    for mesh in &meshes {
+      let has_tangents = !mesh.tangents.is_empty();
+      let has_colors = !mesh.colors.is_empty();
+      let has_skin = !mesh.joints.is_empty();
+      let bounds = mesh.bounds();
+      let flags = has_tangents as u32 * HAS_TANGENTS
+        + has_colors as u32 * HAS_COLORS
+        + bounds.is_some() as u32 * HAS_BOUNDS
+        + has_skin as u32 * HAS_SKIN;
+
+      table.extend_from_slice(&(mesh.vertex_count() as u32).to_le_bytes());
+      table.extend_from_slice(&(mesh.indices.len() as u32).to_le_bytes());
+      table.extend_from_slice(&flags.to_le_bytes());
+      table.extend_from_slice(&((data_start + data.len()) as u64).to_le_bytes());
+      let bounds = bounds.map_or([0.0; 10], |b| {
+        let (aabb, sphere) = (b.aabb, b.sphere);
+        let c = sphere.center;
+        [
+          aabb.min[0],
+          aabb.min[1],
+          aabb.min[2],
+          aabb.max[0],
+          aabb.max[1],
+          aabb.max[2],
+          c[0],
+          c[1],
+          c[2],
+          sphere.radius,
+        ]
+      });
+      extend_floats(&mut table, &bounds);
+      table.extend_from_slice(&(mesh.morph_targets.len() as u32).to_le_bytes());
+
+      for vertex in mesh.vertices() {
+        extend_floats(&mut data, &vertex.position);
+        extend_floats(&mut data, &vertex.normal);
+        extend_floats(&mut data, &vertex.uv);
+      }
+      for v in mesh.tangents.iter().chain(&mesh.colors) {
+        extend_floats(&mut data, v);
+      }
+      for vertex in mesh.skin_vertices() {
+        for joint in &vertex.joints {
+          data.extend_from_slice(&joint.to_le_bytes());
+        }
+        extend_floats(&mut data, &vertex.weights);
+      }
+      for delta in mesh.morph_deltas() {
+        extend_floats(&mut data, &delta.position);
+        extend_floats(&mut data, &delta.normal);
+      }
+      for index in &mesh.indices {
+        data.extend_from_slice(&index.to_le_bytes());
+      }
+    }
+
+    let count = (meshes.len() as u32).to_le_bytes();
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&count);
+    hasher.update(&table);
+    hasher.update(&data);
+
+    let mut file = Vec::with_capacity(data_start + data.len());
+    file.extend_from_slice(&MAGIC);
+    file.extend_from_slice(&VERSION.to_le_bytes());
+    file.extend_from_slice(&count);
+    file.extend_from_slice(&hasher.finalize().to_le_bytes());
+    file.extend_from_slice(&table);
+    file.extend_from_slice(&data);
+    replace_file(path, &file).map_err(io_error(path))
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn mesh(&self, index: usize) -> CachedMesh<'_> {
+    let entry = self.entries[index];
+    let mut offset = entry.offset;
+    let mut take = |count: usize| {
+      let bytes = &self.mmap[offset..offset + count];
+      offset += count;
+      bytes
+    };
+
+    let vertices = take(entry.vertex_count * mem::size_of::<MeshVertex>());
+    let tangents = take(if entry.flags & HAS_TANGENTS != 0 {
+      entry.vertex_count * 16
+    } else {
+      0
+    });
+    let colors = take(if entry.flags & HAS_COLORS != 0 {
+      entry.vertex_count * 16
+    } else {
+      0
+    });
+    let skin = take(if entry.flags & HAS_SKIN != 0 {
+      entry.vertex_count * mem::size_of::<SkinVertex>()
+    } else {
+      0
+    });
+    let morph_deltas = take(entry.morph_target_count * entry.vertex_count * mem::size_of::<MorphDelta>());
+    let indices = take(entry.index_count * 4);
+    // `parse_header` checked the ranges, and every array is made of values at most 4-byte aligned, at a 4-byte
+    // aligned offset in a page aligned mapping.
+    unsafe {
+      CachedMesh {
+        vertices: cast(vertices),
+        tangents: cast(tangents),
+        colors: cast(colors),
+        skin: cast(skin),
+        morph_deltas: cast(morph_deltas),
+        morph_target_count: entry.morph_target_count,
+        indices: cast(indices),
+        bounds: entry.bounds,
+      }
+    }
+  }
+
+  pub fn meshes(&self) -> impl Iterator<Item = CachedMesh<'_>> + '_ {
+    (0..self.len()).map(move |i| self.mesh(i))
+  }
+}
+
+impl CachedMesh<'_> {
+  /// Copies the mesh out of the cache, for editing it.
+  pub fn to_mesh(&self) -> Mesh {
+    Mesh {
+      positions: self.vertices.iter().map(|v| v.position).collect(),
+      normals: self.vertices.iter().map(|v| v.normal).collect(),
+      uvs: self.vertices.iter().map(|v| v.uv).collect(),
+      tangents: self.tangents.to_vec(),
+      colors: self.colors.iter().map(|c| c.color).collect(),
+      joints: self.skin.iter().map(|s| s.joints).collect(),
+      weights: self.skin.iter().map(|s| s.weights).collect(),
+      morph_targets: (0..self.morph_target_count)
+        .map(|target| {
+          let deltas = &self.morph_deltas[target * self.vertices.len()..(target + 1) * self.vertices.len()];
+          MorphTarget {
+            positions: deltas
+              .iter()
+              .map(|d| [d.position[0], d.position[1], d.position[2]])
+              .collect(),
+            normals: deltas.iter().map(|d| [d.normal[0], d.normal[1], d.normal[2]]).collect(),
+          }
+        })
+        .collect(),
+      indices: self.indices.to_vec(),
+    }
+  }
+
+  /// Uploads the mesh straight from the mapping, like `Mesh::upload` without building the vertices first.
+  pub unsafe fn upload(&self) -> GpuMesh {
+    GpuMesh::new(
+      self.vertices,
+      self.colors,
+      self.skin,
+      self.morph_deltas,
+      self.indices,
+      self.bounds,
+    )
+  }
+}
+
+/// Writes `contents` to a temporary file in the directory of `path` and renames it to `path`.
+fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+  let name = path
+    .file_name()
+    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+  let mut temp_name = name.to_os_string();
+  temp_name.push(format!(".{}.tmp", process::id()));
+  let temp = path.with_file_name(temp_name);
+
+  let result = fs::write(&temp, contents).and_then(|_| fs::rename(&temp, path));
+  if result.is_err() {
+    let _ = fs::remove_file(&temp);
+  }
+  result
+}
+
+fn extend_floats(bytes: &mut Vec<u8>, values: &[f32]) {
+  for value in values {
+    bytes.extend_from_slice(&value.to_le_bytes());
+  }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+  u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+fn read_f32(bytes: &[u8], at: usize) -> f32 {
+  f32::from_bits(read_u32(bytes, at))
+}
+
+/// Reinterprets `bytes` as a slice of `T`, which has to be made of plain numbers at most 4-byte aligned.
+unsafe fn cast<T>(bytes: &[u8]) -> &[T] {
+  debug_assert_eq!(bytes.as_ptr() as usize % mem::align_of::<T>(), 0);
+  debug_assert_eq!(bytes.len() % mem::size_of::<T>(), 0);
+  slice::from_raw_parts(bytes.as_ptr() as *const T, bytes.len() / mem::size_of::<T>())
+}
+
+fn parse_header(bytes: &[u8]) -> Result<Vec<Entry>, MeshCacheError> {
+  if bytes.len() < HEADER_SIZE || bytes[..8] != MAGIC {
+    return Err(MeshCacheError::NotACache);
+  }
+  let version = read_u32(bytes, 8);
+  if version != VERSION {
+    return Err(MeshCacheError::Version(version));
+  }
+  let stored = read_u32(bytes, 16);
+  let mut hasher = crc32fast::Hasher::new();
+  hasher.update(&bytes[12..16]);
+  hasher.update(&bytes[HEADER_SIZE..]);
+  let computed = hasher.finalize();
+  if stored != computed {
+    return Err(MeshCacheError::Checksum { stored, computed });
+  }
+
+  let corrupt = |message: String| MeshCacheError::Corrupt(message);
+  let count = read_u32(bytes, 12) as usize;
+  let table_end = count
+    .checked_mul(ENTRY_SIZE)
+    .and_then(|size| size.checked_add(HEADER_SIZE))
+    .filter(|&end| end <= bytes.len())
+    .ok_or_else(|| corrupt(format!("the table of {} meshes doesn't fit", count)))?;
+
+  (HEADER_SIZE..table_end)
+    .step_by(ENTRY_SIZE)
+    .enumerate()
+    .map(|(i, at)| {
+      let vertex_count = read_u32(bytes, at) as usize;
+      let index_count = read_u32(bytes, at + 4) as usize;
+      let flags = read_u32(bytes, at + 8);
+      let offset = u64::from_le_bytes([
+        bytes[at + 12],
+        bytes[at + 13],
+        bytes[at + 14],
+        bytes[at + 15],
+        bytes[at + 16],
+        bytes[at + 17],
+        bytes[at + 18],
+        bytes[at + 19],
+      ]) as usize;
+      let morph_target_count = read_u32(bytes, at + 60) as usize;
+
+      let per_vertex = mem::size_of::<MeshVertex>()
+        + if flags & HAS_TANGENTS != 0 { 16 } else { 0 }
+        + if flags & HAS_COLORS != 0 { 16 } else { 0 }
+        + if flags & HAS_SKIN != 0 {
+          mem::size_of::<SkinVertex>()
+        } else {
+          0
+        };
+      let end = morph_target_count
+        .checked_mul(mem::size_of::<MorphDelta>())
+        .and_then(|size| size.checked_add(per_vertex))
+        .and_then(|size| size.checked_mul(vertex_count))
+        .and_then(|size| size.checked_add(index_count.checked_mul(4)?))
+        .and_then(|size| size.checked_add(offset));
+      if !offset.is_multiple_of(4) || offset < table_end || end.is_none_or(|end| end > bytes.len()) {
+        return Err(corrupt(format!("mesh {} points outside of the file", i)));
+      }
+      if !index_count.is_multiple_of(3) {
+        return Err(corrupt(format!("mesh {} has {} indices", i, index_count)));
+      }
+
+      let b = |k: usize| read_f32(bytes, at + 20 + k * 4);
+      let bounds = if flags & HAS_BOUNDS != 0 {
+        Some(Bounds {
+          aabb: Aabb {
+            min: [b(0), b(1), b(2)],
+            max: [b(3), b(4), b(5)],
+          },
+          sphere: BoundingSphere {
+            center: [b(6), b(7), b(8)],
+            radius: b(9),
+          },
+        })
+      } else {
+        None
+      };
+
+      Ok(Entry {
+        vertex_count,
+        index_count,
+        flags,
+        offset,
+        morph_target_count,
+        bounds,
+      })
+    })
+    .collect()
+}
+
+fn load_source(path: &Path) -> Result<Vec<Mesh>, Box<dyn Error>> {
+  let extension = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase);
+  Ok(match extension.as_deref() {
+    Some("obj") => ObjModel::load(path)?.meshes.into_iter().map(|m| m.mesh).collect(),
+    Some("gltf") | Some("glb") => GltfScene::load(path)?
+      .meshes
+      .into_iter()
+      .flat_map(|m| m.primitives)
+      .map(|p| p.mesh)
+      .collect(),
+    Some("ply") => vec![load_ply(path)?],
+    Some("stl") => vec![load_stl(path)?],
+    _ => return Err(format!("{} isn't an OBJ, glTF, PLY or STL file", path.display()).into()),
+  })
+}
@@ -0,0 +1,396 @@
+use gl::types::{GLchar, GLenum, GLint, GLuint};
+use std::error::Error;
+use std::ffi::CString;
+use std::fmt;
+use std::ptr;
+
+use crate::uniform::{validate_fields, UniformValue, Uniforms};
+
+mod binary_cache;
+mod spirv;
+mod variant;
+
+pub use binary_cache::ProgramCache;
+pub use spirv::SpirvModule;
+pub use variant::{Defines, ProgramVariants};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShaderStage {
+  Vertex,
+  TessControl,
+  TessEvaluation,
+  Geometry,
+  Fragment,
+  Compute,
+}
+
+impl ShaderStage {
+  pub fn gl_enum(self) -> GLenum {
+    match self {
+      ShaderStage::Vertex => gl::VERTEX_SHADER,
+      ShaderStage::TessControl => gl::TESS_CONTROL_SHADER,
+      ShaderStage::TessEvaluation => gl::TESS_EVALUATION_SHADER,
+      ShaderStage::Geometry => gl::GEOMETRY_SHADER,
+      ShaderStage::Fragment => gl::FRAGMENT_SHADER,
+      ShaderStage::Compute => gl::COMPUTE_SHADER,
+    }
+  }
+}
+
+impl fmt::Display for ShaderStage {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let name = match self {
+      ShaderStage::Vertex => "vertex",
+      ShaderStage::TessControl => "tessellation control",
+      ShaderStage::TessEvaluation => "tessellation evaluation",
+      ShaderStage::Geometry => "geometry",
+      ShaderStage::Fragment => "fragment",
+      ShaderStage::Compute => "compute",
+    };
+    f.write_str(name)
+  }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+  Compile {
+    stage: ShaderStage,
+    log: String,
+  },
+  Link(String),
+  MissingStage(ShaderStage),
+  /// Compute shaders can't be linked together with graphics stages.
+  MixedCompute,
+  /// A uniform set from Rust isn't an active uniform of the program.
+  UnknownUniform(String),
+  UniformType {
+    name: String,
+    rust_type: &'static str,
+    gl_type: GLenum,
+  },
+  /// A Rust array has more elements than the GLSL array it's set to.
+  UniformLength {
+    name: String,
+    len: usize,
+    size: usize,
+  },
+}
+
+impl fmt::Display for ShaderError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ShaderError::Compile { stage, log } => write!(f, "failed to compile {} shader: {}", stage, log),
+      ShaderError::Link(log) => write!(f, "failed to link shader program: {}", log),
+      ShaderError::MissingStage(stage) => write!(f, "shader program is missing a {} stage", stage),
+      ShaderError::MixedCompute => write!(f, "compute stage can't be combined with graphics stages"),
+      ShaderError::UnknownUniform(name) => write!(f, "no active uniform named `{}`", name),
+      ShaderError::UniformType {
+        name,
+        rust_type,
+        gl_type,
+      } => write!(
+        f,
+        "uniform `{}` has GL type {:#06x} which can't be set from {}",
+        name, gl_type, rust_type
+      ),
+      ShaderError::UniformLength { name, len, size } => write!(
+        f,
+        "uniform `{}` has {} elements but is set from an array of {}",
+        name, size, len
+      ),
+    }
+  }
+}
+
+impl Error for ShaderError {}
+
+pub struct ShaderProgram {
+  id: GLuint,
+}
+
+impl ShaderProgram {
+  /// Compiles and links a vertex + fragment program, panicking on errors.
+  pub unsafe fn from_str(vs_code: &str, fs_code: &str) -> Self {
+    ProgramBuilder::new()
+      .vertex(vs_code)
+      .fragment(fs_code)
+      .build()
+      .unwrap_or_else(|e| panic!("{}", e))
+  }
+
+  pub unsafe fn compute(cs_code: &str) -> Result<Self, ShaderError> {
+    ProgramBuilder::new().compute(cs_code).build()
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+
+  pub unsafe fn use_program(&self) {
+    gl::UseProgram(self.id);
+  }
+
+  pub unsafe fn uniform_location(&self, name: &str) -> GLint {
+    let name = CString::new(name).expect("uniform name contains a nul byte");
+    gl::GetUniformLocation(self.id, name.as_ptr())
+  }
+
+  /// Sets a uniform on this program, which must be the one currently in use.
+  pub unsafe fn set_uniform_value<T: UniformValue>(&self, name: &str, value: T) {
+    value.set_uniform(self.uniform_location(name));
+  }
+
+  /// Sets a uniform by its explicit `layout(location = N)`, as SPIR-V programs can't be queried by name.
+  pub unsafe fn set_uniform_value_at<T: UniformValue>(&self, location: GLint, value: T) {
+    value.set_uniform(location);
+  }
+
+  /// Names, GL types and array sizes of the active uniforms, with array uniforms reported without their `[0]`.
+  pub unsafe fn active_uniforms(&self) -> Vec<(String, GLenum, usize)> {
+    let mut count = 0;
+    gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORMS, &mut count);
+    let mut max_len = 0;
+    gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+
+    (0..count as GLuint)
+      .map(|i| {
+        let mut name = vec![0u8; max_len.max(1) as usize];
+        let mut len = 0;
+        let mut size = 0;
+        let mut gl_type = 0;
+        gl::GetActiveUniform(
+          self.id,
+          i,
+          max_len,
+          &mut len,
+          &mut size,
+          &mut gl_type,
+          name.as_mut_ptr() as *mut GLchar,
+        );
+        name.truncate(len as usize);
+
+        let mut name = String::from_utf8_lossy(&name).into_owned();
+        if name.ends_with("[0]") {
+          name.truncate(name.len() - 3);
+        }
+        (name, gl_type, size as usize)
+      })
+      .collect()
+  }
+
+  /// Checks that every field of `U` is an active uniform with a compatible type.
+  pub unsafe fn validate_uniforms<U: Uniforms>(&self) -> Result<(), ShaderError> {
+    validate_fields(U::fields(), &self.active_uniforms())
+  }
+
+  /// Runs a compute program over the given number of work groups.
+  pub unsafe fn dispatch_compute(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
+    self.use_program();
+    gl::DispatchCompute(groups_x, groups_y, groups_z);
+  }
+}
+
+impl Drop for ShaderProgram {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteProgram(self.id);
+    }
+  }
+}
+
+/// Collects the stages of a program before compiling and linking them together.
+///
+/// A graphics program needs at least a vertex stage; tessellation evaluation is required whenever a
+/// tessellation control stage is given. A compute program has a single compute stage.
+#[derive(Default)]
+pub struct ProgramBuilder<'a> {
+  stages: Vec<(ShaderStage, StageSource<'a>)>,
+  checks: Vec<ProgramCheck>,
+}
+
+type ProgramCheck = unsafe fn(&ShaderProgram) -> Result<(), ShaderError>;
+
+enum StageSource<'a> {
+  Glsl(&'a str),
+  SpirV(SpirvModule<'a>),
+}
+
+impl<'a> ProgramBuilder<'a> {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  fn set_stage(mut self, stage: ShaderStage, source: StageSource<'a>) -> Self {
+    self.stages.retain(|(s, _)| *s != stage);
+    self.stages.push((stage, source));
+    self
+  }
+
+  /// Sets the GLSL source of a stage, replacing any previous source for it.
+  pub fn stage(self, stage: ShaderStage, code: &'a str) -> Self {
+    self.set_stage(stage, StageSource::Glsl(code))
+  }
+
+  /// Sets a precompiled SPIR-V module for a stage, replacing any previous source for it.
+  ///
+  /// GLSL and SPIR-V stages can be mixed in one program.
+  pub fn spirv(self, stage: ShaderStage, module: SpirvModule<'a>) -> Self {
+    self.set_stage(stage, StageSource::SpirV(module))
+  }
+
+  pub fn vertex(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::Vertex, code)
+  }
+
+  pub fn tess_control(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::TessControl, code)
+  }
+
+  pub fn tess_evaluation(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::TessEvaluation, code)
+  }
+
+  pub fn geometry(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::Geometry, code)
+  }
+
+  pub fn fragment(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::Fragment, code)
+  }
+
+  pub fn compute(self, code: &'a str) -> Self {
+    self.stage(ShaderStage::Compute, code)
+  }
+
+  /// Validates the linked program against the uniforms of `U`, see `ShaderProgram::validate_uniforms`.
+  pub fn uniforms<U: Uniforms>(mut self) -> Self {
+    self.checks.push(ShaderProgram::validate_uniforms::<U>);
+    self
+  }
+
+  fn has(&self, stage: ShaderStage) -> bool {
+    self.stages.iter().any(|(s, _)| *s == stage)
+  }
+
+  fn validate(&self) -> Result<(), ShaderError> {
+    if self.has(ShaderStage::Compute) {
+      if self.stages.len() > 1 {
+        return Err(ShaderError::MixedCompute);
+      }
+      return Ok(());
+    }
+
+    if !self.has(ShaderStage::Vertex) {
+      return Err(ShaderError::MissingStage(ShaderStage::Vertex));
+    }
+    if self.has(ShaderStage::TessControl) && !self.has(ShaderStage::TessEvaluation) {
+      return Err(ShaderError::MissingStage(ShaderStage::TessEvaluation));
+    }
+
+    Ok(())
+  }
+
+  pub unsafe fn build(self) -> Result<ShaderProgram, ShaderError> {
+    self.compile_and_link(false)
+  }
+
+  /// With `retrievable` set the driver is asked to keep the linked binary for `glGetProgramBinary`.
+  unsafe fn compile_and_link(&self, retrievable: bool) -> Result<ShaderProgram, ShaderError> {
+    self.validate()?;
+
+    let mut shaders = Vec::with_capacity(self.stages.len());
+    for (stage, source) in &self.stages {
+      let compiled = match source {
+        StageSource::Glsl(code) => compile_shader(*stage, code),
+        StageSource::SpirV(module) => spirv::specialize_shader(*stage, module),
+      };
+      match compiled {
+        Ok(shader) => shaders.push(shader),
+        Err(e) => {
+          shaders.iter().for_each(|&s| gl::DeleteShader(s));
+          return Err(e);
+        }
+      }
+    }
+
+    let program = link_program(&shaders, retrievable)?;
+    self.run_checks(&program)?;
+
+    Ok(program)
+  }
+
+  unsafe fn run_checks(&self, program: &ShaderProgram) -> Result<(), ShaderError> {
+    self.checks.iter().try_for_each(|check| check(program))
+  }
+}
+
+pub(crate) unsafe fn compile_shader(stage: ShaderStage, code: &str) -> Result<GLuint, ShaderError> {
+  let shader = gl::CreateShader(stage.gl_enum());
+  let code_ptr = code.as_ptr() as *const GLchar;
+  let code_len = code.len() as GLint;
+  gl::ShaderSource(shader, 1, &code_ptr, &code_len);
+  gl::CompileShader(shader);
+
+  check_compile_status(stage, shader)
+}
+
+/// Returns the shader if it compiled, otherwise deletes it and returns the info log.
+unsafe fn check_compile_status(stage: ShaderStage, shader: GLuint) -> Result<GLuint, ShaderError> {
+  let mut success = 0;
+  gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+  if success == 0 {
+    let mut len = 0;
+    gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut len);
+    let mut log = vec![0u8; len.max(1) as usize];
+    gl::GetShaderInfoLog(shader, len, ptr::null_mut(), log.as_mut_ptr() as *mut GLchar);
+    gl::DeleteShader(shader);
+
+    return Err(ShaderError::Compile {
+      stage,
+      log: info_log_to_string(log),
+    });
+  }
+
+  Ok(shader)
+}
+
+/// Links the given shaders into a program. The shaders are deleted afterwards either way.
+pub(crate) unsafe fn link_program(shaders: &[GLuint], retrievable: bool) -> Result<ShaderProgram, ShaderError> {
+  let id = gl::CreateProgram();
+  if retrievable {
+    gl::ProgramParameteri(id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as GLint);
+  }
+  shaders.iter().for_each(|&s| gl::AttachShader(id, s));
+  gl::LinkProgram(id);
+  shaders.iter().for_each(|&s| {
+    gl::DetachShader(id, s);
+    gl::DeleteShader(s);
+  });
+
+  let program = ShaderProgram { id };
+  check_link_status(&program)?;
+
+  Ok(program)
+}
+
+pub(crate) unsafe fn check_link_status(program: &ShaderProgram) -> Result<(), ShaderError> {
+  let mut success = 0;
+  gl::GetProgramiv(program.id, gl::LINK_STATUS, &mut success);
+  if success == 0 {
+    let mut len = 0;
+    gl::GetProgramiv(program.id, gl::INFO_LOG_LENGTH, &mut len);
+    let mut log = vec![0u8; len.max(1) as usize];
+    gl::GetProgramInfoLog(program.id, len, ptr::null_mut(), log.as_mut_ptr() as *mut GLchar);
+
+    return Err(ShaderError::Link(info_log_to_string(log)));
+  }
+
+  Ok(())
+}
+
+fn info_log_to_string(mut log: Vec<u8>) -> String {
+  if let Some(nul) = log.iter().position(|&b| b == 0) {
+    log.truncate(nul);
+  }
+  String::from_utf8_lossy(&log).trim_end().to_string()
+}
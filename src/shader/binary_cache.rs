@@ -0,0 +1,200 @@
+use gl::types::{GLenum, GLsizei};
+use std::convert::TryInto;
+use std::ffi::{c_void, CStr};
+use std::fs;
+use std::path::PathBuf;
+use std::ptr;
+
+use super::{check_link_status, ProgramBuilder, ShaderError, ShaderProgram, StageSource};
+
+const MAGIC: &[u8; 8] = b"LOGLPRG1";
+
+/// Stores linked program binaries on disk so later runs can skip compiling.
+///
+/// Entries are keyed by a hash of the stage sources together with the driver vendor, renderer and
+/// version, so updating either the shaders or the driver simply misses the cache. A binary the driver
+/// rejects is treated the same way: the program is compiled again and the entry replaced. Failing to
+/// write an entry is not an error, the program just gets compiled again next time.
+pub struct ProgramCache {
+  dir: PathBuf,
+}
+
+impl ProgramCache {
+  pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+    Self { dir: dir.into() }
+  }
+
+  pub unsafe fn load_or_build(&self, builder: &ProgramBuilder) -> Result<ShaderProgram, ShaderError> {
+    let mut num_formats = 0;
+    gl::GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut num_formats);
+    if num_formats == 0 {
+      return builder.compile_and_link(false);
+    }
+
+    let driver = [gl::VENDOR, gl::RENDERER, gl::VERSION].map(|name| gl_string(name));
+    let key = cache_key(&driver, builder);
+    let path = self.dir.join(format!("{:016x}.bin", key));
+    if let Some(program) = fs::read(&path).ok().and_then(|data| load_binary(&data)) {
+      builder.run_checks(&program)?;
+      return Ok(program);
+    }
+
+    let program = builder.compile_and_link(true)?;
+    // The cache is only an optimization, failing to write it shouldn't fail the program.
+    let _ = fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, store_binary(&program)));
+
+    Ok(program)
+  }
+}
+
+unsafe fn gl_string(name: GLenum) -> String {
+  let s = gl::GetString(name);
+  if s.is_null() {
+    String::new()
+  } else {
+    CStr::from_ptr(s as *const _).to_string_lossy().into_owned()
+  }
+}
+
+/// 64-bit FNV-1a, stable across runs and Rust versions unlike `DefaultHasher`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+  bytes
+    .iter()
+    .fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Hashes the driver's vendor, renderer and version strings together with the program's stages.
+fn cache_key(driver: &[String; 3], builder: &ProgramBuilder) -> u64 {
+  let mut hash = 0xcbf2_9ce4_8422_2325;
+  for name in driver {
+    hash = fnv1a(hash, name.as_bytes());
+    hash = fnv1a(hash, &[0]);
+  }
+  for (stage, source) in &builder.stages {
+    hash = fnv1a(hash, &stage.gl_enum().to_le_bytes());
+    match source {
+      StageSource::Glsl(code) => hash = fnv1a(hash, code.as_bytes()),
+      StageSource::SpirV(module) => {
+        hash = fnv1a(hash, module.binary);
+        hash = fnv1a(hash, module.entry_point.as_bytes());
+        for (index, value) in &module.constants {
+          hash = fnv1a(hash, &index.to_le_bytes());
+          hash = fnv1a(hash, &value.to_le_bytes());
+        }
+      }
+    }
+  }
+  hash
+}
+
+/// Splits a cache file into the binary format and the binary, `None` when it isn't one.
+fn parse_binary(data: &[u8]) -> Option<(GLenum, &[u8])> {
+  if data.len() < 12 || &data[..8] != MAGIC {
+    return None;
+  }
+  let format = u32::from_le_bytes(data[8..12].try_into().unwrap());
+  Some((format, &data[12..]))
+}
+
+unsafe fn load_binary(data: &[u8]) -> Option<ShaderProgram> {
+  let (format, binary) = parse_binary(data)?;
+
+  let program = ShaderProgram {
+    id: gl::CreateProgram(),
+  };
+  gl::ProgramBinary(
+    program.id,
+    format,
+    binary.as_ptr() as *const c_void,
+    binary.len() as GLsizei,
+  );
+
+  check_link_status(&program).ok().map(|_| program)
+}
+
+unsafe fn store_binary(program: &ShaderProgram) -> Vec<u8> {
+  let mut len = 0;
+  gl::GetProgramiv(program.id, gl::PROGRAM_BINARY_LENGTH, &mut len);
+
+  let mut format = 0;
+  let mut binary = vec![0u8; len as usize];
+  gl::GetProgramBinary(
+    program.id,
+    len,
+    ptr::null_mut(),
+    &mut format,
+    binary.as_mut_ptr() as *mut c_void,
+  );
+
+  let mut data = Vec::with_capacity(12 + binary.len());
+  data.extend_from_slice(MAGIC);
+  data.extend_from_slice(&format.to_le_bytes());
+  data.extend_from_slice(&binary);
+  data
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shader::{ShaderStage, SpirvModule};
+
+  const VERT: &str = "#version 460 core\nvoid main() { gl_Position = vec4(0.0); }\n";
+  const FRAG: &str = "#version 460 core\nout vec4 color;\nvoid main() { color = vec4(1.0); }\n";
+
+  fn driver(vendor: &str, renderer: &str, version: &str) -> [String; 3] {
+    [vendor.to_string(), renderer.to_string(), version.to_string()]
+  }
+
+  #[test]
+  fn keys_change_with_the_driver_and_the_sources() {
+    let nvidia = driver("NVIDIA Corporation", "GeForce", "4.6.0 NVIDIA 535.54");
+    let program = ProgramBuilder::new().vertex(VERT).fragment(FRAG);
+    let key = cache_key(&nvidia, &program);
+    assert_eq!(
+      key,
+      cache_key(&nvidia, &ProgramBuilder::new().vertex(VERT).fragment(FRAG))
+    );
+
+    let others = [
+      driver("AMD", "GeForce", "4.6.0 NVIDIA 535.54"),
+      driver("NVIDIA Corporation", "Radeon", "4.6.0 NVIDIA 535.54"),
+      driver("NVIDIA Corporation", "GeForce", "4.6.0 NVIDIA 545.29"),
+      // The strings are separated, so moving text from one to the next changes the key.
+      driver("NVIDIA CorporationGeForce", "", "4.6.0 NVIDIA 535.54"),
+    ];
+    for other in &others {
+      assert_ne!(cache_key(other, &program), key, "{:?}", other);
+    }
+
+    let edited = FRAG.replace("1.0", "0.5");
+    let variants = [
+      ProgramBuilder::new().vertex(VERT).fragment(&edited),
+      ProgramBuilder::new().vertex(VERT),
+      ProgramBuilder::new().vertex(VERT).stage(ShaderStage::Geometry, FRAG),
+    ];
+    for variant in &variants {
+      assert_ne!(cache_key(&nvidia, variant), key);
+    }
+
+    let spirv = |value| {
+      let module = SpirvModule::new(&[3, 2, 0x23, 7]).constant_u32(0, value);
+      ProgramBuilder::new().spirv(ShaderStage::Vertex, module)
+    };
+    assert_ne!(cache_key(&nvidia, &spirv(1)), cache_key(&nvidia, &spirv(2)));
+  }
+
+  #[test]
+  fn headers_are_checked() {
+    let mut data = MAGIC.to_vec();
+    data.extend_from_slice(&0x8e21u32.to_le_bytes());
+    data.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(parse_binary(&data), Some((0x8e21, &[1, 2, 3][..])));
+    assert_eq!(parse_binary(&data[..12]), Some((0x8e21, &[][..])));
+
+    assert_eq!(parse_binary(&data[..11]), None);
+    assert_eq!(parse_binary(&[]), None);
+    let mut wrong_magic = data.clone();
+    wrong_magic[7] = b'2';
+    assert_eq!(parse_binary(&wrong_magic), None);
+  }
+}
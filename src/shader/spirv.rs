@@ -0,0 +1,113 @@
+use gl::types::{GLsizei, GLuint};
+use std::ffi::{c_void, CString};
+
+use super::{check_compile_status, ShaderError, ShaderStage};
+use crate::gl46;
+
+/// A SPIR-V shader module together with its entry point and specialization constants.
+///
+/// Uniforms in SPIR-V programs can't be looked up by name, so they need explicit
+/// `layout(location = N)` qualifiers and are set with `ShaderProgram::set_uniform_value_at`.
+#[derive(Clone, Debug)]
+pub struct SpirvModule<'a> {
+  pub(super) binary: &'a [u8],
+  pub(super) entry_point: &'a str,
+  pub(super) constants: Vec<(u32, u32)>,
+}
+
+impl<'a> SpirvModule<'a> {
+  pub fn new(binary: &'a [u8]) -> Self {
+    Self {
+      binary,
+      entry_point: "main",
+      constants: vec![],
+    }
+  }
+
+  pub fn entry_point(mut self, name: &'a str) -> Self {
+    self.entry_point = name;
+    self
+  }
+
+  /// Overrides the specialization constant declared with `layout(constant_id = id)`.
+  pub fn constant_u32(mut self, id: u32, value: u32) -> Self {
+    self.constants.retain(|(i, _)| *i != id);
+    self.constants.push((id, value));
+    self
+  }
+
+  pub fn constant_i32(self, id: u32, value: i32) -> Self {
+    self.constant_u32(id, value as u32)
+  }
+
+  pub fn constant_f32(self, id: u32, value: f32) -> Self {
+    self.constant_u32(id, value.to_bits())
+  }
+
+  pub fn constant_bool(self, id: u32, value: bool) -> Self {
+    self.constant_u32(id, value as u32)
+  }
+}
+
+pub(super) unsafe fn specialize_shader(stage: ShaderStage, module: &SpirvModule) -> Result<GLuint, ShaderError> {
+  let shader = gl::CreateShader(stage.gl_enum());
+  gl::ShaderBinary(
+    1,
+    &shader,
+    gl46::SHADER_BINARY_FORMAT_SPIR_V,
+    module.binary.as_ptr() as *const c_void,
+    module.binary.len() as GLsizei,
+  );
+
+  let entry_point = CString::new(module.entry_point).expect("entry point contains a nul byte");
+  let (indices, values): (Vec<u32>, Vec<u32>) = module.constants.iter().cloned().unzip();
+  gl46::specialize_shader(
+    shader,
+    entry_point.as_ptr(),
+    indices.len() as GLuint,
+    indices.as_ptr(),
+    values.as_ptr(),
+  );
+
+  check_compile_status(stage, shader)
+}
+
+/// Includes a shader from `shaders/` compiled to SPIR-V by the build script, e.g.
+/// `include_spirv!("colors.vert")`. Requires the `spirv` feature and `glslangValidator` on the `PATH`.
+#[macro_export]
+macro_rules! include_spirv {
+  ($name:literal) => {
+    include_bytes!(concat!(env!("OUT_DIR"), "/shaders/", $name, ".spv"))
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn constants_are_encoded_as_their_bits() {
+    let module = SpirvModule::new(&[])
+      .constant_u32(0, 7)
+      .constant_i32(1, -2)
+      .constant_f32(2, 0.5)
+      .constant_bool(3, true)
+      .constant_bool(4, false);
+    assert_eq!(
+      module.constants,
+      vec![(0, 7), (1, 0xffff_fffe), (2, 0x3f00_0000), (3, 1), (4, 0)]
+    );
+    assert_eq!(module.entry_point, "main");
+  }
+
+  #[test]
+  fn constants_are_overridden() {
+    let module = SpirvModule::new(&[])
+      .constant_u32(0, 1)
+      .constant_u32(1, 2)
+      .constant_f32(0, 1.0)
+      .entry_point("shade");
+    assert_eq!(module.constants, vec![(1, 2), (0, 0x3f80_0000)]);
+    assert_eq!(module.entry_point, "shade");
+  }
+}
@@ -0,0 +1,106 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::rc::Rc;
+
+use super::{ProgramBuilder, ProgramCache, ShaderError, ShaderProgram, ShaderStage};
+
+/// Preprocessor defines for a program variant, e.g. `USE_TEXTURE` or `NUM_POINT_LIGHTS=4`.
+///
+/// Defines are kept sorted by name, so two sets built in a different order are the same cache key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Defines(BTreeMap<String, Option<String>>);
+
+impl Defines {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds `#define NAME`.
+  pub fn flag(mut self, name: &str) -> Self {
+    self.0.insert(name.to_string(), None);
+    self
+  }
+
+  /// Adds `#define NAME VALUE`.
+  pub fn value<V: ToString>(mut self, name: &str, value: V) -> Self {
+    self.0.insert(name.to_string(), Some(value.to_string()));
+    self
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Inserts the defines right after the `#version` line (or at the top if there is none).
+  ///
+  /// A `#line` directive follows the defines so compile errors still point at the original lines.
+  pub fn inject(&self, code: &str) -> String {
+    if self.is_empty() {
+      return code.to_string();
+    }
+
+    let mut lines: Vec<&str> = code.lines().collect();
+    let insert_at = lines
+      .iter()
+      .position(|l| l.trim_start().starts_with("#version"))
+      .map_or(0, |i| i + 1);
+
+    let mut block = String::new();
+    for (name, value) in &self.0 {
+      match value {
+        Some(value) => writeln!(block, "#define {} {}", name, value).unwrap(),
+        None => writeln!(block, "#define {}", name).unwrap(),
+      }
+    }
+    write!(block, "#line {}", insert_at + 1).unwrap();
+
+    lines.insert(insert_at, &block);
+    lines.join("\n")
+  }
+}
+
+/// Lazily compiles and caches variants of one set of shader sources, keyed by their defines.
+pub struct ProgramVariants {
+  stages: Vec<(ShaderStage, String)>,
+  cache: HashMap<Defines, Rc<ShaderProgram>>,
+  disk_cache: Option<ProgramCache>,
+}
+
+impl ProgramVariants {
+  pub fn new(stages: &[(ShaderStage, &str)]) -> Self {
+    Self {
+      stages: stages.iter().map(|&(stage, code)| (stage, code.to_string())).collect(),
+      cache: HashMap::new(),
+      disk_cache: None,
+    }
+  }
+
+  /// Loads variants from (and stores them to) an on-disk program binary cache.
+  pub fn with_disk_cache(mut self, disk_cache: ProgramCache) -> Self {
+    self.disk_cache = Some(disk_cache);
+    self
+  }
+
+  /// Returns the variant for `defines`, compiling it on first use.
+  pub unsafe fn get(&mut self, defines: &Defines) -> Result<Rc<ShaderProgram>, ShaderError> {
+    if let Some(program) = self.cache.get(defines) {
+      return Ok(program.clone());
+    }
+
+    let sources: Vec<(ShaderStage, String)> = self
+      .stages
+      .iter()
+      .map(|(stage, code)| (*stage, defines.inject(code)))
+      .collect();
+    let builder = sources.iter().fold(ProgramBuilder::new(), |builder, (stage, code)| {
+      builder.stage(*stage, code)
+    });
+    let program = Rc::new(match &self.disk_cache {
+      Some(disk_cache) => disk_cache.load_or_build(&builder)?,
+      None => builder.build()?,
+    });
+
+    self.cache.insert(defines.clone(), program.clone());
+    Ok(program)
+  }
+}
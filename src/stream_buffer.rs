@@ -0,0 +1,216 @@
+use gl::types::{GLbitfield, GLenum, GLintptr, GLsizeiptr, GLsync, GLuint64};
+use std::mem;
+use std::ops::Range;
+use std::ptr;
+
+use crate::buffer::Buffer;
+
+/// How long a single `glClientWaitSync` call may block, in nanoseconds, before waiting again.
+const WAIT_TIMEOUT: GLuint64 = 1_000_000;
+
+/// A buffer for data rewritten every frame, like particles, debug lines or instance transforms.
+///
+/// The buffer is mapped once for its whole life (`GL_MAP_PERSISTENT_BIT`) and split into regions used in turn, so
+/// the CPU fills one region while the GPU still reads the previous ones. Each region is fenced when the next one is
+/// written and waited on before it is reused, which only blocks when the CPU gets a whole ring ahead of the GPU.
+///
+/// For uniform and shader storage buffers, regions start at the offset alignment the driver requires, so each one
+/// can be bound with `bind_range`.
+pub struct StreamBuffer<T: Copy> {
+  buffer: Buffer,
+  mapping: *mut T,
+  capacity: usize,
+  /// Elements from the start of one region to the start of the next, `capacity` plus any alignment padding.
+  stride: usize,
+  fences: Vec<Option<GLsync>>,
+  current: Option<usize>,
+  stalls: usize,
+}
+
+impl<T: Copy> StreamBuffer<T> {
+  /// Creates a buffer bound to `target` with `regions` regions of `capacity` elements each. Three regions cover
+  /// the frames a driver usually queues.
+  pub unsafe fn new(target: GLenum, capacity: usize, regions: usize) -> Self {
+    assert!(
+      capacity > 0 && regions > 0,
+      "a stream buffer needs room for at least one element"
+    );
+    assert!(mem::size_of::<T>() > 0, "zero-sized elements can't be streamed");
+
+    let alignment_query = match target {
+      gl::UNIFORM_BUFFER => Some(gl::UNIFORM_BUFFER_OFFSET_ALIGNMENT),
+      gl::SHADER_STORAGE_BUFFER => Some(gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT),
+      _ => None,
+    };
+    let mut alignment = 1;
+    if let Some(query) = alignment_query {
+      gl::GetIntegerv(query, &mut alignment);
+    }
+    let stride = region_stride(capacity, mem::size_of::<T>(), alignment.max(1) as usize);
+
+    let flags: GLbitfield = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+    let size = (stride * regions * mem::size_of::<T>()) as GLsizeiptr;
+    let buffer = Buffer::new(target);
+    gl::NamedBufferStorage(buffer.id(), size, ptr::null(), flags);
+    let mapping = gl::MapNamedBufferRange(buffer.id(), 0, size, flags) as *mut T;
+    assert!(!mapping.is_null(), "failed to map the stream buffer");
+
+    Self {
+      buffer,
+      mapping,
+      capacity,
+      stride,
+      fences: vec![None; regions],
+      current: None,
+      stalls: 0,
+    }
+  }
+
+  /// The underlying buffer, for attaching it to a vertex array.
+  pub fn buffer(&self) -> &Buffer {
+    &self.buffer
+  }
+
+  /// Elements per region, the most a single `write` takes.
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  pub fn regions(&self) -> usize {
+    self.fences.len()
+  }
+
+  /// How many writes had to wait for the GPU to release their region. Growing steadily means more regions are
+  /// needed.
+  pub fn stalls(&self) -> usize {
+    self.stalls
+  }
+
+  /// Copies `data` into the next region and returns where it landed, in elements from the start of the buffer:
+  /// the `first` of a `glDrawArrays` or the instances of `GpuMesh::draw_instance_range`.
+  ///
+  /// The previously written region is fenced first, so every command issued since that write must be done with it
+  /// before it is handed out again. Issue the draws using the returned range before the next `write`.
+  pub fn write(&mut self, data: &[T]) -> Range<usize> {
+    assert!(
+      data.len() <= self.capacity,
+      "{} elements don't fit in stream buffer regions of {}",
+      data.len(),
+      self.capacity
+    );
+
+    // `new` needed a current context, and the buffer can't leave its thread, so the GL calls are fine. The copy
+    // stays inside the region, which is inside the mapping.
+    unsafe {
+      if let Some(previous) = self.current {
+        self.fences[previous] = Some(gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0));
+      }
+      let region = self.current.map_or(0, |previous| (previous + 1) % self.regions());
+      if let Some(fence) = self.fences[region].take() {
+        self.wait(fence);
+      }
+      self.current = Some(region);
+
+      let range = region_range(region, self.stride, data.len());
+      ptr::copy_nonoverlapping(data.as_ptr(), self.mapping.add(range.start), data.len());
+      range
+    }
+  }
+
+  /// Binds the elements in `range`, as returned by `write`, to binding point `index` of an indexed target like
+  /// `gl::UNIFORM_BUFFER`.
+  pub unsafe fn bind_range(&self, index: u32, range: Range<usize>) {
+    let size = mem::size_of::<T>();
+    gl::BindBufferRange(
+      self.buffer.target(),
+      index,
+      self.buffer.id(),
+      (range.start * size) as GLintptr,
+      (range.len() * size) as GLsizeiptr,
+    );
+  }
+
+  unsafe fn wait(&mut self, fence: GLsync) {
+    let mut flags = 0;
+    let mut timeout = 0;
+    loop {
+      match gl::ClientWaitSync(fence, flags, timeout) {
+        gl::ALREADY_SIGNALED | gl::CONDITION_SATISFIED => break,
+        gl::WAIT_FAILED => panic!("glClientWaitSync failed"),
+        _ => {
+          if timeout == 0 {
+            self.stalls += 1;
+          }
+          // Make sure the fence reaches the GPU, or the wait could never end.
+          flags = gl::SYNC_FLUSH_COMMANDS_BIT;
+          timeout = WAIT_TIMEOUT;
+        }
+      }
+    }
+    gl::DeleteSync(fence);
+  }
+}
+
+impl<T: Copy> Drop for StreamBuffer<T> {
+  fn drop(&mut self) {
+    unsafe {
+      for fence in self.fences.iter().flatten() {
+        gl::DeleteSync(*fence);
+      }
+      gl::UnmapNamedBuffer(self.buffer.id());
+    }
+  }
+}
+
+/// The elements between region starts: enough for `capacity` elements, rounded up so every region starts at a
+/// multiple of `alignment` bytes as well as of the element size.
+fn region_stride(capacity: usize, element_size: usize, alignment: usize) -> usize {
+  let step = element_size / gcd(element_size, alignment) * alignment;
+  (capacity * element_size).div_ceil(step) * step / element_size
+}
+
+/// Where `len` elements written to `region` land, in elements from the start of the buffer.
+fn region_range(region: usize, stride: usize, len: usize) -> Range<usize> {
+  let start = region * stride;
+  start..start + len
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn unaligned_regions_are_packed() {
+    assert_eq!(region_stride(100, 80, 1), 100);
+    assert_eq!(region_stride(3, 12, 4), 3);
+    assert_eq!(region_range(0, 100, 40), 0..40);
+    assert_eq!(region_range(2, 100, 100), 200..300);
+  }
+
+  #[test]
+  fn aligned_regions_start_on_the_alignment() {
+    // 5 mat4s take 320 bytes, padded to the next multiple of 256.
+    assert_eq!(region_stride(5, 64, 256), 8);
+    // 3 instances of 80 bytes: 1280 is the first offset that is a multiple of both 80 and 256.
+    assert_eq!(region_stride(3, 80, 256), 16);
+    assert_eq!(region_stride(4, 64, 256), 4);
+
+    for &(capacity, size, alignment) in &[(5, 64, 256), (3, 80, 256), (7, 12, 16), (1, 4, 256), (33, 48, 64)] {
+      let stride = region_stride(capacity, size, alignment);
+      assert!(stride >= capacity);
+      for region in 0..4 {
+        let range = region_range(region, stride, capacity);
+        assert_eq!(range.start * size % alignment, 0, "{:?}", (capacity, size, alignment));
+        assert!(range.end <= (region + 1) * stride);
+      }
+    }
+  }
+}
@@ -0,0 +1,327 @@
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use image::{Bgr, Bgra, DynamicImage, ImageBuffer, Luma, LumaA, Pixel, Rgb, Rgba};
+use std::error::Error;
+use std::ffi::c_void;
+use std::fmt;
+use std::mem;
+
+use crate::uniform::TextureUnit;
+
+/// A 2D texture with immutable storage, deleted on drop.
+///
+/// Everything goes through direct state access, so creating or editing a texture never disturbs the texture
+/// bound to the active unit.
+pub struct Texture2D {
+  id: GLuint,
+  width: u32,
+  height: u32,
+  levels: u32,
+}
+
+impl Texture2D {
+  /// Allocates `levels` mip levels of `internal_format`, which has to be a sized format like `gl::RGBA8`. `0` levels
+  /// allocates the full chain down to 1×1.
+  pub unsafe fn new(width: u32, height: u32, levels: u32, internal_format: GLenum) -> Self {
+    let levels = if levels == 0 { mip_levels(width, height) } else { levels };
+    let mut id = 0;
+    gl::CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+    gl::TextureStorage2D(
+      id,
+      levels as GLsizei,
+      internal_format,
+      width as GLsizei,
+      height as GLsizei,
+    );
+    Self {
+      id,
+      width,
+      height,
+      levels,
+    }
+  }
+
+  /// Creates a texture with a full mip chain from `image`, in the sized format matching its pixels, e.g. `gl::RG8`
+  /// for 8-bit gray with alpha. Gray images read as gray in all three color channels.
+  ///
+  /// GL expects the bottom row first, so images loaded from files usually need `flipv` beforehand.
+  pub unsafe fn from_image(image: &DynamicImage) -> Result<Self, TextureError> {
+    match image {
+      DynamicImage::ImageLuma8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageLumaA8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageRgb8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageRgba8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageBgr8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageBgra8(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageLuma16(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageLumaA16(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageRgb16(buffer) => Self::from_buffer(buffer),
+      DynamicImage::ImageRgba16(buffer) => Self::from_buffer(buffer),
+    }
+  }
+
+  /// Like `from_image`, for any pixel type with a matching format, including the floating point ones a
+  /// `DynamicImage` can't hold, like HDR images' `Rgb<f32>`.
+  pub unsafe fn from_buffer<P: TexturePixel>(image: &ImageBuffer<P, Vec<P::Subpixel>>) -> Result<Self, TextureError> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+      return Err(TextureError::Empty);
+    }
+    let mut max = 0;
+    gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max);
+    let max = max as u32;
+    if width > max || height > max {
+      return Err(TextureError::TooLarge { width, height, max });
+    }
+
+    let texture = Self::new(width, height, 0, P::INTERNAL_FORMAT);
+    if let Some(swizzle) = P::SWIZZLE {
+      let swizzle = swizzle.map(|channel| channel as GLint);
+      gl::TextureParameteriv(texture.id, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+    }
+    texture.upload(0, P::FORMAT, P::PIXEL_TYPE, image.as_raw());
+    texture.generate_mipmaps();
+    Ok(texture)
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+
+  pub fn levels(&self) -> u32 {
+    self.levels
+  }
+
+  /// Replaces mip level `level` with `pixels`, laid out as `format` and `pixel_type` like `gl::RGB` and
+  /// `gl::UNSIGNED_BYTE`, with rows tightly packed.
+  pub unsafe fn upload<T: Copy>(&self, level: u32, format: GLenum, pixel_type: GLenum, pixels: &[T]) {
+    assert!(
+      level < self.levels,
+      "mip level {} out of range for {} levels",
+      level,
+      self.levels
+    );
+    let (width, height) = ((self.width >> level).max(1), (self.height >> level).max(1));
+    let size = pixel_size(format, pixel_type)
+      .unwrap_or_else(|| panic!("unsupported pixel format {:#06x} of type {:#06x}", format, pixel_type));
+    let required = width as usize * height as usize * size;
+    assert!(
+      mem::size_of_val(pixels) >= required,
+      "{}×{} pixels need {} bytes, got {}",
+      width,
+      height,
+      required,
+      mem::size_of_val(pixels)
+    );
+    // The default 4-byte row alignment would skip bytes after rows of e.g. an odd number of RGB8 pixels.
+    let mut alignment = 0;
+    gl::GetIntegerv(gl::UNPACK_ALIGNMENT, &mut alignment);
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    gl::TextureSubImage2D(
+      self.id,
+      level as GLint,
+      0,
+      0,
+      width as GLsizei,
+      height as GLsizei,
+      format,
+      pixel_type,
+      pixels.as_ptr() as *const c_void,
+    );
+    gl::PixelStorei(gl::UNPACK_ALIGNMENT, alignment);
+  }
+
+  /// Fills the other mip levels from level 0.
+  pub unsafe fn generate_mipmaps(&self) {
+    gl::GenerateTextureMipmap(self.id);
+  }
+
+  pub unsafe fn set_parameter(&self, name: GLenum, value: GLint) {
+    gl::TextureParameteri(self.id, name, value);
+  }
+
+  pub unsafe fn set_wrap(&self, s: GLenum, t: GLenum) {
+    self.set_parameter(gl::TEXTURE_WRAP_S, s as GLint);
+    self.set_parameter(gl::TEXTURE_WRAP_T, t as GLint);
+  }
+
+  pub unsafe fn set_filter(&self, min: GLenum, mag: GLenum) {
+    self.set_parameter(gl::TEXTURE_MIN_FILTER, min as GLint);
+    self.set_parameter(gl::TEXTURE_MAG_FILTER, mag as GLint);
+  }
+
+  /// Binds the texture to `unit`, the value to give its `sampler2D` uniform.
+  pub unsafe fn bind_to_unit(&self, unit: TextureUnit) {
+    gl::BindTextureUnit(unit.0 as GLuint, self.id);
+  }
+}
+
+impl Drop for Texture2D {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteTextures(1, &self.id);
+    }
+  }
+}
+
+/// Returned by `Texture2D::from_image` for images GL can't store.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureError {
+  /// The image has no pixels.
+  Empty,
+  /// A side of the image is longer than `GL_MAX_TEXTURE_SIZE`.
+  TooLarge { width: u32, height: u32, max: u32 },
+}
+
+impl fmt::Display for TextureError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TextureError::Empty => write!(f, "image has no pixels"),
+      TextureError::TooLarge { width, height, max } => write!(
+        f,
+        "{}×{} image is larger than the maximum texture size of {}",
+        width, height, max
+      ),
+    }
+  }
+}
+
+impl Error for TextureError {}
+
+/// An `image` pixel type GL can upload as is, and the texture format that keeps all of it.
+pub trait TexturePixel: Pixel + 'static {
+  /// The sized internal format, like `gl::RGBA8`.
+  const INTERNAL_FORMAT: GLenum;
+  /// The channels of the pixel data, like `gl::RGBA`.
+  const FORMAT: GLenum;
+  /// The type of each channel, like `gl::UNSIGNED_BYTE`.
+  const PIXEL_TYPE: GLenum;
+  /// Where sampling reads red, green, blue and alpha from, for formats that don't store them as such.
+  const SWIZZLE: Option<[GLenum; 4]> = None;
+}
+
+const GRAY: [GLenum; 4] = [gl::RED, gl::RED, gl::RED, gl::ONE];
+const GRAY_ALPHA: [GLenum; 4] = [gl::RED, gl::RED, gl::RED, gl::GREEN];
+
+macro_rules! texture_pixel {
+  ($pixel:ty, $internal_format:ident, $format:ident, $pixel_type:ident $(, $swizzle:ident)?) => {
+    impl TexturePixel for $pixel {
+      const INTERNAL_FORMAT: GLenum = gl::$internal_format;
+      const FORMAT: GLenum = gl::$format;
+      const PIXEL_TYPE: GLenum = gl::$pixel_type;
+      $(const SWIZZLE: Option<[GLenum; 4]> = Some($swizzle);)?
+    }
+  };
+}
+
+texture_pixel!(Luma<u8>, R8, RED, UNSIGNED_BYTE, GRAY);
+texture_pixel!(LumaA<u8>, RG8, RG, UNSIGNED_BYTE, GRAY_ALPHA);
+texture_pixel!(Rgb<u8>, RGB8, RGB, UNSIGNED_BYTE);
+texture_pixel!(Rgba<u8>, RGBA8, RGBA, UNSIGNED_BYTE);
+texture_pixel!(Bgr<u8>, RGB8, BGR, UNSIGNED_BYTE);
+texture_pixel!(Bgra<u8>, RGBA8, BGRA, UNSIGNED_BYTE);
+texture_pixel!(Luma<u16>, R16, RED, UNSIGNED_SHORT, GRAY);
+texture_pixel!(LumaA<u16>, RG16, RG, UNSIGNED_SHORT, GRAY_ALPHA);
+texture_pixel!(Rgb<u16>, RGB16, RGB, UNSIGNED_SHORT);
+texture_pixel!(Rgba<u16>, RGBA16, RGBA, UNSIGNED_SHORT);
+texture_pixel!(Luma<f32>, R32F, RED, FLOAT, GRAY);
+texture_pixel!(LumaA<f32>, RG32F, RG, FLOAT, GRAY_ALPHA);
+texture_pixel!(Rgb<f32>, RGB32F, RGB, FLOAT);
+texture_pixel!(Rgba<f32>, RGBA32F, RGBA, FLOAT);
+
+/// The bytes a pixel of `format` and `pixel_type` takes in client memory, `None` for combinations this crate
+/// doesn't upload.
+fn pixel_size(format: GLenum, pixel_type: GLenum) -> Option<usize> {
+  let channels = match format {
+    gl::RED | gl::RED_INTEGER | gl::DEPTH_COMPONENT | gl::STENCIL_INDEX => 1,
+    gl::RG | gl::RG_INTEGER | gl::DEPTH_STENCIL => 2,
+    gl::RGB | gl::BGR | gl::RGB_INTEGER | gl::BGR_INTEGER => 3,
+    gl::RGBA | gl::BGRA | gl::RGBA_INTEGER | gl::BGRA_INTEGER => 4,
+    _ => return None,
+  };
+  let size = match pixel_type {
+    gl::UNSIGNED_BYTE | gl::BYTE => channels,
+    gl::UNSIGNED_SHORT | gl::SHORT | gl::HALF_FLOAT => 2 * channels,
+    gl::UNSIGNED_INT | gl::INT | gl::FLOAT => 4 * channels,
+    // Packed types hold a whole pixel.
+    gl::UNSIGNED_BYTE_3_3_2 | gl::UNSIGNED_BYTE_2_3_3_REV => 1,
+    gl::UNSIGNED_SHORT_5_6_5
+    | gl::UNSIGNED_SHORT_5_6_5_REV
+    | gl::UNSIGNED_SHORT_4_4_4_4
+    | gl::UNSIGNED_SHORT_4_4_4_4_REV
+    | gl::UNSIGNED_SHORT_5_5_5_1
+    | gl::UNSIGNED_SHORT_1_5_5_5_REV => 2,
+    gl::UNSIGNED_INT_8_8_8_8
+    | gl::UNSIGNED_INT_8_8_8_8_REV
+    | gl::UNSIGNED_INT_10_10_10_2
+    | gl::UNSIGNED_INT_2_10_10_10_REV
+    | gl::UNSIGNED_INT_24_8
+    | gl::UNSIGNED_INT_10F_11F_11F_REV
+    | gl::UNSIGNED_INT_5_9_9_9_REV => 4,
+    gl::FLOAT_32_UNSIGNED_INT_24_8_REV => 8,
+    _ => return None,
+  };
+  Some(size)
+}
+
+/// The number of levels in a full mip chain for a `width`×`height` texture.
+pub fn mip_levels(width: u32, height: u32) -> u32 {
+  32 - width.max(height).max(1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn full_mip_chains_end_at_one_pixel() {
+    assert_eq!(mip_levels(1, 1), 1);
+    assert_eq!(mip_levels(512, 512), 10);
+    assert_eq!(mip_levels(256, 300), 9);
+    assert_eq!(mip_levels(0, 0), 1);
+  }
+
+  #[test]
+  fn pixel_sizes() {
+    assert_eq!(pixel_size(gl::RGB, gl::UNSIGNED_BYTE), Some(3));
+    assert_eq!(pixel_size(gl::RGBA, gl::UNSIGNED_BYTE), Some(4));
+    assert_eq!(pixel_size(gl::RG, gl::HALF_FLOAT), Some(4));
+    assert_eq!(pixel_size(gl::RGBA, gl::FLOAT), Some(16));
+    assert_eq!(pixel_size(gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV), Some(4));
+    assert_eq!(pixel_size(gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8), Some(4));
+    assert_eq!(pixel_size(gl::RGBA, gl::RGBA), None);
+  }
+
+  #[test]
+  fn formats_match_pixel_layouts() {
+    fn check<P: TexturePixel>() {
+      assert_eq!(pixel_size(P::FORMAT, P::PIXEL_TYPE), Some(mem::size_of::<P>()));
+      assert_eq!(P::SWIZZLE.is_some(), P::CHANNEL_COUNT <= 2);
+    }
+    check::<Luma<u8>>();
+    check::<LumaA<u8>>();
+    check::<Rgb<u8>>();
+    check::<Rgba<u8>>();
+    check::<Bgr<u8>>();
+    check::<Bgra<u8>>();
+    check::<Luma<u16>>();
+    check::<LumaA<u16>>();
+    check::<Rgb<u16>>();
+    check::<Rgba<u16>>();
+    check::<Luma<f32>>();
+    check::<LumaA<f32>>();
+    check::<Rgb<f32>>();
+    check::<Rgba<f32>>();
+
+    // Alpha is kept.
+    assert_eq!(<Rgba<u8> as TexturePixel>::INTERNAL_FORMAT, gl::RGBA8);
+    assert_eq!(<Bgra<u8> as TexturePixel>::INTERNAL_FORMAT, gl::RGBA8);
+  }
+}
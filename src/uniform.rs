@@ -0,0 +1,380 @@
+use gl::types::{GLenum, GLint, GLsizei};
+use std::slice;
+
+use crate::shader::{ShaderError, ShaderProgram};
+
+/// A Rust value that can be uploaded to a uniform location of the currently used program.
+pub trait UniformValue {
+  /// Whether a uniform of this GLSL type, as reported by `glGetActiveUniform`, can be set from `Self`.
+  fn accepts(gl_type: GLenum) -> bool;
+
+  /// How many array elements a value sets, `None` when only known at run time like for slices.
+  const LEN: Option<usize> = Some(1);
+
+  unsafe fn set_uniform(&self, location: GLint);
+}
+
+/// A uniform type that can also be uploaded as a whole array with one `glUniform*v` call.
+///
+/// Every element is a `UniformValue`, and so are arrays, slices and `Vec`s of elements.
+pub trait UniformElement: Sized {
+  fn accepts(gl_type: GLenum) -> bool;
+
+  unsafe fn set_uniform_array(values: &[Self], location: GLint);
+}
+
+impl<T: UniformElement> UniformValue for T {
+  fn accepts(gl_type: GLenum) -> bool {
+    <T as UniformElement>::accepts(gl_type)
+  }
+
+  unsafe fn set_uniform(&self, location: GLint) {
+    T::set_uniform_array(slice::from_ref(self), location);
+  }
+}
+
+impl<T: UniformElement, const N: usize> UniformValue for [T; N] {
+  const LEN: Option<usize> = Some(N);
+
+  fn accepts(gl_type: GLenum) -> bool {
+    <T as UniformElement>::accepts(gl_type)
+  }
+
+  unsafe fn set_uniform(&self, location: GLint) {
+    T::set_uniform_array(self, location);
+  }
+}
+
+impl<T: UniformElement> UniformValue for &[T] {
+  const LEN: Option<usize> = None;
+
+  fn accepts(gl_type: GLenum) -> bool {
+    <T as UniformElement>::accepts(gl_type)
+  }
+
+  unsafe fn set_uniform(&self, location: GLint) {
+    T::set_uniform_array(self, location);
+  }
+}
+
+impl<T: UniformElement> UniformValue for Vec<T> {
+  const LEN: Option<usize> = None;
+
+  fn accepts(gl_type: GLenum) -> bool {
+    <T as UniformElement>::accepts(gl_type)
+  }
+
+  unsafe fn set_uniform(&self, location: GLint) {
+    T::set_uniform_array(self, location);
+  }
+}
+
+/// A group of uniforms set together, usually implemented with `#[derive(Uniforms)]`.
+///
+/// The `_prefixed` methods place the uniforms inside a GLSL struct: with a prefix of `lights[3]` a
+/// `position` field becomes `lights[3].position`. An empty prefix means top-level uniforms.
+pub trait Uniforms {
+  fn fields_prefixed(prefix: &str) -> Vec<UniformField>;
+
+  unsafe fn apply_prefixed(&self, program: &ShaderProgram, prefix: &str);
+
+  /// The uniforms written by `apply`, used to validate a program against this struct.
+  fn fields() -> Vec<UniformField> {
+    Self::fields_prefixed("")
+  }
+
+  /// Sets every uniform on `program`, which must be the one currently in use.
+  unsafe fn apply(&self, program: &ShaderProgram) {
+    self.apply_prefixed(program, "")
+  }
+}
+
+/// Arrays of GLSL structs, e.g. `uniform PointLight pointLights[4]`.
+impl<U: Uniforms, const N: usize> Uniforms for [U; N] {
+  fn fields_prefixed(prefix: &str) -> Vec<UniformField> {
+    (0..N).flat_map(|i| U::fields_prefixed(&element(prefix, i))).collect()
+  }
+
+  unsafe fn apply_prefixed(&self, program: &ShaderProgram, prefix: &str) {
+    for (i, u) in self.iter().enumerate() {
+      u.apply_prefixed(program, &element(prefix, i));
+    }
+  }
+}
+
+/// Name of a struct member: `member("material", "diffuse")` is `material.diffuse`.
+pub fn member(prefix: &str, name: &str) -> String {
+  if prefix.is_empty() {
+    name.to_string()
+  } else {
+    format!("{}.{}", prefix, name)
+  }
+}
+
+/// Name of an array element: `element("lights", 3)` is `lights[3]`.
+pub fn element(name: &str, index: usize) -> String {
+  format!("{}[{}]", name, index)
+}
+
+pub struct UniformField {
+  pub name: String,
+  pub rust_type: &'static str,
+  pub accepts: fn(GLenum) -> bool,
+  /// See `UniformValue::LEN`.
+  pub len: Option<usize>,
+}
+
+impl UniformField {
+  pub fn new<T: UniformValue>(name: &str) -> Self {
+    Self {
+      name: name.to_string(),
+      rust_type: std::any::type_name::<T>(),
+      accepts: T::accepts,
+      len: T::LEN,
+    }
+  }
+}
+
+/// Checks `fields` against a program's active uniforms, given as `(name, gl_type, size)`.
+pub(crate) fn validate_fields(
+  fields: Vec<UniformField>,
+  active: &[(String, GLenum, usize)],
+) -> Result<(), ShaderError> {
+  for field in fields {
+    match active.iter().find(|(name, _, _)| *name == field.name) {
+      None => return Err(ShaderError::UnknownUniform(field.name)),
+      Some(&(_, gl_type, _)) if !(field.accepts)(gl_type) => {
+        return Err(ShaderError::UniformType {
+          name: field.name,
+          rust_type: field.rust_type,
+          gl_type,
+        })
+      }
+      // Elements past the end of the GLSL array would be silently dropped by `glUniform*v`.
+      Some(&(_, _, size)) => {
+        if let Some(len) = field.len.filter(|&len| len > size) {
+          return Err(ShaderError::UniformLength {
+            name: field.name,
+            len,
+            size,
+          });
+        }
+      }
+    }
+  }
+  Ok(())
+}
+
+/// The index of a texture unit, for `sampler*` uniforms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct TextureUnit(pub i32);
+
+fn is_sampler(gl_type: GLenum) -> bool {
+  matches!(
+    gl_type,
+    gl::SAMPLER_1D
+      | gl::SAMPLER_2D
+      | gl::SAMPLER_3D
+      | gl::SAMPLER_CUBE
+      | gl::SAMPLER_1D_SHADOW
+      | gl::SAMPLER_2D_SHADOW
+      | gl::SAMPLER_CUBE_SHADOW
+      | gl::SAMPLER_1D_ARRAY
+      | gl::SAMPLER_2D_ARRAY
+      | gl::SAMPLER_2D_ARRAY_SHADOW
+      | gl::SAMPLER_CUBE_MAP_ARRAY
+      | gl::SAMPLER_2D_MULTISAMPLE
+      | gl::SAMPLER_2D_RECT
+      | gl::SAMPLER_BUFFER
+      | gl::INT_SAMPLER_2D
+      | gl::INT_SAMPLER_3D
+      | gl::INT_SAMPLER_CUBE
+      | gl::INT_SAMPLER_2D_ARRAY
+      | gl::UNSIGNED_INT_SAMPLER_2D
+      | gl::UNSIGNED_INT_SAMPLER_3D
+      | gl::UNSIGNED_INT_SAMPLER_CUBE
+      | gl::UNSIGNED_INT_SAMPLER_2D_ARRAY
+  )
+}
+
+// Types whose memory layout matches what `glUniform*v` expects, so slices are passed as is.
+macro_rules! uniform_element {
+  ($ty:ty, $gl_type:path, $func:ident, $scalar:ty) => {
+    impl UniformElement for $ty {
+      fn accepts(gl_type: GLenum) -> bool {
+        gl_type == $gl_type
+      }
+
+      unsafe fn set_uniform_array(values: &[Self], location: GLint) {
+        gl::$func(location, values.len() as GLsizei, values.as_ptr() as *const $scalar);
+      }
+    }
+  };
+}
+
+macro_rules! uniform_matrix {
+  ($ty:ty, $gl_type:path, $func:ident) => {
+    impl UniformElement for $ty {
+      fn accepts(gl_type: GLenum) -> bool {
+        gl_type == $gl_type
+      }
+
+      unsafe fn set_uniform_array(values: &[Self], location: GLint) {
+        gl::$func(
+          location,
+          values.len() as GLsizei,
+          gl::FALSE,
+          values.as_ptr() as *const f32,
+        );
+      }
+    }
+  };
+}
+
+uniform_element!(f32, gl::FLOAT, Uniform1fv, f32);
+uniform_element!(mint::Vector2<f32>, gl::FLOAT_VEC2, Uniform2fv, f32);
+uniform_element!(mint::Vector3<f32>, gl::FLOAT_VEC3, Uniform3fv, f32);
+uniform_element!(mint::Vector4<f32>, gl::FLOAT_VEC4, Uniform4fv, f32);
+uniform_element!(mint::Vector2<i32>, gl::INT_VEC2, Uniform2iv, i32);
+uniform_element!(mint::Vector3<i32>, gl::INT_VEC3, Uniform3iv, i32);
+uniform_element!(mint::Vector4<i32>, gl::INT_VEC4, Uniform4iv, i32);
+uniform_element!(u32, gl::UNSIGNED_INT, Uniform1uiv, u32);
+uniform_element!(mint::Vector2<u32>, gl::UNSIGNED_INT_VEC2, Uniform2uiv, u32);
+uniform_element!(mint::Vector3<u32>, gl::UNSIGNED_INT_VEC3, Uniform3uiv, u32);
+uniform_element!(mint::Vector4<u32>, gl::UNSIGNED_INT_VEC4, Uniform4uiv, u32);
+
+uniform_matrix!(mint::ColumnMatrix2<f32>, gl::FLOAT_MAT2, UniformMatrix2fv);
+uniform_matrix!(mint::ColumnMatrix3<f32>, gl::FLOAT_MAT3, UniformMatrix3fv);
+uniform_matrix!(mint::ColumnMatrix4<f32>, gl::FLOAT_MAT4, UniformMatrix4fv);
+
+impl UniformElement for i32 {
+  // Samplers are set to the index of their texture unit.
+  fn accepts(gl_type: GLenum) -> bool {
+    gl_type == gl::INT || gl_type == gl::BOOL || is_sampler(gl_type)
+  }
+
+  unsafe fn set_uniform_array(values: &[Self], location: GLint) {
+    gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr());
+  }
+}
+
+impl UniformElement for bool {
+  fn accepts(gl_type: GLenum) -> bool {
+    gl_type == gl::BOOL
+  }
+
+  unsafe fn set_uniform_array(values: &[Self], location: GLint) {
+    let values: Vec<i32> = values.iter().map(|&b| b as i32).collect();
+    gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr());
+  }
+}
+
+impl UniformElement for TextureUnit {
+  fn accepts(gl_type: GLenum) -> bool {
+    is_sampler(gl_type)
+  }
+
+  unsafe fn set_uniform_array(values: &[Self], location: GLint) {
+    gl::Uniform1iv(location, values.len() as GLsizei, values.as_ptr() as *const i32);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Uniforms;
+
+  #[derive(Uniforms)]
+  struct Light {
+    color: mint::Vector3<f32>,
+    intensity: f32,
+  }
+
+  #[derive(Uniforms)]
+  struct Scene {
+    #[uniform(nested)]
+    lights: [Light; 3],
+    #[uniform(nested)]
+    sun: Light,
+    #[uniform(name = "uWeights")]
+    weights: [f32; 4],
+    offsets: Vec<f32>,
+  }
+
+  fn names(fields: &[UniformField]) -> Vec<&str> {
+    fields.iter().map(|field| field.name.as_str()).collect()
+  }
+
+  /// The uniforms of a program declaring `Scene`'s members, with `lights` and `uWeights` of the given sizes.
+  fn active(lights: usize, weights: usize) -> Vec<(String, GLenum, usize)> {
+    let mut active = vec![];
+    for prefix in (0..lights).map(|i| element("lights", i)).chain(Some("sun".to_string())) {
+      active.push((member(&prefix, "color"), gl::FLOAT_VEC3, 1));
+      active.push((member(&prefix, "intensity"), gl::FLOAT, 1));
+    }
+    active.push(("uWeights".to_string(), gl::FLOAT, weights));
+    active.push(("offsets".to_string(), gl::FLOAT, 8));
+    active
+  }
+
+  #[test]
+  fn names_follow_glsl_paths() {
+    assert_eq!(member("", "view"), "view");
+    assert_eq!(member("material", "diffuse"), "material.diffuse");
+    assert_eq!(element("lights", 2), "lights[2]");
+    assert_eq!(member(&element("lights", 2), "color"), "lights[2].color");
+
+    assert_eq!(
+      names(&Scene::fields()),
+      [
+        "lights[0].color",
+        "lights[0].intensity",
+        "lights[1].color",
+        "lights[1].intensity",
+        "lights[2].color",
+        "lights[2].intensity",
+        "sun.color",
+        "sun.intensity",
+        "uWeights",
+        "offsets",
+      ]
+    );
+    assert_eq!(
+      names(&Light::fields_prefixed("scene.lights[1]")),
+      ["scene.lights[1].color", "scene.lights[1].intensity"]
+    );
+  }
+
+  #[test]
+  fn arrays_must_fit() {
+    let lens: Vec<_> = Scene::fields().iter().map(|field| field.len).collect();
+    assert_eq!(lens[8..], [Some(4), None]);
+    assert!(validate_fields(Scene::fields(), &active(3, 4)).is_ok());
+    // Setting fewer elements than the array has is fine.
+    assert!(validate_fields(Scene::fields(), &active(4, 6)).is_ok());
+
+    match validate_fields(Scene::fields(), &active(3, 3)) {
+      Err(ShaderError::UniformLength { name, len: 4, size: 3 }) => assert_eq!(name, "uWeights"),
+      other => panic!("expected a length error, got {:?}", other),
+    }
+    // Struct arrays are checked element by element, so the missing ones are unknown.
+    match validate_fields(Scene::fields(), &active(2, 4)) {
+      Err(ShaderError::UnknownUniform(name)) => assert_eq!(name, "lights[2].color"),
+      other => panic!("expected an unknown uniform, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn types_must_match() {
+    let mut active = active(3, 4);
+    active[1].1 = gl::INT;
+    match validate_fields(Scene::fields(), &active) {
+      Err(ShaderError::UniformType { name, gl_type, .. }) => {
+        assert_eq!(name, "lights[0].intensity");
+        assert_eq!(gl_type, gl::INT);
+      }
+      other => panic!("expected a type error, got {:?}", other),
+    }
+  }
+}
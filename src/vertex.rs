@@ -0,0 +1,146 @@
+use gl::types::{GLenum, GLint, GLuint};
+
+/// One vertex attribute inside a vertex struct.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+  pub location: GLuint,
+  pub components: GLint,
+  pub gl_type: GLenum,
+  /// Integer data converted to floats in `[0, 1]` (or `[-1, 1]` for signed types).
+  pub normalized: bool,
+  /// Integer data read as `int`/`uint` in the shader, through `glVertexAttribIPointer`.
+  pub integer: bool,
+  /// Byte offset of the field inside the vertex.
+  pub offset: usize,
+}
+
+/// A `#[repr(C)]` vertex struct whose fields are attributes, usually implemented with `#[derive(Vertex)]`.
+pub trait Vertex: Copy {
+  fn attributes() -> Vec<VertexAttribute>;
+}
+
+/// A field type usable as a vertex attribute.
+pub trait AttributeType {
+  /// Components per location.
+  const COMPONENTS: GLint;
+  const GL_TYPE: GLenum;
+  /// Consecutive locations taken, one per column for matrices.
+  const LOCATIONS: GLuint = 1;
+}
+
+/// A field type with integer components, which `#[vertex(integer)]` fields need: a float would be handed to
+/// `glVertexArrayAttribIFormat`, which only takes integer types.
+///
+/// ```compile_fail
+/// use learn_opengl::Vertex;
+///
+/// #[derive(Clone, Copy, Vertex)]
+/// #[repr(C)]
+/// struct Bad {
+///   #[vertex(integer)]
+///   weight: f32,
+/// }
+/// ```
+pub trait IntegerAttributeType: AttributeType {}
+
+/// Called by `#[derive(Vertex)]` to check `#[vertex(integer)]` fields.
+#[doc(hidden)]
+pub fn assert_integer<T: IntegerAttributeType>() {}
+
+macro_rules! integer_attribute_type {
+  ($($scalar:ty),*) => {
+    $(
+      impl IntegerAttributeType for $scalar {}
+      impl IntegerAttributeType for [$scalar; 1] {}
+      impl IntegerAttributeType for [$scalar; 2] {}
+      impl IntegerAttributeType for [$scalar; 3] {}
+      impl IntegerAttributeType for [$scalar; 4] {}
+      impl IntegerAttributeType for mint::Vector2<$scalar> {}
+      impl IntegerAttributeType for mint::Vector3<$scalar> {}
+      impl IntegerAttributeType for mint::Vector4<$scalar> {}
+    )*
+  };
+}
+
+macro_rules! attribute_type {
+  ($scalar:ty, $gl_type:path) => {
+    attribute_type!($scalar, $gl_type, 1);
+    attribute_type!([$scalar; 1], $gl_type, 1);
+    attribute_type!([$scalar; 2], $gl_type, 2);
+    attribute_type!([$scalar; 3], $gl_type, 3);
+    attribute_type!([$scalar; 4], $gl_type, 4);
+    attribute_type!(mint::Vector2<$scalar>, $gl_type, 2);
+    attribute_type!(mint::Vector3<$scalar>, $gl_type, 3);
+    attribute_type!(mint::Vector4<$scalar>, $gl_type, 4);
+  };
+  ($ty:ty, $gl_type:path, $components:expr) => {
+    impl AttributeType for $ty {
+      const COMPONENTS: GLint = $components;
+      const GL_TYPE: GLenum = $gl_type;
+    }
+  };
+}
+
+attribute_type!(f32, gl::FLOAT);
+attribute_type!(i32, gl::INT);
+attribute_type!(u32, gl::UNSIGNED_INT);
+attribute_type!(i16, gl::SHORT);
+attribute_type!(u16, gl::UNSIGNED_SHORT);
+attribute_type!(i8, gl::BYTE);
+attribute_type!(u8, gl::UNSIGNED_BYTE);
+integer_attribute_type!(i32, u32, i16, u16, i8, u8);
+
+macro_rules! matrix_attribute_type {
+  ($ty:ty, $size:expr) => {
+    impl AttributeType for $ty {
+      const COMPONENTS: GLint = $size;
+      const GL_TYPE: GLenum = gl::FLOAT;
+      const LOCATIONS: GLuint = $size;
+    }
+  };
+}
+
+matrix_attribute_type!([[f32; 2]; 2], 2);
+matrix_attribute_type!([[f32; 3]; 3], 3);
+matrix_attribute_type!([[f32; 4]; 4], 4);
+matrix_attribute_type!(mint::ColumnMatrix2<f32>, 2);
+matrix_attribute_type!(mint::ColumnMatrix3<f32>, 3);
+matrix_attribute_type!(mint::ColumnMatrix4<f32>, 4);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::mesh::MeshInstance;
+  use crate::Vertex;
+
+  fn layout<V: Vertex>() -> Vec<(GLuint, GLint, usize)> {
+    V::attributes()
+      .iter()
+      .map(|a| (a.location, a.components, a.offset))
+      .collect()
+  }
+
+  #[test]
+  fn matrices_take_one_location_per_column() {
+    assert_eq!(
+      layout::<MeshInstance>(),
+      [(3, 4, 0), (4, 4, 16), (5, 4, 32), (6, 4, 48), (7, 4, 64)]
+    );
+  }
+
+  #[test]
+  fn default_locations_skip_matrix_columns() {
+    #[derive(Clone, Copy, Vertex)]
+    #[repr(C)]
+    struct Instance {
+      offset: [f32; 2],
+      rotation: [[f32; 3]; 3],
+      scale: f32,
+    }
+
+    assert_eq!(
+      layout::<Instance>(),
+      [(0, 2, 0), (1, 3, 8), (2, 3, 20), (3, 3, 32), (4, 1, 44)]
+    );
+  }
+}
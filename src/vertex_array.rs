@@ -0,0 +1,141 @@
+use gl::types::{GLsizei, GLuint};
+use std::cell::Cell;
+use std::mem;
+
+use crate::buffer::Buffer;
+use crate::index_buffer::{Index, IndexBuffer};
+use crate::vertex::Vertex;
+
+/// A GL vertex array object, deleted on drop. Configured through direct state access, so only drawing needs it
+/// bound.
+pub struct VertexArray {
+  id: GLuint,
+  /// The first buffer binding point not used by an attached vertex buffer yet.
+  next_binding: Cell<GLuint>,
+}
+
+impl VertexArray {
+  pub unsafe fn new() -> Self {
+    let mut id = 0;
+    gl::CreateVertexArrays(1, &mut id);
+    Self {
+      id,
+      next_binding: Cell::new(0),
+    }
+  }
+
+  /// Starts a vertex array whose attributes are configured from vertex structs.
+  pub unsafe fn builder() -> VertexArrayBuilder {
+    VertexArrayBuilder {
+      vertex_array: Self::new(),
+    }
+  }
+
+  pub fn id(&self) -> GLuint {
+    self.id
+  }
+
+  /// Binds the vertex array until the returned guard is dropped.
+  pub unsafe fn bind(&self) -> VertexArrayBinding<'_> {
+    let mut previous = 0;
+    gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut previous);
+    gl::BindVertexArray(self.id);
+
+    VertexArrayBinding {
+      _vertex_array: self,
+      previous: previous as GLuint,
+    }
+  }
+
+  /// Sources the attributes of `V` from `buffer` through a new buffer binding point, advancing once every
+  /// `divisor` instances rather than per vertex when `divisor` isn't 0.
+  unsafe fn attach<V: Vertex>(&self, buffer: &Buffer, divisor: GLuint) {
+    let binding = self.next_binding.get();
+    self.next_binding.set(binding + 1);
+
+    gl::VertexArrayVertexBuffer(self.id, binding, buffer.id(), 0, mem::size_of::<V>() as GLsizei);
+    gl::VertexArrayBindingDivisor(self.id, binding, divisor);
+    for attr in V::attributes() {
+      gl::EnableVertexArrayAttrib(self.id, attr.location);
+      if attr.integer {
+        gl::VertexArrayAttribIFormat(
+          self.id,
+          attr.location,
+          attr.components,
+          attr.gl_type,
+          attr.offset as GLuint,
+        );
+      } else {
+        gl::VertexArrayAttribFormat(
+          self.id,
+          attr.location,
+          attr.components,
+          attr.gl_type,
+          attr.normalized as u8,
+          attr.offset as GLuint,
+        );
+      }
+      gl::VertexArrayAttribBinding(self.id, attr.location, binding);
+    }
+  }
+
+  /// Sources the attributes of `I` from `buffer`, one `I` per instance for `draw_instanced` calls.
+  pub unsafe fn set_instance_buffer<I: Vertex>(&self, buffer: &Buffer) {
+    self.attach::<I>(buffer, 1);
+  }
+
+  /// Attaches `indices` as the element array buffer of this vertex array.
+  pub unsafe fn set_index_buffer<T: Index>(&self, indices: &IndexBuffer<T>) {
+    gl::VertexArrayElementBuffer(self.id, indices.id());
+  }
+}
+
+impl Drop for VertexArray {
+  fn drop(&mut self) {
+    unsafe {
+      gl::DeleteVertexArrays(1, &self.id);
+    }
+  }
+}
+
+/// Restores the previously bound vertex array when dropped.
+#[must_use]
+pub struct VertexArrayBinding<'a> {
+  _vertex_array: &'a VertexArray,
+  previous: GLuint,
+}
+
+impl Drop for VertexArrayBinding<'_> {
+  fn drop(&mut self) {
+    unsafe {
+      gl::BindVertexArray(self.previous);
+    }
+  }
+}
+
+pub struct VertexArrayBuilder {
+  vertex_array: VertexArray,
+}
+
+impl VertexArrayBuilder {
+  /// Sources every attribute of `V` from `buffer`, which holds a tightly packed `[V]`.
+  pub unsafe fn vertex_buffer<V: Vertex>(self, buffer: &Buffer) -> Self {
+    self.vertex_array.attach::<V>(buffer, 0);
+    self
+  }
+
+  /// Sources every attribute of `I` from `buffer`, which holds one `I` per instance.
+  pub unsafe fn instance_buffer<I: Vertex>(self, buffer: &Buffer) -> Self {
+    self.vertex_array.set_instance_buffer::<I>(buffer);
+    self
+  }
+
+  pub unsafe fn index_buffer<T: Index>(self, indices: &IndexBuffer<T>) -> Self {
+    self.vertex_array.set_index_buffer(indices);
+    self
+  }
+
+  pub fn build(self) -> VertexArray {
+    self.vertex_array
+  }
+}
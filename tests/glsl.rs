@@ -0,0 +1,175 @@
+// Checks the shaders embedded by the examples without needing a GPU: every source must parse, the
+// fragment inputs must be written by the vertex stage, and every uniform set from Rust must exist, whether by name
+// or as a field of a struct deriving `Uniforms`.
+//
+// The examples embed their shaders with `include_str!("../shaders/...")` rather than as string literals, so the
+// sources checked here are exactly the ones they compile.
+
+use glsl::parser::Parse;
+use glsl::syntax::{
+  Declaration, ExternalDeclaration, StorageQualifier, TranslationUnit, TypeQualifier, TypeQualifierSpec, TypeSpecifier,
+};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+#[derive(Default)]
+struct Interface {
+  inputs: HashMap<String, TypeSpecifier>,
+  outputs: HashMap<String, TypeSpecifier>,
+  uniforms: BTreeSet<String>,
+}
+
+fn storage(qualifier: Option<&TypeQualifier>) -> Option<&StorageQualifier> {
+  qualifier?.qualifiers.0.iter().find_map(|q| match q {
+    TypeQualifierSpec::Storage(s) => Some(s),
+    _ => None,
+  })
+}
+
+fn parse_shader(path: &Path) -> Interface {
+  let code = fs::read_to_string(path).unwrap();
+  let unit = TranslationUnit::parse(code.as_str()).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+  let mut interface = Interface::default();
+  for decl in &(unit.0).0 {
+    match decl {
+      ExternalDeclaration::Declaration(Declaration::InitDeclaratorList(list)) => {
+        let names = list
+          .head
+          .name
+          .iter()
+          .chain(list.tail.iter().map(|t| &t.ident.ident))
+          .map(|ident| ident.0.clone());
+
+        let ty = &list.head.ty;
+        match storage(ty.qualifier.as_ref()) {
+          Some(StorageQualifier::In) => interface.inputs.extend(names.map(|n| (n, ty.ty.clone()))),
+          Some(StorageQualifier::Out) => interface.outputs.extend(names.map(|n| (n, ty.ty.clone()))),
+          Some(StorageQualifier::Uniform) => interface.uniforms.extend(names),
+          _ => {}
+        }
+      }
+      // Members of an unnamed uniform block are accessed like plain uniforms.
+      ExternalDeclaration::Declaration(Declaration::Block(block)) => {
+        let uniform = matches!(storage(Some(&block.qualifier)), Some(StorageQualifier::Uniform));
+        if uniform && block.identifier.is_none() {
+          for field in &block.fields {
+            interface
+              .uniforms
+              .extend(field.identifiers.0.iter().map(|i| i.ident.0.clone()));
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  interface
+}
+
+/// Shader files pulled in by an example through `include_str!("../shaders/...")`.
+fn included_shaders(example: &str) -> Vec<String> {
+  example
+    .split("include_str!(\"../shaders/")
+    .skip(1)
+    .map(|rest| rest[..rest.find('"').unwrap()].to_string())
+    .collect()
+}
+
+/// Uniform names passed as string literals to `set_uniform_value`.
+fn uniforms_set(example: &str) -> BTreeSet<String> {
+  example
+    .split("set_uniform_value(\"")
+    .skip(1)
+    .map(|rest| rest[..rest.find('"').unwrap()].to_string())
+    .collect()
+}
+
+/// Uniform names of the fields of structs deriving `Uniforms`, with `#[uniform(name = "...")]` overrides applied.
+/// Fields are expected one per line, the way rustfmt lays them out.
+fn uniforms_derived(example: &str) -> BTreeSet<String> {
+  let mut names = BTreeSet::new();
+  for rest in example.split("#[derive(").skip(1) {
+    let derives = &rest[..rest.find(')').unwrap()];
+    if !derives.split(',').any(|derive| derive.trim() == "Uniforms") {
+      continue;
+    }
+    let body = &rest[rest.find('{').unwrap() + 1..];
+    let body = &body[..body.find('}').unwrap()];
+
+    let mut rename = None;
+    for line in body.lines().map(str::trim).filter(|line| !line.starts_with("//")) {
+      if let Some(args) = line.strip_prefix("#[uniform(") {
+        if let Some(name) = args.split("name = \"").nth(1) {
+          rename = Some(name[..name.find('"').unwrap()].to_string());
+        }
+      } else if let Some((field, _)) = line.split_once(':') {
+        let field = field.split_whitespace().last().unwrap();
+        names.insert(rename.take().unwrap_or_else(|| field.to_string()));
+      }
+    }
+  }
+  names
+}
+
+#[test]
+fn all_shaders_parse() {
+  for entry in fs::read_dir("shaders").unwrap() {
+    parse_shader(&entry.unwrap().path());
+  }
+}
+
+#[test]
+fn examples_match_their_shaders() {
+  for entry in fs::read_dir("examples").unwrap() {
+    let path = entry.unwrap().path();
+    if path.extension().map_or(true, |ext| ext != "rs") {
+      continue;
+    }
+
+    let example = fs::read_to_string(&path).unwrap();
+    let shaders: Vec<(String, Interface)> = included_shaders(&example)
+      .into_iter()
+      .map(|name| {
+        let interface = parse_shader(&Path::new("shaders").join(&name));
+        (name, interface)
+      })
+      .collect();
+
+    for (vert_name, vert) in shaders.iter().filter(|(name, _)| name.ends_with(".vert")) {
+      for (frag_name, frag) in shaders.iter().filter(|(name, _)| name.ends_with(".frag")) {
+        for (name, ty) in &frag.inputs {
+          match vert.outputs.get(name) {
+            Some(out_ty) => assert_eq!(
+              out_ty,
+              ty,
+              "{}: `{}` has different types in {} and {}",
+              path.display(),
+              name,
+              vert_name,
+              frag_name
+            ),
+            None => panic!(
+              "{}: {} reads `{}` but {} doesn't write it",
+              path.display(),
+              frag_name,
+              name,
+              vert_name
+            ),
+          }
+        }
+      }
+    }
+
+    let declared: BTreeSet<&String> = shaders.iter().flat_map(|(_, s)| &s.uniforms).collect();
+    for name in uniforms_set(&example).into_iter().chain(uniforms_derived(&example)) {
+      assert!(
+        declared.contains(&name),
+        "{} sets uniform `{}` which none of its shaders declare",
+        path.display(),
+        name
+      );
+    }
+  }
+}
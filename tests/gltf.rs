@@ -0,0 +1,284 @@
+// Loads the glTF fixtures in `tests/fixtures`, whose buffers and images are embedded as data URIs in
+// `scene.gltf` and in the binary chunk of `scene.glb`, and checks the imported scene. `skinned.gltf` holds a strip
+// bent by a two-joint skin and an animation, and `morph.gltf` a quad with two animated morph targets.
+
+use learn_opengl::import::{AlphaMode, GltfError, GltfProjection, GltfScene};
+use learn_opengl::Interpolation;
+
+fn transform_point(m: [[f32; 4]; 4], p: [f32; 3]) -> [f32; 3] {
+  let mut out = [0.0; 3];
+  for (row, value) in out.iter_mut().enumerate() {
+    *value = m[0][row] * p[0] + m[1][row] * p[1] + m[2][row] * p[2] + m[3][row];
+  }
+  out
+}
+
+fn assert_close(a: [f32; 3], b: [f32; 3]) {
+  let d: f32 = (0..3).map(|k| (a[k] - b[k]).powi(2)).sum();
+  assert!(d < 1e-10, "{:?} != {:?}", a, b);
+}
+
+fn check_scene(scene: &GltfScene) {
+  let names: Vec<_> = scene.meshes.iter().map(|m| m.name.as_deref()).collect();
+  assert_eq!(names, [Some("quad"), Some("strip")]);
+
+  let quad = &scene.meshes[0].primitives[0];
+  assert_eq!(quad.material, Some(0));
+  assert_eq!(quad.mesh.indices, [0, 1, 2, 0, 2, 3]);
+  assert_eq!(quad.mesh.normals, [[0.0, 0.0, 1.0]; 4]);
+  // glTF's `v` points down, the crate's up.
+  assert_eq!(quad.mesh.uvs, [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+
+  let strip = &scene.meshes[1].primitives[0];
+  assert_eq!(strip.material, None);
+  assert_eq!(strip.mesh.triangle_count(), 2);
+  assert!(strip.mesh.normals.iter().all(|&n| n == [0.0, 0.0, 1.0]));
+  assert!(strip.mesh.uvs.iter().all(|&uv| uv == [0.0, 0.0]));
+
+  let image = &scene.images[0];
+  assert_eq!((image.width, image.height, image.format), (2, 2, gl::RGBA));
+  // The bottom row (blue, white) comes first.
+  assert_eq!(&image.pixels[..8], &[0, 0, 255, 255, 255, 255, 255, 255]);
+  assert_eq!(&image.pixels[8..], &[255, 0, 0, 255, 0, 255, 0, 255]);
+
+  let texture = &scene.textures[0];
+  assert_eq!(texture.image, 0);
+  assert_eq!((texture.mag_filter, texture.min_filter), (gl::NEAREST, gl::LINEAR));
+  assert_eq!(
+    (texture.wrap_s, texture.wrap_t),
+    (gl::CLAMP_TO_EDGE, gl::MIRRORED_REPEAT)
+  );
+}
+
+#[test]
+fn gltf_with_data_uris() {
+  check_scene(&GltfScene::load("tests/fixtures/scene.gltf").unwrap());
+}
+
+#[test]
+fn glb_matches_gltf() {
+  let glb = GltfScene::load("tests/fixtures/scene.glb").unwrap();
+  check_scene(&glb);
+
+  let gltf = GltfScene::load("tests/fixtures/scene.gltf").unwrap();
+  assert_eq!(glb.nodes, gltf.nodes);
+  assert_eq!(glb.materials, gltf.materials);
+  assert_eq!(glb.cameras, gltf.cameras);
+}
+
+#[test]
+fn materials_and_defaults() {
+  let scene = GltfScene::load("tests/fixtures/scene.gltf").unwrap();
+  let (painted, plain) = (&scene.materials[0], &scene.materials[1]);
+
+  assert_eq!(painted.name.as_deref(), Some("painted"));
+  assert_eq!(painted.base_color, [1.0, 0.5, 0.25, 1.0]);
+  assert_eq!(painted.base_color_texture, Some(0));
+  assert_eq!((painted.metallic, painted.roughness), (0.25, 0.75));
+  assert_eq!((painted.normal_texture, painted.normal_scale), (Some(0), 0.5));
+  assert_eq!(painted.emissive, [0.1, 0.2, 0.3]);
+  assert_eq!(painted.alpha_mode, AlphaMode::Mask { cutoff: 0.3 });
+  assert!(painted.double_sided);
+
+  assert_eq!(plain.base_color, [1.0; 4]);
+  assert_eq!((plain.metallic, plain.roughness), (1.0, 1.0));
+  assert_eq!(plain.base_color_texture, None);
+  assert_eq!(plain.occlusion_strength, 1.0);
+  assert_eq!(plain.alpha_mode, AlphaMode::Opaque);
+  assert!(!plain.double_sided);
+}
+
+#[test]
+fn cameras() {
+  let scene = GltfScene::load("tests/fixtures/scene.gltf").unwrap();
+  assert_eq!(
+    scene.cameras[0].projection,
+    GltfProjection::Perspective {
+      yfov: 0.8,
+      aspect_ratio: Some(1.5),
+      znear: 0.1,
+      zfar: Some(100.0),
+    }
+  );
+  assert_eq!(
+    scene.cameras[1].projection,
+    GltfProjection::Orthographic {
+      xmag: 2.0,
+      ymag: 1.0,
+      znear: 0.5,
+      zfar: 10.0,
+    }
+  );
+}
+
+#[test]
+fn node_hierarchy_and_transforms() {
+  let scene = GltfScene::load("tests/fixtures/scene.gltf").unwrap();
+  assert_eq!(scene.roots, [0, 3]);
+
+  let root = &scene.nodes[0];
+  assert_eq!(root.children, [1, 2]);
+  assert_eq!(root.parent, None);
+  assert_eq!(scene.nodes[1].parent, Some(0));
+  assert_eq!((scene.nodes[1].mesh, scene.nodes[2].camera), (Some(0), Some(0)));
+  assert_eq!((scene.nodes[3].mesh, scene.nodes[3].camera), (Some(1), Some(1)));
+
+  // The quad is scaled by 2 and then moved with its parent.
+  assert_close(
+    transform_point(scene.world_transform(1), [1.0, 1.0, 0.0]),
+    [3.0, 4.0, 3.0],
+  );
+  // The camera is turned a quarter around y, so it looks down -x instead of -z.
+  assert_close(
+    transform_point(scene.world_transform(2), [0.0, 0.0, -1.0]),
+    [0.0, 2.0, 3.0],
+  );
+  assert_close(
+    transform_point(scene.world_transform(3), [0.0, 1.0, 0.0]),
+    [5.0, 1.0, 0.0],
+  );
+}
+
+#[test]
+fn rest_pose_matches_node_transforms() {
+  let scene = GltfScene::load("tests/fixtures/scene.gltf").unwrap();
+  let world = scene.world_transforms(&scene.rest_pose());
+  for (node, transform) in world.iter().enumerate() {
+    for (column, expected) in transform.iter().zip(&scene.world_transform(node)) {
+      assert_close(
+        [column[0], column[1], column[2]],
+        [expected[0], expected[1], expected[2]],
+      );
+    }
+  }
+}
+
+#[test]
+fn skins_and_joint_attributes() {
+  let scene = GltfScene::load("tests/fixtures/skinned.gltf").unwrap();
+  assert_eq!(scene.nodes[2].skin, Some(0));
+  assert_eq!(scene.nodes[1].pose.translation, [0.0, 1.0, 0.0]);
+
+  let skin = &scene.skins[0];
+  assert_eq!(skin.name.as_deref(), Some("arm"));
+  assert_eq!(skin.joints, [0, 1]);
+  assert_eq!(skin.inverse_bind_matrices[1][3], [0.0, -1.0, 0.0, 1.0]);
+
+  let mesh = &scene.meshes[0].primitives[0].mesh;
+  assert_eq!(mesh.joints, [[0, 1, 0, 0]; 6]);
+  assert_eq!(mesh.weights[0], [1.0, 0.0, 0.0, 0.0]);
+  assert_eq!(mesh.weights[2], [0.5, 0.5, 0.0, 0.0]);
+  assert_eq!(mesh.weights[5], [0.0, 1.0, 0.0, 0.0]);
+
+  // In the bind pose skinning leaves the mesh where it is.
+  let joint_matrices = skin.joint_matrices(&scene.world_transforms(&scene.rest_pose()));
+  let skinned = mesh.skinned(&joint_matrices);
+  for (a, &b) in skinned.positions.iter().zip(&mesh.positions) {
+    assert_close(*a, b);
+  }
+}
+
+#[test]
+fn animations_pose_the_skin() {
+  let scene = GltfScene::load("tests/fixtures/skinned.gltf").unwrap();
+  let clip = &scene.animations[0];
+  assert_eq!(clip.name.as_deref(), Some("bend"));
+  assert_eq!(clip.duration(), 1.0);
+  let interpolations: Vec<_> = clip.channels.iter().map(|c| c.interpolation).collect();
+  assert_eq!(interpolations, [Interpolation::Linear, Interpolation::Step]);
+
+  let mut pose = scene.rest_pose();
+  clip.apply(1.0, &mut pose);
+  let joint_matrices = scene.skins[0].joint_matrices(&scene.world_transforms(&pose));
+  let skinned = scene.meshes[0].primitives[0].mesh.skinned(&joint_matrices);
+
+  // The root steps one unit along x and the tip turns a quarter around z about the middle of the strip.
+  assert_close(skinned.positions[0], [0.5, 0.0, 0.0]);
+  assert_close(skinned.positions[5], [0.0, 1.5, 0.0]);
+  assert_close(skinned.normals[5], [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn morph_targets_and_weights() {
+  let scene = GltfScene::load("tests/fixtures/morph.gltf").unwrap();
+  let mesh = &scene.meshes[0].primitives[0].mesh;
+  assert_eq!(mesh.morph_targets.len(), 2);
+  assert_eq!(mesh.morph_targets[0].positions[2], [0.0, 0.0, 1.0]);
+  // The first target has no normal offsets.
+  assert_eq!(mesh.morph_targets[0].normals, [[0.0; 3]; 4]);
+  assert_eq!(mesh.morph_targets[1].normals, [[1.0, 0.0, -1.0]; 4]);
+
+  assert_eq!(scene.meshes[0].weights, [0.5, 0.0]);
+  assert_eq!(scene.nodes[1].weights.as_deref(), Some(&[0.0, 1.0][..]));
+  assert_eq!(scene.rest_weights(), [vec![0.5, 0.0], vec![0.0, 1.0]]);
+}
+
+#[test]
+fn animations_weight_the_targets() {
+  let scene = GltfScene::load("tests/fixtures/morph.gltf").unwrap();
+  let clip = &scene.animations[0];
+  assert_eq!(clip.name.as_deref(), Some("smile"));
+
+  let mut weights = scene.rest_weights();
+  clip.apply_weights(0.5, &mut weights);
+  assert_eq!(weights, [vec![0.5, 0.25], vec![0.0, 1.0]]);
+
+  let morphed = scene.meshes[0].primitives[0].mesh.morphed(&weights[0]);
+  assert_close(morphed.positions[2], [1.25, 1.0, 0.5]);
+  let length = (0.25f32 * 0.25 + 0.75 * 0.75).sqrt();
+  assert_close(morphed.normals[2], [0.25 / length, 0.0, 0.75 / length]);
+}
+
+#[test]
+fn malformed_skins_and_hierarchies() {
+  let fixture = std::fs::read_to_string("tests/fixtures/skinned.gltf").unwrap();
+  let unsupported = |name: &str, replacements: &[(&str, &str)]| {
+    let mut json = fixture.clone();
+    for (from, to) in replacements {
+      assert!(json.contains(from), "{} isn't in the fixture", from);
+      json = json.replacen(from, to, 1);
+    }
+    let path = std::env::temp_dir().join(format!("learn-opengl-{}-{}.gltf", std::process::id(), name));
+    std::fs::write(&path, json).unwrap();
+    let result = GltfScene::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    match result {
+      Err(GltfError::Unsupported(message)) => message,
+      other => panic!("expected {} to be unsupported, got {:?}", name, other),
+    }
+  };
+  let tip_children = ("\"name\": \"tip\",", "\"name\": \"tip\", \"children\": [0],");
+  let one_matrix = (
+    "\"count\": 2,\n      \"type\": \"MAT4\"",
+    "\"count\": 1, \"type\": \"MAT4\"",
+  );
+  let one_joint = ("\"joints\": [\n        0,\n        1\n      ]", "\"joints\": [0]");
+
+  // The tip becomes the parent of the root as well as its child.
+  assert_eq!(unsupported("cycle", &[tip_children]), "node 0 is its own ancestor");
+  assert_eq!(
+    unsupported("matrices", &[one_matrix]),
+    "skin 0 has 2 joints but 1 inverse bind matrices"
+  );
+  // The strip's vertices use the tip, which the skin no longer has.
+  assert_eq!(
+    unsupported("joints", &[one_joint, one_matrix]),
+    "mesh 0 uses joint 1 of skin 0, which has 1 joints"
+  );
+}
+
+#[test]
+#[should_panic(expected = "cycle")]
+fn world_transform_of_a_cycle_panics() {
+  let mut scene = GltfScene::load("tests/fixtures/skinned.gltf").unwrap();
+  scene.nodes[0].parent = Some(1);
+  scene.world_transform(1);
+}
+
+#[test]
+fn missing_files_are_import_errors() {
+  match GltfScene::load("tests/fixtures/missing.gltf") {
+    Err(GltfError::Import(_)) => {}
+    other => panic!("expected an import error, got {:?}", other),
+  }
+}
@@ -0,0 +1,166 @@
+// Converts the fixtures in `tests/fixtures` to mesh caches and checks that they read back exactly like the source
+// loaders' meshes, and that damaged caches are rejected.
+
+use learn_opengl::import::{load_ply, load_stl, GltfScene, ObjModel};
+use learn_opengl::mesh_cache::VERSION;
+use learn_opengl::{Mesh, MeshCache, MeshCacheError};
+use std::fs;
+use std::path::PathBuf;
+
+/// A path in the system temporary directory, unique to this test process.
+fn temp_path(name: &str) -> PathBuf {
+  std::env::temp_dir().join(format!("learn-opengl-{}-{}", std::process::id(), name))
+}
+
+fn check_round_trip(source: &str, meshes: &[Mesh]) {
+  let path = temp_path(&format!("{}.meshcache", source.replace('/', "_")));
+  MeshCache::convert(source, &path).unwrap();
+  let cache = MeshCache::open(&path).unwrap();
+
+  assert_eq!(cache.len(), meshes.len(), "{}", source);
+  for (cached, mesh) in cache.meshes().zip(meshes) {
+    assert_eq!(&cached.to_mesh(), mesh, "{}", source);
+    assert_eq!(cached.bounds, mesh.bounds(), "{}", source);
+    assert_eq!(cached.vertices, &mesh.vertices()[..], "{}", source);
+  }
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn obj_round_trip() {
+  let model = ObjModel::load("tests/fixtures/cube.obj").unwrap();
+  let meshes: Vec<Mesh> = model.meshes.into_iter().map(|m| m.mesh).collect();
+  check_round_trip("tests/fixtures/cube.obj", &meshes);
+}
+
+#[test]
+fn gltf_round_trip() {
+  for source in &[
+    "tests/fixtures/scene.gltf",
+    "tests/fixtures/scene.glb",
+    "tests/fixtures/skinned.gltf",
+    "tests/fixtures/morph.gltf",
+  ] {
+    let scene = GltfScene::load(source).unwrap();
+    let meshes: Vec<Mesh> = scene
+      .meshes
+      .into_iter()
+      .flat_map(|m| m.primitives)
+      .map(|p| p.mesh)
+      .collect();
+    check_round_trip(source, &meshes);
+  }
+}
+
+#[test]
+fn ply_and_stl_round_trip() {
+  let ply = load_ply("tests/fixtures/quad_binary_le.ply").unwrap();
+  assert!(!ply.colors.is_empty());
+  check_round_trip("tests/fixtures/quad_binary_le.ply", &[ply]);
+
+  let stl = load_stl("tests/fixtures/tetrahedron_binary.stl").unwrap();
+  check_round_trip("tests/fixtures/tetrahedron_binary.stl", &[stl]);
+}
+
+#[test]
+fn empty_meshes() {
+  let path = temp_path("empty.meshcache");
+  MeshCache::write(&path, &[Mesh::default(), Mesh::cube(1.0)]).unwrap();
+  let cache = MeshCache::open(&path).unwrap();
+  assert_eq!(cache.mesh(0).to_mesh(), Mesh::default());
+  assert_eq!(cache.mesh(0).bounds, None);
+  assert_eq!(cache.mesh(1).to_mesh(), Mesh::cube(1.0));
+
+  MeshCache::write(&path, &[]).unwrap();
+  assert!(MeshCache::open(&path).unwrap().is_empty());
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn damaged_caches_are_rejected() {
+  let path = temp_path("damaged.meshcache");
+  MeshCache::write(&path, &[Mesh::cube(1.0)]).unwrap();
+  let good = fs::read(&path).unwrap();
+
+  let open_with = |bytes: &[u8]| {
+    fs::write(&path, bytes).unwrap();
+    MeshCache::open(&path).err()
+  };
+
+  let mut flipped = good.clone();
+  *flipped.last_mut().unwrap() ^= 1;
+  assert!(matches!(open_with(&flipped), Some(MeshCacheError::Checksum { .. })));
+
+  assert!(matches!(
+    open_with(&good[..good.len() - 4]),
+    Some(MeshCacheError::Checksum { .. })
+  ));
+
+  // The mesh count is part of the checksum too.
+  let mut fewer = good.clone();
+  fewer[12..16].copy_from_slice(&0u32.to_le_bytes());
+  assert!(matches!(open_with(&fewer), Some(MeshCacheError::Checksum { .. })));
+
+  let mut newer = good.clone();
+  newer[8..12].copy_from_slice(&(VERSION + 1).to_le_bytes());
+  assert!(matches!(open_with(&newer), Some(MeshCacheError::Version(v)) if v == VERSION + 1));
+
+  assert!(matches!(open_with(b"LOGL"), Some(MeshCacheError::NotACache)));
+  assert!(matches!(open_with(b"solid cube"), Some(MeshCacheError::NotACache)));
+
+  fs::remove_file(&path).unwrap();
+  assert!(matches!(MeshCache::open(&path), Err(MeshCacheError::Io { .. })));
+}
+
+#[test]
+fn rewriting_an_open_cache() {
+  let path = temp_path("rewritten.meshcache");
+  MeshCache::write(&path, &[Mesh::cube(1.0)]).unwrap();
+  let old = MeshCache::open(&path).unwrap();
+
+  let sphere = Mesh::uv_sphere(1.0, 8, 4);
+  MeshCache::write(&path, &[sphere.clone(), sphere.clone()]).unwrap();
+  // The open cache still sees the file it mapped, and no temporary file is left behind.
+  assert_eq!(old.len(), 1);
+  assert_eq!(old.mesh(0).to_mesh(), Mesh::cube(1.0));
+  assert_eq!(MeshCache::open(&path).unwrap().len(), 2);
+  let prefix = path.file_name().unwrap().to_str().unwrap();
+  let leftovers = fs::read_dir(path.parent().unwrap())
+    .unwrap()
+    .filter(|entry| {
+      let name = entry.as_ref().unwrap().file_name();
+      name
+        .to_str()
+        .is_some_and(|name| name.starts_with(prefix) && name != prefix)
+    })
+    .count();
+  assert_eq!(leftovers, 0);
+
+  drop(old);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn open_or_convert() {
+  let path = temp_path("open_or_convert.meshcache");
+  let _ = fs::remove_file(&path);
+  let source = "tests/fixtures/tetrahedron.stl";
+
+  // A missing cache is converted.
+  let cache = MeshCache::open_or_convert(source, &path).unwrap();
+  assert_eq!(cache.mesh(0).to_mesh(), load_stl(source).unwrap());
+  drop(cache);
+
+  // A broken one too, even though it's newer than the source.
+  fs::write(&path, b"LOGLMESH broken").unwrap();
+  let cache = MeshCache::open_or_convert(source, &path).unwrap();
+  assert_eq!(cache.len(), 1);
+  drop(cache);
+
+  fs::remove_file(&path).unwrap();
+
+  assert!(matches!(
+    MeshCache::open_or_convert("tests/fixtures/cube.mtl", &path),
+    Err(MeshCacheError::Import(_))
+  ));
+}
@@ -0,0 +1,171 @@
+// Loads the OBJ fixtures in `tests/fixtures` and checks the resulting meshes and materials.
+
+use learn_opengl::import::{ObjError, ObjModel};
+use learn_opengl::Mesh;
+use std::path::Path;
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [
+    a[1] * b[2] - a[2] * b[1],
+    a[2] * b[0] - a[0] * b[2],
+    a[0] * b[1] - a[1] * b[0],
+  ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+  a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn assert_wound_like_normals(mesh: &Mesh) {
+  for [a, b, c] in mesh.triangles() {
+    let [pa, pb, pc] = [a, b, c].map(|i| mesh.positions[i as usize]);
+    let face = cross(sub(pb, pa), sub(pc, pa));
+    for &i in &[a, b, c] {
+      assert!(
+        dot(face, mesh.normals[i as usize]) > 0.0,
+        "triangle {:?} is flipped",
+        [a, b, c]
+      );
+    }
+  }
+}
+
+fn parse_error(path: &str) -> (usize, String) {
+  match ObjModel::load(path) {
+    Err(ObjError::Parse { line, message, .. }) => (line, message),
+    other => panic!("expected a parse error, got {:?}", other),
+  }
+}
+
+#[test]
+fn quads_are_grouped_by_material() {
+  let model = ObjModel::load("tests/fixtures/cube.obj").unwrap();
+
+  let names: Vec<_> = model.materials.iter().map(|m| m.name.as_str()).collect();
+  assert_eq!(names, ["crate", "paint"]);
+
+  assert_eq!(model.meshes.len(), 2);
+  let (sides, caps) = (&model.meshes[0], &model.meshes[1]);
+  assert_eq!(sides.material, Some(0));
+  assert_eq!(caps.material, Some(1));
+
+  // Every quad has its own normal, so no vertices are shared between faces.
+  assert_eq!((sides.mesh.vertex_count(), sides.mesh.triangle_count()), (16, 8));
+  assert_eq!((caps.mesh.vertex_count(), caps.mesh.triangle_count()), (8, 4));
+  assert_wound_like_normals(&sides.mesh);
+  assert_wound_like_normals(&caps.mesh);
+
+  for uv in &sides.mesh.uvs {
+    assert!(uv.iter().all(|&c| c == 0.0 || c == 1.0));
+  }
+}
+
+#[test]
+fn mtl_values_and_texture_paths() {
+  let model = ObjModel::load("tests/fixtures/cube.obj").unwrap();
+  let (crate_material, paint) = (&model.materials[0], &model.materials[1]);
+
+  assert_eq!(crate_material.ambient, [0.1; 3]);
+  assert_eq!(crate_material.specular, [0.5; 3]);
+  assert_eq!(crate_material.shininess, 32.0);
+  assert_eq!(
+    crate_material.diffuse_map.as_deref(),
+    Some(Path::new("tests/fixtures/textures/container.jpg"))
+  );
+
+  assert_eq!(paint.diffuse, [0.8, 0.1, 0.1]);
+  assert_eq!(paint.opacity, 0.75);
+  assert_eq!(paint.specular, [1.0; 3], "missing values keep the MTL defaults");
+  assert_eq!(
+    paint.normal_map.as_deref(),
+    Some(Path::new("tests/fixtures/normals.png"))
+  );
+  assert_eq!(paint.diffuse_map, None);
+}
+
+#[test]
+fn negative_indices_ngons_and_missing_attributes() {
+  let model = ObjModel::load("tests/fixtures/relative.obj").unwrap();
+  assert!(model.materials.is_empty());
+  assert_eq!(model.meshes.len(), 1);
+
+  let mesh = &model.meshes[0].mesh;
+  assert_eq!(model.meshes[0].material, None);
+  assert_eq!(mesh.triangle_count(), 4 + 1);
+
+  // Without a smoothing group the missing normals are flat.
+  for n in &mesh.normals {
+    assert_eq!(*n, [0.0, 0.0, 1.0]);
+  }
+  assert_wound_like_normals(mesh);
+
+  // The L shape has an area of 3, which a fan from its first corner would overshoot.
+  let l_area: f32 = mesh
+    .triangles()
+    .map(|t| t.map(|i| mesh.positions[i as usize]))
+    .filter(|[a, _, _]| a[2] == 0.0)
+    .map(|[a, b, c]| cross(sub(b, a), sub(c, a))[2] / 2.0)
+    .sum();
+  assert!((l_area - 3.0).abs() < 1e-6);
+
+  for (p, uv) in mesh.positions.iter().zip(&mesh.uvs) {
+    let expected = if p[2] == 1.0 { [p[0], p[1]] } else { [0.0, 0.0] };
+    assert_eq!(*uv, expected);
+  }
+}
+
+#[test]
+fn smoothing_groups_get_smooth_normals() {
+  let model = ObjModel::load("tests/fixtures/smooth.obj").unwrap();
+  let mesh = &model.meshes[0].mesh;
+
+  assert_eq!(mesh.vertex_count(), 5);
+  let apex = mesh.positions.iter().position(|p| p[1] == 1.0).unwrap();
+  let n = mesh.normals[apex];
+  assert!(n[0].abs() < 1e-6 && n[2].abs() < 1e-6 && (n[1] - 1.0).abs() < 1e-6);
+  assert_wound_like_normals(mesh);
+}
+
+#[test]
+fn vertex_colors() {
+  let model = ObjModel::load("tests/fixtures/colors.obj").unwrap();
+  let mesh = &model.meshes[0].mesh;
+  let color_at = |position: [f32; 3]| mesh.colors[mesh.positions.iter().position(|&p| p == position).unwrap()];
+  assert_eq!(mesh.colors.len(), 4);
+  assert_eq!(color_at([0.0, 0.0, 0.0]), [1.0, 0.0, 0.0, 1.0]);
+  assert_eq!(color_at([1.0, 1.0, 0.0]), [0.0, 0.0, 1.0, 0.5]);
+
+  // Files without colors leave them empty.
+  let model = ObjModel::load("tests/fixtures/smooth.obj").unwrap();
+  assert!(model.meshes[0].mesh.colors.is_empty());
+}
+
+#[test]
+fn errors_point_at_the_line() {
+  let (line, message) = parse_error("tests/fixtures/bad_index.obj");
+  assert_eq!(line, 5);
+  assert!(message.contains("position index 4"), "{}", message);
+
+  let (line, message) = parse_error("tests/fixtures/bad_number.obj");
+  assert_eq!(line, 2);
+  assert!(message.contains("`zero`"), "{}", message);
+
+  let error = ObjModel::load("tests/fixtures/bad_index.obj").unwrap_err();
+  assert!(
+    error.to_string().starts_with("tests/fixtures/bad_index.obj:5: "),
+    "{}",
+    error
+  );
+}
+
+#[test]
+fn missing_files_are_io_errors() {
+  match ObjModel::load("tests/fixtures/missing_mtl.obj") {
+    Err(ObjError::Io { path, .. }) => assert_eq!(path, Path::new("tests/fixtures/missing.mtl")),
+    other => panic!("expected an IO error, got {:?}", other),
+  }
+}
@@ -0,0 +1,123 @@
+// Loads the same colored quad from the ASCII and both binary PLY fixtures in `tests/fixtures`.
+
+use learn_opengl::import::{load_ply, parse_ply, PlyError};
+use learn_opengl::Mesh;
+
+fn check_quad(mesh: &Mesh) {
+  // Generating normals may renumber the vertices, but each keeps its color.
+  let expected = [
+    ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0, 1.0]),
+    ([1.0, 0.0, 0.0], [0.0, 1.0, 0.0, 1.0]),
+    ([1.0, 1.0, 0.0], [0.0, 0.0, 1.0, 1.0]),
+    ([0.0, 1.0, 0.0], [1.0, 1.0, 1.0, 1.0]),
+  ];
+  assert_eq!(mesh.vertex_count(), 4);
+  for (position, color) in mesh.positions.iter().zip(&mesh.colors) {
+    assert!(
+      expected.contains(&(*position, *color)),
+      "{:?} has color {:?}",
+      position,
+      color
+    );
+  }
+  assert_eq!(mesh.triangle_count(), 2);
+  // The file has no normals, so they're generated facing the counter-clockwise side.
+  for normal in &mesh.normals {
+    assert!((normal[2] - 1.0).abs() < 1e-6, "{:?}", normal);
+  }
+}
+
+#[test]
+fn ascii() {
+  check_quad(&load_ply("tests/fixtures/quad_ascii.ply").unwrap());
+}
+
+#[test]
+fn binary_little_endian() {
+  check_quad(&load_ply("tests/fixtures/quad_binary_le.ply").unwrap());
+}
+
+#[test]
+fn binary_big_endian() {
+  check_quad(&load_ply("tests/fixtures/quad_binary_be.ply").unwrap());
+}
+
+#[test]
+fn normals_and_float_colors() {
+  let data = b"ply
+format ascii 1.0
+element vertex 3
+property double x
+property double y
+property double z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+property float red
+property float green
+property float blue
+property float alpha
+element face 1
+property list uchar uint vertex_index
+end_header
+0 0 0 0 0 -1 0 0 0.5 0.5 0.5 0.25
+1 0 0 0 0 -1 1 0 0.5 0.5 0.5 0.25
+0 1 0 0 0 -1 0 1 0.5 0.5 0.5 0.25
+3 0 1 2
+";
+  let mesh = parse_ply(data).unwrap();
+  assert_eq!(mesh.indices, [0, 1, 2]);
+  assert_eq!(mesh.normals, [[0.0, 0.0, -1.0]; 3]);
+  assert_eq!(mesh.uvs, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+  assert_eq!(mesh.colors, [[0.5, 0.5, 0.5, 0.25]; 3]);
+}
+
+#[test]
+fn errors() {
+  let invalid = |data: &[u8]| match parse_ply(data) {
+    Err(PlyError::Invalid(message)) => message,
+    other => panic!("expected an error, got {:?}", other),
+  };
+  assert_eq!(invalid(b"obj\n"), "missing the `ply` magic number");
+  assert_eq!(invalid(b"ply\nformat ascii 1.0\n"), "the header has no `end_header`");
+  assert_eq!(
+    invalid(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n"),
+    "unknown property type `half`"
+  );
+  assert_eq!(
+    invalid(
+      b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\n\
+        end_header\n0 0\n"
+    ),
+    "the data ends early"
+  );
+  assert_eq!(
+    invalid(
+      b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\n\
+        element face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n3 0 1 2\n"
+    ),
+    "face index 1 out of range for 1 vertices"
+  );
+  assert!(matches!(load_ply("tests/fixtures/missing.ply"), Err(PlyError::Io(_))));
+}
+
+#[test]
+fn non_finite_positions() {
+  let with_vertex = |vertex: &str| {
+    format!(
+      "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+       element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+       0 0 0\n{}\n0 1 0\n3 0 1 2\n",
+      vertex
+    )
+  };
+  assert!(parse_ply(with_vertex("1 0 0").as_bytes()).is_ok());
+  for vertex in &["nan 0 0", "1 inf 0", "1 0 -inf"] {
+    match parse_ply(with_vertex(vertex).as_bytes()) {
+      Err(PlyError::Invalid(message)) => assert_eq!(message, "vertex 1 has a non-finite position"),
+      other => panic!("expected an error for `{}`, got {:?}", vertex, other),
+    }
+  }
+}
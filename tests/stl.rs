@@ -0,0 +1,104 @@
+// Loads the tetrahedron STL fixtures in `tests/fixtures`, in the ASCII and the binary format.
+
+use learn_opengl::import::{load_stl, parse_stl, StlError};
+use learn_opengl::Mesh;
+
+fn check_tetrahedron(mesh: &Mesh) {
+  // Every facet keeps its own three vertices, since their normals differ.
+  assert_eq!(mesh.triangle_count(), 4);
+  assert_eq!(mesh.vertex_count(), 12);
+  for [a, b, c] in mesh.triangles() {
+    let normal = mesh.normals[a as usize];
+    assert_eq!(mesh.normals[b as usize], normal);
+    assert_eq!(mesh.normals[c as usize], normal);
+    // Every normal points away from the inside.
+    let center = [0.25; 3];
+    let out: f32 = (0..3)
+      .map(|k| normal[k] * (mesh.positions[a as usize][k] - center[k]))
+      .sum();
+    assert!(out > 0.0, "facet {:?} faces inwards", [a, b, c]);
+  }
+}
+
+#[test]
+fn ascii() {
+  let mesh = load_stl("tests/fixtures/tetrahedron.stl").unwrap();
+  check_tetrahedron(&mesh);
+  assert!(mesh.colors.is_empty());
+}
+
+#[test]
+fn binary_with_colors() {
+  let mesh = load_stl("tests/fixtures/tetrahedron_binary.stl").unwrap();
+  check_tetrahedron(&mesh);
+
+  // The first facet is red, the others have no color and are white.
+  let red = mesh.colors.iter().filter(|&&c| c == [1.0, 0.0, 0.0, 1.0]).count();
+  let white = mesh.colors.iter().filter(|&&c| c == [1.0; 4]).count();
+  assert_eq!((red, white), (3, 9));
+}
+
+#[test]
+fn errors() {
+  let invalid = |data: &[u8]| match parse_stl(data) {
+    Err(StlError::Invalid(message)) => message,
+    other => panic!("expected an error, got {:?}", other),
+  };
+  assert_eq!(invalid(b"not an stl file"), "neither ASCII nor binary STL");
+  assert_eq!(
+    invalid(b"solid x\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\nendfacet\n"),
+    "a facet has 2 vertices instead of 3"
+  );
+  assert_eq!(
+    invalid(b"solid x\nfacet normal 0 0 one\n"),
+    "invalid number `one` in a normal"
+  );
+  assert!(matches!(load_stl("tests/fixtures/missing.stl"), Err(StlError::Io(_))));
+}
+
+#[test]
+fn non_finite_positions() {
+  // Two binary triangles, the second with a NaN in its last vertex.
+  let mut data = vec![0; 80];
+  data.extend_from_slice(&2u32.to_le_bytes());
+  for last in &[1.0, f32::NAN] {
+    let floats = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, *last];
+    data.extend(floats.iter().flat_map(|f| f.to_le_bytes()));
+    data.extend_from_slice(&[0, 0]);
+  }
+  match parse_stl(&data) {
+    Err(StlError::Invalid(message)) => assert_eq!(message, "triangle 1 has a non-finite vertex"),
+    other => panic!("expected an error, got {:?}", other),
+  }
+
+  let ascii =
+    b"solid x\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex inf 0 0\nvertex 0 1 0\nendloop\nendfacet\n";
+  assert!(matches!(parse_stl(ascii), Err(StlError::Invalid(_))));
+}
+
+#[test]
+fn non_finite_normals_are_recomputed() {
+  let mut data = vec![0; 80];
+  data.extend_from_slice(&1u32.to_le_bytes());
+  let floats = [
+    f32::NAN,
+    f32::INFINITY,
+    0.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+    0.0,
+    0.0,
+    0.0,
+    1.0,
+    0.0,
+  ];
+  data.extend(floats.iter().flat_map(|f| f.to_le_bytes()));
+  data.extend_from_slice(&[0, 0]);
+  assert_eq!(parse_stl(&data).unwrap().normals, [[0.0, 0.0, 1.0]; 3]);
+
+  let ascii =
+    b"solid x\nfacet normal nan 0 -inf\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\n";
+  assert_eq!(parse_stl(ascii).unwrap().normals, [[0.0, 0.0, 1.0]; 3]);
+}